@@ -0,0 +1,43 @@
+//! `to_vulkan_vertex_input` の結果をashの型にする (`ash` feature)
+
+#[cfg(feature = "ash")]
+use meshguard_pack::{to_vulkan_vertex_input, PackedMesh};
+
+/// locationとoffsetは `to_vulkan_vertex_input` と同じ
+#[cfg(feature = "ash")]
+pub fn to_ash_vertex_input(mesh: &PackedMesh, binding: u32) -> Vec<ash::vk::VertexInputAttributeDescription> {
+    to_vulkan_vertex_input(mesh).into_iter().map(|d| ash::vk::VertexInputAttributeDescription {
+        location: d.location,
+        binding,
+        format: ash::vk::Format::from_raw(d.format.as_raw()),
+        offset: d.offset,
+    }).collect()
+}
+
+/// 頂点ごとに進む1本のバインディング
+#[cfg(feature = "ash")]
+pub fn to_ash_vertex_binding(mesh: &PackedMesh, binding: u32) -> ash::vk::VertexInputBindingDescription {
+    ash::vk::VertexInputBindingDescription {
+        binding,
+        stride: mesh.layout.stride as u32,
+        input_rate: ash::vk::VertexInputRate::VERTEX,
+    }
+}
+
+#[cfg(all(test, feature = "ash"))]
+mod tests {
+    use super::*;
+    use meshguard_pack::pack_interleave_permute;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn ash_formats() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let mesh = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&[[0.0,0.0,1.0]; 2]), &quantize_uvs(&[[0.25,0.5]; 2]), None, 1).unwrap();
+        let attrs = to_ash_vertex_input(&mesh, 2);
+        assert!(attrs[0].format == ash::vk::Format::R16G16B16_SINT);
+        assert!(attrs[1].format == ash::vk::Format::R16G16_UNORM);
+        assert!(attrs.iter().all(|a| a.binding == 2));
+        assert_eq!(to_ash_vertex_binding(&mesh, 2).stride, 14);
+    }
+}
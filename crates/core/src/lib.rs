@@ -0,0 +1,125 @@
+//! meshguardの各crateで共有する型
+
+use std::fmt;
+use std::io;
+
+/// ストリップを区切るプリミティブリスタート値 (ストリップ生成とパッカーで共有する)
+pub const STRIP_RESTART_INDEX: u32 = u32::MAX;
+
+/// meshguard共通のエラー
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MeshGuardError {
+    /// 属性の要素数が頂点数と合わない
+    LengthMismatch { attribute: &'static str, expected: usize, got: usize },
+    /// 頂点が1つもない
+    EmptyMesh,
+    /// インデックスが頂点数を超えている
+    /// positionはインデックスバッファ (や置換表) 上の位置。単独の番号を引いたときはNone
+    IndexOutOfBounds { index: u32, position: Option<usize>, vertex_count: usize },
+    /// PackConfigで要求された属性が渡されていない
+    MissingAttribute(&'static str),
+    /// その他の不正な入力
+    InvalidInput(String),
+    IoError(io::Error),
+}
+
+/// io::ErrorはCloneできないので種類とメッセージだけ写す
+impl Clone for MeshGuardError {
+    fn clone(&self) -> Self {
+        match self {
+            MeshGuardError::LengthMismatch { attribute, expected, got } => MeshGuardError::LengthMismatch { attribute, expected: *expected, got: *got },
+            MeshGuardError::EmptyMesh => MeshGuardError::EmptyMesh,
+            MeshGuardError::IndexOutOfBounds { index, position, vertex_count } => MeshGuardError::IndexOutOfBounds { index: *index, position: *position, vertex_count: *vertex_count },
+            MeshGuardError::MissingAttribute(a) => MeshGuardError::MissingAttribute(a),
+            MeshGuardError::InvalidInput(msg) => MeshGuardError::InvalidInput(msg.clone()),
+            MeshGuardError::IoError(e) => MeshGuardError::IoError(io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+}
+
+/// IoError同士は ErrorKind が同じなら等しいとみなす
+impl PartialEq for MeshGuardError {
+    fn eq(&self, other: &Self) -> bool {
+        use MeshGuardError::*;
+        match (self, other) {
+            (LengthMismatch { attribute: a, expected: e, got: g }, LengthMismatch { attribute: a2, expected: e2, got: g2 }) => (a, e, g) == (a2, e2, g2),
+            (EmptyMesh, EmptyMesh) => true,
+            (IndexOutOfBounds { index: i, position: p, vertex_count: n }, IndexOutOfBounds { index: i2, position: p2, vertex_count: n2 }) => (i, p, n) == (i2, p2, n2),
+            (MissingAttribute(a), MissingAttribute(b)) => a == b,
+            (InvalidInput(a), InvalidInput(b)) => a == b,
+            (IoError(a), IoError(b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MeshGuardError {}
+
+impl fmt::Display for MeshGuardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshGuardError::LengthMismatch { attribute, expected, got } => {
+                write!(f, "{} length mismatch: expected {}, got {}", attribute, expected, got)
+            }
+            MeshGuardError::EmptyMesh => write!(f, "mesh has no vertices"),
+            MeshGuardError::IndexOutOfBounds { index, position: Some(position), vertex_count } => {
+                write!(f, "index {} at position {} out of bounds for {} vertices", index, position, vertex_count)
+            }
+            MeshGuardError::IndexOutOfBounds { index, position: None, vertex_count } => {
+                write!(f, "index {} out of bounds for {} vertices", index, vertex_count)
+            }
+            MeshGuardError::MissingAttribute(attribute) => {
+                write!(f, "{} attribute is required by the pack config but was not supplied", attribute)
+            }
+            MeshGuardError::InvalidInput(msg) => write!(f, "invalid input: {}", msg),
+            MeshGuardError::IoError(e) => write!(f, "i/o error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MeshGuardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshGuardError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MeshGuardError {
+    fn from(e: io::Error) -> Self {
+        MeshGuardError::IoError(e)
+    }
+}
+
+/// 三角形リストのインデックスが全て頂点数未満か調べる (packは置換を作る前に同じ検査をする)
+/// 最初に見つかった範囲外の値とインデックスバッファ上の位置をIndexOutOfBoundsで返す
+pub fn validate_indices(indices: &[u32], vertex_count: usize) -> Result<(), MeshGuardError> {
+    match indices.iter().position(|&i| i as usize >= vertex_count) {
+        Some(position) => Err(MeshGuardError::IndexOutOfBounds { index: indices[position], position: Some(position), vertex_count }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn io_errors_wrap_and_compare_by_kind() {
+        let e: MeshGuardError = io::Error::new(io::ErrorKind::UnexpectedEof, "short read").into();
+        assert_eq!(e.to_string(), "i/o error: short read");
+        assert!(e.source().is_some());
+        assert_eq!(e.clone(), MeshGuardError::IoError(io::ErrorKind::UnexpectedEof.into()));
+        assert_ne!(e, MeshGuardError::IoError(io::ErrorKind::NotFound.into()));
+        assert_ne!(e, MeshGuardError::EmptyMesh);
+    }
+
+    #[test]
+    fn index_validation_reports_position() {
+        assert!(validate_indices(&[0, 1, 2], 3).is_ok());
+        assert_eq!(validate_indices(&[0, 3, 9], 3), Err(MeshGuardError::IndexOutOfBounds { index: 3, position: Some(1), vertex_count: 3 }));
+    }
+}
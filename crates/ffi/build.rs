@@ -0,0 +1,20 @@
+use std::env;
+use std::path::PathBuf;
+
+/// ヘッダはOUT_DIRに生成する (ソースツリーには書かない)
+/// チェックインしている include/meshguard.h は crates/ffi で
+/// `cbindgen --config cbindgen.toml --crate meshguard-ffi --output include/meshguard.h` を実行して更新する
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("cbindgen.toml");
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(out_dir.join("meshguard.h"));
+        }
+        // ヘッダ生成に失敗してもライブラリのビルドは止めない
+        Err(e) => println!("cargo:warning=cbindgen failed: {e}"),
+    }
+}
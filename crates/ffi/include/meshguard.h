@@ -0,0 +1,135 @@
+#ifndef MESHGUARD_H
+#define MESHGUARD_H
+
+/* cbindgen --config cbindgen.toml --crate meshguard-ffi --output include/meshguard.h で生成。直接編集しないこと */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define MESHGUARD_OK 0
+
+/**
+ * 必須のポインタがNULL
+ */
+#define MESHGUARD_ERR_NULL -1
+
+/**
+ * 長さ不一致や範囲外インデックスなど
+ */
+#define MESHGUARD_ERR_INVALID -2
+
+/**
+ * 座標は常にi16で渡す (xyz並び、len = 頂点数 * 3)
+ */
+typedef struct QuantizedPositionsFFI {
+  int16_t *data;
+  size_t len;
+  uint8_t bits[3];
+  float scale[3];
+  float offset[3];
+} QuantizedPositionsFFI;
+
+/**
+ * oct法線 (len = 頂点数 * 2)
+ */
+typedef struct QuantizedNormalsFFI {
+  uint16_t *data;
+  size_t len;
+} QuantizedNormalsFFI;
+
+/**
+ * UV (len = 頂点数 * 2)
+ */
+typedef struct QuantizedUVsFFI {
+  uint16_t *data;
+  size_t len;
+} QuantizedUVsFFI;
+
+/**
+ * インデックスは内部表現に関わらずu32で渡す
+ */
+typedef struct PackedMeshFFI {
+  uint8_t *interleaved;
+  size_t interleaved_len;
+  size_t vertex_count;
+  size_t stride;
+  uint32_t *indices;
+  size_t index_count;
+  float pos_scale[3];
+  float pos_offset[3];
+  uint8_t pos_bits[3];
+  uint64_t perm_seed;
+} PackedMeshFFI;
+
+/**
+ * `positions` の頂点 `count` 個を16bitで量子化する
+ *
+ * # Safety
+ * `positions` は `count * 3` 個のf32を指し、`out` は書き込み可能であること
+ */
+int32_t meshguard_quantize_positions(const float *positions,
+                                     size_t count,
+                                     struct QuantizedPositionsFFI *out);
+
+/**
+ * 法線 `count` 個をoct符号化する
+ *
+ * # Safety
+ * `normals` は `count * 3` 個のf32を指し、`out` は書き込み可能であること
+ */
+int32_t meshguard_encode_normals_oct(const float *normals,
+                                     size_t count,
+                                     struct QuantizedNormalsFFI *out);
+
+/**
+ * UV `count` 個を量子化する
+ *
+ * # Safety
+ * `uvs` は `count * 2` 個のf32を指し、`out` は書き込み可能であること
+ */
+int32_t meshguard_quantize_uvs(const float *uvs, size_t count, struct QuantizedUVsFFI *out);
+
+/**
+ * インターリーブ + 頂点置換
+ * `indices` がNULLなら 0..頂点数 を使う
+ *
+ * # Safety
+ * 入力は上の関数で得たものか同じ規約を満たすこと
+ * `indices` は非NULLなら `index_count` 個のu32を指すこと
+ */
+int32_t meshguard_pack_interleave_permute(const struct QuantizedPositionsFFI *positions,
+                                          const struct QuantizedNormalsFFI *normals,
+                                          const struct QuantizedUVsFFI *uvs,
+                                          const uint32_t *indices,
+                                          size_t index_count,
+                                          uint64_t perm_seed,
+                                          struct PackedMeshFFI *out);
+
+/**
+ * # Safety
+ * `meshguard_quantize_positions` で埋めた構造体を一度だけ渡すこと
+ */
+void meshguard_free_positions(struct QuantizedPositionsFFI *q);
+
+/**
+ * # Safety
+ * `meshguard_encode_normals_oct` で埋めた構造体を一度だけ渡すこと
+ */
+void meshguard_free_normals(struct QuantizedNormalsFFI *q);
+
+/**
+ * # Safety
+ * `meshguard_quantize_uvs` で埋めた構造体を一度だけ渡すこと
+ */
+void meshguard_free_uvs(struct QuantizedUVsFFI *q);
+
+/**
+ * # Safety
+ * `meshguard_pack_interleave_permute` で埋めた構造体を一度だけ渡すこと
+ */
+void meshguard_free_packed_mesh(struct PackedMeshFFI *m);
+
+#endif  /* MESHGUARD_H */
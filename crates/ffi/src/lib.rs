@@ -0,0 +1,266 @@
+//! C/C++ から呼ぶための extern "C" API
+//! ヒープに確保したバッファは必ず対応する `meshguard_free_*` で解放すること
+
+use meshguard_pack::{pack_interleave_permute, IndexBuffer};
+use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs, PositionData, QuantizedNormalsOct, QuantizedPositions, QuantizedUVs};
+use std::ptr;
+use std::slice;
+
+pub const MESHGUARD_OK: i32 = 0;
+/// 必須のポインタがNULL
+pub const MESHGUARD_ERR_NULL: i32 = -1;
+/// 長さ不一致や範囲外インデックスなど
+pub const MESHGUARD_ERR_INVALID: i32 = -2;
+
+/// 座標は常にi16で渡す (xyz並び、len = 頂点数 * 3)
+#[repr(C)]
+pub struct QuantizedPositionsFFI {
+    pub data: *mut i16,
+    pub len: usize,
+    pub bits: [u8; 3],
+    pub scale: [f32; 3],
+    pub offset: [f32; 3],
+}
+
+/// oct法線 (len = 頂点数 * 2)
+#[repr(C)]
+pub struct QuantizedNormalsFFI {
+    pub data: *mut u16,
+    pub len: usize,
+}
+
+/// UV (len = 頂点数 * 2)
+#[repr(C)]
+pub struct QuantizedUVsFFI {
+    pub data: *mut u16,
+    pub len: usize,
+}
+
+/// インデックスは内部表現に関わらずu32で渡す
+#[repr(C)]
+pub struct PackedMeshFFI {
+    pub interleaved: *mut u8,
+    pub interleaved_len: usize,
+    pub vertex_count: usize,
+    pub stride: usize,
+    pub indices: *mut u32,
+    pub index_count: usize,
+    pub pos_scale: [f32; 3],
+    pub pos_offset: [f32; 3],
+    pub pos_bits: [u8; 3],
+    pub perm_seed: u64,
+}
+
+fn into_raw<T>(v: Vec<T>) -> (*mut T, usize) {
+    let len = v.len();
+    (Box::into_raw(v.into_boxed_slice()) as *mut T, len)
+}
+
+unsafe fn free_raw<T>(data: *mut T, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+unsafe fn vec3s<'a>(p: *const f32, count: usize) -> &'a [[f32; 3]] {
+    if count == 0 { &[] } else { slice::from_raw_parts(p as *const [f32; 3], count) }
+}
+
+unsafe fn vec2s<'a>(p: *const f32, count: usize) -> &'a [[f32; 2]] {
+    if count == 0 { &[] } else { slice::from_raw_parts(p as *const [f32; 2], count) }
+}
+
+unsafe fn view<'a, T>(p: *const T, len: usize) -> &'a [T] {
+    if len == 0 { &[] } else { slice::from_raw_parts(p, len) }
+}
+
+/// `positions` の頂点 `count` 個を16bitで量子化する
+///
+/// # Safety
+/// `positions` は `count * 3` 個のf32を指し、`out` は書き込み可能であること
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_quantize_positions(positions: *const f32, count: usize, out: *mut QuantizedPositionsFFI) -> i32 {
+    if positions.is_null() || out.is_null() {
+        return MESHGUARD_ERR_NULL;
+    }
+    let q = match quantize_positions(vec3s(positions, count)) {
+        Ok(q) => q,
+        Err(_) => return MESHGUARD_ERR_INVALID,
+    };
+    let data: Vec<i16> = (0..q.data.len()).map(|i| q.data.get(i)).collect();
+    let (data, len) = into_raw(data);
+    out.write(QuantizedPositionsFFI { data, len, bits: q.bits, scale: q.scale, offset: q.offset });
+    MESHGUARD_OK
+}
+
+/// 法線 `count` 個をoct符号化する
+///
+/// # Safety
+/// `normals` は `count * 3` 個のf32を指し、`out` は書き込み可能であること
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_encode_normals_oct(normals: *const f32, count: usize, out: *mut QuantizedNormalsFFI) -> i32 {
+    if normals.is_null() || out.is_null() {
+        return MESHGUARD_ERR_NULL;
+    }
+    let (data, len) = into_raw(encode_normals_oct(vec3s(normals, count)).data);
+    out.write(QuantizedNormalsFFI { data, len });
+    MESHGUARD_OK
+}
+
+/// UV `count` 個を量子化する
+///
+/// # Safety
+/// `uvs` は `count * 2` 個のf32を指し、`out` は書き込み可能であること
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_quantize_uvs(uvs: *const f32, count: usize, out: *mut QuantizedUVsFFI) -> i32 {
+    if uvs.is_null() || out.is_null() {
+        return MESHGUARD_ERR_NULL;
+    }
+    let (data, len) = into_raw(quantize_uvs(vec2s(uvs, count)).data);
+    out.write(QuantizedUVsFFI { data, len });
+    MESHGUARD_OK
+}
+
+/// インターリーブ + 頂点置換
+/// `indices` がNULLなら 0..頂点数 を使う
+///
+/// # Safety
+/// 入力は上の関数で得たものか同じ規約を満たすこと
+/// `indices` は非NULLなら `index_count` 個のu32を指すこと
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_pack_interleave_permute(
+    positions: *const QuantizedPositionsFFI,
+    normals: *const QuantizedNormalsFFI,
+    uvs: *const QuantizedUVsFFI,
+    indices: *const u32,
+    index_count: usize,
+    perm_seed: u64,
+    out: *mut PackedMeshFFI,
+) -> i32 {
+    if positions.is_null() || normals.is_null() || uvs.is_null() || out.is_null() {
+        return MESHGUARD_ERR_NULL;
+    }
+    let (p, n, u) = (&*positions, &*normals, &*uvs);
+    let raw = view(p.data, p.len);
+    let data = if p.bits.iter().all(|&b| b <= 8) {
+        PositionData::I8(raw.iter().map(|&v| v as i8).collect())
+    } else {
+        PositionData::I16(raw.to_vec())
+    };
+    let qpos = QuantizedPositions { data, bits: p.bits, scale: p.scale, offset: p.offset };
+    let qnor = QuantizedNormalsOct { data: view(n.data, n.len).to_vec() };
+    let quv = QuantizedUVs { data: view(u.data, u.len).to_vec() };
+    let idx = if indices.is_null() { None } else { Some(view(indices, index_count)) };
+
+    let mesh = match pack_interleave_permute(&qpos, &qnor, &quv, idx, perm_seed) {
+        Ok(m) => m,
+        Err(_) => return MESHGUARD_ERR_INVALID,
+    };
+    let indices = match mesh.indices {
+        IndexBuffer::U16(v) => v.into_iter().map(u32::from).collect(),
+        IndexBuffer::U32(v) => v,
+    };
+    let (interleaved, interleaved_len) = into_raw(mesh.interleaved);
+    let (indices, index_count) = into_raw(indices);
+    out.write(PackedMeshFFI {
+        interleaved,
+        interleaved_len,
+        vertex_count: mesh.vertex_count,
+        stride: mesh.layout.stride,
+        indices,
+        index_count,
+        pos_scale: mesh.pos_scale,
+        pos_offset: mesh.pos_offset,
+        pos_bits: mesh.pos_bits,
+        perm_seed,
+    });
+    MESHGUARD_OK
+}
+
+/// # Safety
+/// `meshguard_quantize_positions` で埋めた構造体を一度だけ渡すこと
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_free_positions(q: *mut QuantizedPositionsFFI) {
+    if let Some(q) = q.as_mut() {
+        free_raw(q.data, q.len);
+        q.data = ptr::null_mut();
+        q.len = 0;
+    }
+}
+
+/// # Safety
+/// `meshguard_encode_normals_oct` で埋めた構造体を一度だけ渡すこと
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_free_normals(q: *mut QuantizedNormalsFFI) {
+    if let Some(q) = q.as_mut() {
+        free_raw(q.data, q.len);
+        q.data = ptr::null_mut();
+        q.len = 0;
+    }
+}
+
+/// # Safety
+/// `meshguard_quantize_uvs` で埋めた構造体を一度だけ渡すこと
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_free_uvs(q: *mut QuantizedUVsFFI) {
+    if let Some(q) = q.as_mut() {
+        free_raw(q.data, q.len);
+        q.data = ptr::null_mut();
+        q.len = 0;
+    }
+}
+
+/// # Safety
+/// `meshguard_pack_interleave_permute` で埋めた構造体を一度だけ渡すこと
+#[no_mangle]
+pub unsafe extern "C" fn meshguard_free_packed_mesh(m: *mut PackedMeshFFI) {
+    if let Some(m) = m.as_mut() {
+        free_raw(m.interleaved, m.interleaved_len);
+        free_raw(m.indices, m.index_count);
+        m.interleaved = ptr::null_mut();
+        m.indices = ptr::null_mut();
+        m.interleaved_len = 0;
+        m.index_count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::MaybeUninit;
+
+    #[test]
+    fn ffi_round_trip() {
+        let pos: [f32; 9] = [0.0,1.0,2.0, 10.0,20.0,30.0, -1.0,0.5,100.0];
+        let nor: [f32; 9] = [0.0,0.0,1.0, 1.0,0.0,0.0, 0.577,0.577,0.577];
+        let uv: [f32; 6] = [0.0,0.0, 0.5,0.75, 1.0,1.0];
+        let idx: [u32; 3] = [0, 1, 2];
+        unsafe {
+            let mut qp = MaybeUninit::uninit();
+            let mut qn = MaybeUninit::uninit();
+            let mut qu = MaybeUninit::uninit();
+            assert_eq!(meshguard_quantize_positions(pos.as_ptr(), 3, qp.as_mut_ptr()), MESHGUARD_OK);
+            assert_eq!(meshguard_encode_normals_oct(nor.as_ptr(), 3, qn.as_mut_ptr()), MESHGUARD_OK);
+            assert_eq!(meshguard_quantize_uvs(uv.as_ptr(), 3, qu.as_mut_ptr()), MESHGUARD_OK);
+            let (mut qp, mut qn, mut qu) = (qp.assume_init(), qn.assume_init(), qu.assume_init());
+            assert_eq!(qp.len, 9);
+
+            let mut m = MaybeUninit::uninit();
+            assert_eq!(meshguard_pack_interleave_permute(&qp, &qn, &qu, idx.as_ptr(), 3, 42, m.as_mut_ptr()), MESHGUARD_OK);
+            let mut m = m.assume_init();
+            assert_eq!((m.vertex_count, m.stride, m.index_count), (3, 14, 3));
+            assert_eq!(m.interleaved_len, 42);
+
+            let bad: [u32; 3] = [0, 1, 7];
+            let mut m2 = MaybeUninit::uninit();
+            assert_eq!(meshguard_pack_interleave_permute(&qp, &qn, &qu, bad.as_ptr(), 3, 42, m2.as_mut_ptr()), MESHGUARD_ERR_INVALID);
+            assert_eq!(meshguard_quantize_positions(ptr::null(), 3, &mut qp), MESHGUARD_ERR_NULL);
+
+            meshguard_free_packed_mesh(&mut m);
+            meshguard_free_positions(&mut qp);
+            meshguard_free_normals(&mut qn);
+            meshguard_free_uvs(&mut qu);
+            assert!(m.interleaved.is_null() && qp.data.is_null());
+        }
+    }
+}
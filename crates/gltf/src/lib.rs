@@ -0,0 +1,413 @@
+use anyhow::{bail, Context, Result};
+use meshguard_pack::{AttributeKind, Endianness, IndexBuffer, PackedMesh, PrimitiveTopology, VertexFormat, VertexLayout, STRIP_RESTART_INDEX};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+pub const ARRAY_BUFFER: u32 = 34962;
+pub const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+pub const UNSIGNED_BYTE: u32 = 5121;
+pub const SHORT: u32 = 5122;
+pub const UNSIGNED_SHORT: u32 = 5123;
+pub const UNSIGNED_INT: u32 = 5125;
+
+pub const KHR_MESH_QUANTIZATION: &str = "KHR_mesh_quantization";
+
+/// PackedMeshをglTFで参照するためのJSON断片
+/// バッファ0は [interleaved頂点][4バイト境界に揃えたインデックス] の並び
+#[derive(Clone, Debug)]
+pub struct GltfMeshPrimitive {
+    pub accessors: Vec<Value>,
+    pub buffer_views: Vec<Value>,
+    /// `meshes[*].primitives` の1要素
+    pub primitive: Value,
+    pub extensions_used: Vec<&'static str>,
+    /// バッファ内のインデックス開始位置
+    pub index_byte_offset: usize,
+    pub buffer_byte_length: usize,
+}
+
+/// accessors / bufferViews / primitive を組み立てる
+/// oct法線やoct接線はglTF標準にないので `_NORMAL_OCT` / `_TANGENT_OCT` として出す
+/// glTFは頂点属性のbyteOffsetとbyteStrideが4の倍数であることを求めるので、揃っていないレイアウト (既定の14バイトなど) は
+/// 各属性を4バイト境界に置き直したバッファとして出す (`binary_buffer` も同じ配置)。`PackConfig::align_attributes` ならそのまま
+pub fn to_gltf_accessors(mesh: &PackedMesh) -> Result<GltfMeshPrimitive> {
+    let layout = &gltf_layout(&mesh.layout);
+    if layout.endianness != Endianness::Little {
+        bail!("glTF buffers must be little-endian");
+    }
+    // glTFはプリミティブリスタートを許さない
+    let mode = match mesh.topology {
+        PrimitiveTopology::Triangles => 4,
+        PrimitiveTopology::TriangleStrip if mesh.indices_u32().contains(&STRIP_RESTART_INDEX) => bail!("glTF does not allow primitive restart in triangle strips"),
+        PrimitiveTopology::TriangleStrip => 5,
+    };
+    let vertex_bytes = mesh.vertex_count * layout.stride;
+    let index_byte_offset = align4(vertex_bytes);
+    let (index_component, index_bytes) = match &mesh.indices {
+        IndexBuffer::U16(v) => (UNSIGNED_SHORT, v.len() * 2),
+        IndexBuffer::U32(v) => (UNSIGNED_INT, v.len() * 4),
+    };
+
+    let buffer_views = vec![
+        json!({
+            "buffer": 0,
+            "byteOffset": 0,
+            "byteLength": vertex_bytes,
+            "byteStride": layout.stride,
+            "target": ARRAY_BUFFER,
+        }),
+        json!({
+            "buffer": 0,
+            "byteOffset": index_byte_offset,
+            "byteLength": index_bytes,
+            "target": ELEMENT_ARRAY_BUFFER,
+        }),
+    ];
+
+    let mut accessors = Vec::new();
+    let mut attributes = serde_json::Map::new();
+    let mut extensions_used = Vec::new();
+    for attr in &layout.attributes {
+        let (name, component, ty, normalized) = match (attr.kind, attr.format) {
+            (AttributeKind::Position, VertexFormat::Sint16x3) => ("POSITION", SHORT, "VEC3", false),
+            (AttributeKind::Normal, VertexFormat::Unorm16x2) => ("_NORMAL_OCT", UNSIGNED_SHORT, "VEC2", true),
+            (AttributeKind::Uv, VertexFormat::Unorm16x2) => ("TEXCOORD_0", UNSIGNED_SHORT, "VEC2", true),
+            (AttributeKind::Uv2, VertexFormat::Unorm16x2) => ("TEXCOORD_1", UNSIGNED_SHORT, "VEC2", true),
+            (AttributeKind::Tangent, VertexFormat::Snorm16x4) => ("_TANGENT_OCT", SHORT, "VEC4", true),
+            (AttributeKind::Color, VertexFormat::Unorm8x4) => ("COLOR_0", UNSIGNED_BYTE, "VEC4", true),
+            (AttributeKind::JointWeights, VertexFormat::Unorm8x4) => ("WEIGHTS_0", UNSIGNED_BYTE, "VEC4", true),
+            (AttributeKind::JointIndices, VertexFormat::Uint8x4) => ("JOINTS_0", UNSIGNED_BYTE, "VEC4", false),
+            (AttributeKind::JointIndices, VertexFormat::Uint16x4) => ("JOINTS_0", UNSIGNED_SHORT, "VEC4", false),
+            (AttributeKind::AmbientOcclusion, VertexFormat::Unorm8) => ("_AO", UNSIGNED_BYTE, "SCALAR", true),
+            (kind, format) => bail!("{:?} in {:?} has no glTF representation", kind, format),
+        };
+        let mut accessor = json!({
+            "bufferView": 0,
+            "byteOffset": attr.offset,
+            "componentType": component,
+            "count": mesh.vertex_count,
+            "type": ty,
+        });
+        if normalized {
+            accessor["normalized"] = json!(true);
+        }
+        if attr.kind == AttributeKind::Position {
+            // POSITIONはmin/maxが必須
+            let (min, max) = position_min_max(mesh);
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+            if !extensions_used.contains(&KHR_MESH_QUANTIZATION) {
+                extensions_used.push(KHR_MESH_QUANTIZATION);
+            }
+        }
+        attributes.insert(name.to_string(), json!(accessors.len()));
+        accessors.push(accessor);
+    }
+
+    let index_accessor = accessors.len();
+    accessors.push(json!({
+        "bufferView": 1,
+        "byteOffset": 0,
+        "componentType": index_component,
+        "count": mesh.indices.len(),
+        "type": "SCALAR",
+    }));
+
+    Ok(GltfMeshPrimitive {
+        accessors,
+        buffer_views,
+        primitive: json!({
+            "attributes": attributes,
+            "indices": index_accessor,
+            "mode": mode,
+        }),
+        extensions_used,
+        index_byte_offset,
+        buffer_byte_length: index_byte_offset + index_bytes,
+    })
+}
+
+/// バッファ0の中身 (4バイト境界に揃えた頂点 + パディング + インデックス)
+pub fn binary_buffer(mesh: &PackedMesh) -> Vec<u8> {
+    let mut buf = gltf_vertices(mesh);
+    buf.resize(align4(buf.len()), 0);
+    match &mesh.indices {
+        IndexBuffer::U16(v) => v.iter().for_each(|i| buf.extend_from_slice(&i.to_le_bytes())),
+        IndexBuffer::U32(v) => v.iter().for_each(|i| buf.extend_from_slice(&i.to_le_bytes())),
+    }
+    buf
+}
+
+/// 量子化座標を元のワールド座標に戻すノード変換 (translation, scale)
+/// world = q * scale + (offset + 2^(bits-1) * scale)
+pub fn node_transform(mesh: &PackedMesh) -> ([f32; 3], [f32; 3]) {
+    let translation = std::array::from_fn(|a| {
+        let half = (1i64 << (mesh.pos_bits[a] - 1)) as f64;
+        (mesh.pos_offset[a] as f64 + half * mesh.pos_scale[a] as f64) as f32
+    });
+    (translation, mesh.pos_scale)
+}
+
+/// 1メッシュ・1ノードのglTFドキュメント
+/// `buffer_uri` がNoneならGLB用にuriを省略する
+pub fn gltf_document(mesh: &PackedMesh, buffer_uri: Option<&str>) -> Result<Value> {
+    let prim = to_gltf_accessors(mesh)?;
+    let (translation, scale) = node_transform(mesh);
+    let mut buffer = json!({ "byteLength": prim.buffer_byte_length });
+    if let Some(uri) = buffer_uri {
+        buffer["uri"] = json!(uri);
+    }
+    let mut doc = json!({
+        "asset": { "version": "2.0", "generator": "meshguard" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "translation": translation, "scale": scale }],
+        "meshes": [{ "primitives": [prim.primitive] }],
+        "accessors": prim.accessors,
+        "bufferViews": prim.buffer_views,
+        "buffers": [buffer],
+    });
+    if !prim.extensions_used.is_empty() {
+        doc["extensionsUsed"] = json!(prim.extensions_used);
+        doc["extensionsRequired"] = json!(prim.extensions_used);
+    }
+    Ok(doc)
+}
+
+/// `.gltf` と同名の `.bin` を書き出す
+pub fn export_gltf_quantized(mesh: &PackedMesh, path: &Path) -> Result<()> {
+    let bin_path = path.with_extension("bin");
+    let bin_name = bin_path.file_name().and_then(|n| n.to_str()).context("invalid output path")?;
+    let doc = gltf_document(mesh, Some(bin_name))?;
+    fs::write(&bin_path, binary_buffer(mesh)).with_context(|| format!("failed to write {}", bin_path.display()))?;
+    fs::write(path, serde_json::to_string_pretty(&doc)?).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// GLB (ヘッダ12バイト + JSONチャンク + BINチャンク) のバイト列
+pub fn glb_bytes(mesh: &PackedMesh) -> Result<Vec<u8>> {
+    let mut json = serde_json::to_vec(&gltf_document(mesh, None)?)?;
+    json.resize(align4(json.len()), b' ');
+    let mut bin = binary_buffer(mesh);
+    bin.resize(align4(bin.len()), 0);
+
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+    out.extend_from_slice(&bin);
+    Ok(out)
+}
+
+/// 単一ファイルの `.glb` を書き出す
+pub fn export_glb(mesh: &PackedMesh, path: &Path) -> Result<()> {
+    fs::write(path, glb_bytes(mesh)?).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+/// 属性の並びは変えずに、各属性とstrideを4バイト境界に揃えた配置
+fn gltf_layout(layout: &VertexLayout) -> VertexLayout {
+    let mut attributes = layout.attributes.clone();
+    attributes.sort_by_key(|a| a.offset);
+    let mut offset = 0;
+    for a in &mut attributes {
+        a.offset = offset;
+        offset = align4(offset + a.format.size());
+    }
+    VertexLayout { attributes, stride: offset, endianness: layout.endianness }
+}
+
+/// `gltf_layout` の配置に詰め直した頂点 (揃っていればそのままコピー)
+fn gltf_vertices(mesh: &PackedMesh) -> Vec<u8> {
+    let out_layout = gltf_layout(&mesh.layout);
+    if out_layout == mesh.layout {
+        return mesh.interleaved.clone();
+    }
+    let mut from: Vec<usize> = mesh.layout.attributes.iter().map(|a| a.offset).collect();
+    from.sort_unstable();
+    let mut out = vec![0u8; mesh.vertex_count * out_layout.stride];
+    for (src, dst) in mesh.interleaved.chunks_exact(mesh.layout.stride).zip(out.chunks_exact_mut(out_layout.stride)) {
+        for (a, &f) in out_layout.attributes.iter().zip(&from) {
+            dst[a.offset..a.offset + a.format.size()].copy_from_slice(&src[f..f + a.format.size()]);
+        }
+    }
+    out
+}
+
+/// 詰め直す前の `mesh.layout` の位置から読む
+fn position_min_max(mesh: &PackedMesh) -> ([i16; 3], [i16; 3]) {
+    let offset = mesh.layout.attribute(AttributeKind::Position).map_or(0, |a| a.offset);
+    let mut min = [i16::MAX; 3];
+    let mut max = [i16::MIN; 3];
+    for v in mesh.interleaved.chunks_exact(mesh.layout.stride) {
+        for a in 0..3 {
+            let o = offset + a * 2;
+            let q = i16::from_le_bytes([v[o], v[o + 1]]);
+            min[a] = min[a].min(q);
+            max[a] = max[a].max(q);
+        }
+    }
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::{pack_interleave_permute, pack_with_config, AttributeOrder, MeshAttributes, PackConfig};
+    use meshguard_quantize::{dequantize_positions, encode_normals_oct, quantize_positions, quantize_uvs};
+
+    pub(crate) fn sample_mesh() -> PackedMesh {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
+        pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&nor), &quantize_uvs(&uv), Some(&[0, 1, 2]), 42).unwrap()
+    }
+
+    #[test]
+    fn accessors_match_layout() {
+        let mesh = sample_mesh();
+        let prim = to_gltf_accessors(&mesh).unwrap();
+        assert_eq!(prim.extensions_used, vec![KHR_MESH_QUANTIZATION]);
+        assert_eq!(prim.accessors.len(), 4);
+
+        let pos = &prim.accessors[prim.primitive["attributes"]["POSITION"].as_u64().unwrap() as usize];
+        assert_eq!(pos["componentType"], SHORT);
+        assert_eq!(pos["type"], "VEC3");
+        assert_eq!(pos["min"], json!([-32768, -32768, -32768]));
+        assert_eq!(pos["max"], json!([32767, 32767, 32767]));
+
+        let uv = &prim.accessors[prim.primitive["attributes"]["TEXCOORD_0"].as_u64().unwrap() as usize];
+        assert_eq!(uv["componentType"], UNSIGNED_SHORT);
+        assert_eq!(uv["normalized"], true);
+
+        // 14バイトの既定レイアウトは P:0 N:8 U:12 の16バイトに置き直す
+        assert_eq!(mesh.layout.stride, 14);
+        assert_eq!(prim.buffer_views[0]["byteStride"], 16);
+        assert_eq!(uv["byteOffset"], 12);
+        assert!(prim.accessors[..3].iter().all(|a| a["byteOffset"].as_u64().unwrap() % 4 == 0));
+        assert_eq!(prim.index_byte_offset, 48);
+        assert_eq!(prim.buffer_byte_length, 48 + 6);
+        let buf = binary_buffer(&mesh);
+        assert_eq!(buf.len(), prim.buffer_byte_length);
+        for (v, src) in buf[..48].chunks_exact(16).zip(mesh.interleaved.chunks_exact(14)) {
+            assert_eq!((&v[..6], &v[8..16]), (&src[..6], &src[6..14]));
+        }
+
+        // 揃ったレイアウトはそのまま
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let aligned = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 1, &PackConfig::new().align_attributes(true)).unwrap();
+        assert_eq!(to_gltf_accessors(&aligned).unwrap().buffer_views[0]["byteStride"], 16);
+        assert_eq!(&binary_buffer(&aligned)[..48], &aligned.interleaved[..]);
+    }
+
+    #[test]
+    fn position_after_ao_keeps_bounds() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let qao = meshguard_quantize::quantize_ao(&[0.5; 3]);
+        let order = AttributeOrder(vec![AttributeKind::AmbientOcclusion, AttributeKind::Position, AttributeKind::Normal, AttributeKind::Uv]);
+        let mesh = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv).with_ao(&qao), None, 1, &PackConfig::new().order(order)).unwrap();
+        assert_eq!(mesh.layout.attribute(AttributeKind::Position).unwrap().offset, 1);
+
+        let prim = to_gltf_accessors(&mesh).unwrap();
+        let p = &prim.accessors[prim.primitive["attributes"]["POSITION"].as_u64().unwrap() as usize];
+        assert_eq!(p["byteOffset"], 4);
+        assert_eq!(p["min"], json!([-32768, -32768, -32768]));
+        assert_eq!(p["max"], json!([32767, 32767, 32767]));
+    }
+
+    #[test]
+    fn second_uv_is_texcoord_1() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_uvs2(&quv);
+        let mesh = pack_with_config(&attrs, None, 1, &PackConfig::new().uv_channels(2)).unwrap();
+        let prim = to_gltf_accessors(&mesh).unwrap();
+        let uv1 = &prim.accessors[prim.primitive["attributes"]["TEXCOORD_1"].as_u64().unwrap() as usize];
+        assert_eq!(uv1["byteOffset"], 16);
+        assert_eq!(prim.buffer_views[0]["byteStride"], 20);
+        assert_eq!(uv1["normalized"], true);
+    }
+
+    #[test]
+    fn node_transform_dequantizes() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let q = quantize_positions(&pos).unwrap();
+        let mesh = sample_mesh();
+        let (t, s) = node_transform(&mesh);
+        let deq = dequantize_positions(&q);
+        for (i, p) in deq.iter().enumerate() {
+            for a in 0..3 {
+                let w = q.data.get(i*3 + a) as f32 * s[a] + t[a];
+                assert!((w - p[a]).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn export_writes_gltf_and_bin() {
+        let mesh = sample_mesh();
+        let dir = std::env::temp_dir().join(format!("meshguard-gltf-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mesh.gltf");
+        export_gltf_quantized(&mesh, &path).unwrap();
+
+        let doc: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["extensionsRequired"], json!([KHR_MESH_QUANTIZATION]));
+        assert_eq!(doc["buffers"][0]["uri"], "mesh.bin");
+        let bin = fs::read(dir.join("mesh.bin")).unwrap();
+        assert_eq!(doc["buffers"][0]["byteLength"], bin.len());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn glb_layout() {
+        let mesh = sample_mesh();
+        let glb = glb_bytes(&mesh).unwrap();
+        let word = |o: usize| u32::from_le_bytes(glb[o..o + 4].try_into().unwrap()) as usize;
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8), glb.len());
+
+        let json_len = word(12);
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(&glb[16..20], b"JSON");
+        let doc: Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+        assert!(doc["buffers"][0].get("uri").is_none());
+
+        let bin_at = 20 + json_len;
+        assert_eq!(&glb[bin_at + 4..bin_at + 8], b"BIN\0");
+        let bin = &glb[bin_at + 8..];
+        assert_eq!(bin.len(), word(bin_at));
+        assert_eq!(&bin[..48], &gltf_vertices(&mesh)[..]);
+        let idx_at = doc["bufferViews"][1]["byteOffset"].as_u64().unwrap() as usize;
+        let first = mesh.indices_u32()[0] as u16;
+        assert_eq!(&bin[idx_at..idx_at + 2], &first.to_le_bytes());
+    }
+
+    #[test]
+    fn strip_topology_sets_mode() {
+        let mut mesh = sample_mesh();
+        assert_eq!(to_gltf_accessors(&mesh).unwrap().primitive["mode"], 4);
+        mesh.topology = PrimitiveTopology::TriangleStrip;
+        assert_eq!(to_gltf_accessors(&mesh).unwrap().primitive["mode"], 5);
+        mesh.indices = IndexBuffer::U16(vec![0, 1, 2, u16::MAX, 2, 1, 0]);
+        assert!(to_gltf_accessors(&mesh).is_err());
+    }
+}
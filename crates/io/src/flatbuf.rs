@@ -0,0 +1,99 @@
+use crate::meshguard_generated::meshguard as fb;
+use anyhow::{Context, Result};
+use flatbuffers::{FlatBufferBuilder, WIPOffset};
+use meshguard_pack::PackedMesh;
+
+/// `schemas/meshguard.fbs` の PackedMesh テーブルを builder に書く
+/// 返したオフセットを `fb::finish_packed_mesh_buffer` に渡すとバッファが完成する
+/// インデックスは常にu32で書く。レイアウトとトポロジーはスキーマにないので書かない
+/// 頂点数がu32、strideがu16に収まらなければエラー (何も書かない)
+pub fn to_flatbuffer<'a>(mesh: &PackedMesh, builder: &mut FlatBufferBuilder<'a>) -> Result<WIPOffset<fb::PackedMesh<'a>>> {
+    let vertex_count = u32::try_from(mesh.vertex_count).with_context(|| format!("vertex count {} does not fit in u32", mesh.vertex_count))?;
+    let stride = u16::try_from(mesh.layout.stride).with_context(|| format!("stride {} does not fit in u16", mesh.layout.stride))?;
+    let interleaved = builder.create_vector(&mesh.interleaved);
+    let indices = builder.create_vector(&mesh.indices_u32());
+    let pos_bits = builder.create_vector(&mesh.pos_bits);
+    let perm_seeds = builder.create_vector(&mesh.perm_seeds);
+    let [sx, sy, sz] = mesh.pos_scale;
+    let [ox, oy, oz] = mesh.pos_offset;
+    Ok(fb::PackedMesh::create(builder, &fb::PackedMeshArgs {
+        interleaved: Some(interleaved),
+        vertex_count,
+        indices: Some(indices),
+        pos_scale: Some(&fb::Vec3::new(sx, sy, sz)),
+        pos_offset: Some(&fb::Vec3::new(ox, oy, oz)),
+        perm_seeds: Some(perm_seeds),
+        pos_bits: Some(pos_bits),
+        stride,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::{pack_interleave_permute, pack_multi_permute};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn flatbuffer_reads_back_without_copy() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let mesh = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&[[0.0,0.0,1.0]; 3]), &quantize_uvs(&[[0.25,0.5]; 3]), Some(&[0, 1, 2]), 77).unwrap();
+        let mut builder = FlatBufferBuilder::new();
+        let root = to_flatbuffer(&mesh, &mut builder).unwrap();
+        fb::finish_packed_mesh_buffer(&mut builder, root);
+        let buf = builder.finished_data();
+        assert!(fb::packed_mesh_buffer_has_identifier(buf));
+
+        let r = fb::root_as_packed_mesh(buf).unwrap();
+        assert_eq!(r.interleaved().unwrap().bytes(), &mesh.interleaved[..]);
+        assert_eq!(r.indices().unwrap().iter().collect::<Vec<u32>>(), mesh.indices_u32());
+        assert_eq!((r.vertex_count(), r.stride()), (3, 14));
+        assert_eq!(r.perm_seeds().unwrap().iter().collect::<Vec<u64>>(), vec![77]);
+        let (s, o) = (r.pos_scale().unwrap(), r.pos_offset().unwrap());
+        assert_eq!([s.x(), s.y(), s.z()], mesh.pos_scale);
+        assert_eq!([o.x(), o.y(), o.z()], mesh.pos_offset);
+        assert_eq!(r.pos_bits().unwrap().bytes(), &mesh.pos_bits[..]);
+        assert!(fb::root_as_packed_mesh(&buf[..buf.len() / 2]).is_err());
+
+        // シードは全部残し、置換なしなら空
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        for seeds in [vec![3, 5, 8], vec![]] {
+            let mut m = pack_multi_permute(&qpos, &qnor, &quv, None, &[3, 5, 8]).unwrap();
+            m.perm_seeds = seeds.clone();
+            let mut builder = FlatBufferBuilder::new();
+            let root = to_flatbuffer(&m, &mut builder).unwrap();
+            fb::finish_packed_mesh_buffer(&mut builder, root);
+            let r = fb::root_as_packed_mesh(builder.finished_data()).unwrap();
+            assert_eq!(r.perm_seeds().unwrap().iter().collect::<Vec<u64>>(), seeds);
+        }
+
+        // 切り詰めずにエラーにする
+        let mut wide = mesh.clone();
+        wide.layout.stride = 70_000;
+        assert!(to_flatbuffer(&wide, &mut FlatBufferBuilder::new()).is_err());
+        let mut many = mesh.clone();
+        many.vertex_count = u32::MAX as usize + 1;
+        assert!(to_flatbuffer(&many, &mut FlatBufferBuilder::new()).is_err());
+    }
+
+    /// チェックインした生成コードがスキーマと一致するか (flatcがなければ飛ばす。FLATCでパスを指定できる)
+    #[test]
+    fn generated_code_matches_schema() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = std::env::temp_dir().join(format!("meshguard-flatc-{}", std::process::id()));
+        let flatc = std::env::var("FLATC").unwrap_or_else(|_| "flatc".into());
+        let status = match std::process::Command::new(&flatc).arg("--rust").arg("-o").arg(&out).arg(root.join("../../schemas/meshguard.fbs")).status() {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("{} not found; skipping the generated code check", flatc);
+                return;
+            }
+            Err(e) => panic!("failed to run {}: {}", flatc, e),
+        };
+        assert!(status.success());
+        let generated = std::fs::read_to_string(out.join("meshguard_generated.rs")).unwrap();
+        std::fs::remove_dir_all(&out).unwrap();
+        let checked_in = std::fs::read_to_string(root.join("src/meshguard_generated.rs")).unwrap();
+        assert!(generated == checked_in, "src/meshguard_generated.rs is out of date; rerun `flatc --rust -o crates/io/src schemas/meshguard.fbs`");
+    }
+}
@@ -0,0 +1,20 @@
+mod flatbuf;
+mod mgb;
+// schemas/meshguard.fbs から `flatc --rust -o crates/io/src schemas/meshguard.fbs` で生成したもの (手で直さない)
+// スキーマを変えたら再生成する。flatcがあれば `flatbuf::tests::generated_code_matches_schema` が差分を検出する
+#[allow(unused_imports, mismatched_lifetime_syntaxes, clippy::all)]
+mod meshguard_generated;
+mod msgpack;
+mod obj;
+mod ply;
+mod stl;
+mod threejs;
+
+pub use flatbuf::to_flatbuffer;
+pub use meshguard_generated::meshguard as fb;
+pub use mgb::{read_mgb, write_mgb, FLAG_BIG_ENDIAN, FLAG_INTEGRITY, FLAG_SUBMESHES, FLAG_TRIANGLE_STRIP, MgbError, MGB_MAGIC, MGB_VERSION};
+pub use msgpack::{from_msgpack, to_msgpack};
+pub use obj::{parse_obj, parse_obj_reader, ObjMesh};
+pub use ply::{parse_ply, PlyMesh};
+pub use stl::{parse_stl_ascii, parse_stl_binary, parse_stl_binary_with_epsilon, StlMesh, DEFAULT_WELD_EPSILON};
+pub use threejs::export_threejs_json;
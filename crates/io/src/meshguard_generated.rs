@@ -0,0 +1,477 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+
+// @generated
+
+use core::mem;
+use core::cmp::Ordering;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+#[allow(unused_imports, dead_code)]
+pub mod meshguard {
+
+  use core::mem;
+  use core::cmp::Ordering;
+
+  extern crate flatbuffers;
+  use self::flatbuffers::{EndianScalar, Follow};
+
+// struct Vec3, aligned to 4
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Vec3(pub [u8; 12]);
+impl Default for Vec3 { 
+  fn default() -> Self { 
+    Self([0; 12])
+  }
+}
+impl core::fmt::Debug for Vec3 {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("Vec3")
+      .field("x", &self.x())
+      .field("y", &self.y())
+      .field("z", &self.z())
+      .finish()
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for Vec3 {}
+impl<'a> flatbuffers::Follow<'a> for Vec3 {
+  type Inner = &'a Vec3;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    <&'a Vec3>::follow(buf, loc)
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a Vec3 {
+  type Inner = &'a Vec3;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::follow_cast_ref::<Vec3>(buf, loc)
+  }
+}
+impl<'b> flatbuffers::Push for Vec3 {
+    type Output = Vec3;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        let src = ::core::slice::from_raw_parts(self as *const Vec3 as *const u8, Self::size());
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for Vec3 {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.in_buffer::<Self>(pos)
+  }
+}
+
+impl<'a> Vec3 {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    x: f32,
+    y: f32,
+    z: f32,
+  ) -> Self {
+    let mut s = Self([0; 12]);
+    s.set_x(x);
+    s.set_y(y);
+    s.set_z(z);
+    s
+  }
+
+  pub fn x(&self) -> f32 {
+    let mut mem = core::mem::MaybeUninit::<<f32 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[0..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_x(&mut self, x: f32) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[0..].as_mut_ptr(),
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+  pub fn y(&self) -> f32 {
+    let mut mem = core::mem::MaybeUninit::<<f32 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[4..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_y(&mut self, x: f32) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[4..].as_mut_ptr(),
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+  pub fn z(&self) -> f32 {
+    let mut mem = core::mem::MaybeUninit::<<f32 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[8..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_z(&mut self, x: f32) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[8..].as_mut_ptr(),
+        core::mem::size_of::<<f32 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+}
+
+pub enum PackedMeshOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct PackedMesh<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for PackedMesh<'a> {
+  type Inner = PackedMesh<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: flatbuffers::Table::new(buf, loc) }
+  }
+}
+
+impl<'a> PackedMesh<'a> {
+  pub const VT_INTERLEAVED: flatbuffers::VOffsetT = 4;
+  pub const VT_VERTEX_COUNT: flatbuffers::VOffsetT = 6;
+  pub const VT_INDICES: flatbuffers::VOffsetT = 8;
+  pub const VT_POS_SCALE: flatbuffers::VOffsetT = 10;
+  pub const VT_POS_OFFSET: flatbuffers::VOffsetT = 12;
+  pub const VT_PERM_SEEDS: flatbuffers::VOffsetT = 14;
+  pub const VT_POS_BITS: flatbuffers::VOffsetT = 16;
+  pub const VT_STRIDE: flatbuffers::VOffsetT = 18;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    PackedMesh { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+    args: &'args PackedMeshArgs<'args>
+  ) -> flatbuffers::WIPOffset<PackedMesh<'bldr>> {
+    let mut builder = PackedMeshBuilder::new(_fbb);
+    if let Some(x) = args.pos_bits { builder.add_pos_bits(x); }
+    if let Some(x) = args.perm_seeds { builder.add_perm_seeds(x); }
+    if let Some(x) = args.pos_offset { builder.add_pos_offset(x); }
+    if let Some(x) = args.pos_scale { builder.add_pos_scale(x); }
+    if let Some(x) = args.indices { builder.add_indices(x); }
+    builder.add_vertex_count(args.vertex_count);
+    if let Some(x) = args.interleaved { builder.add_interleaved(x); }
+    builder.add_stride(args.stride);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn interleaved(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(PackedMesh::VT_INTERLEAVED, None)}
+  }
+  #[inline]
+  pub fn vertex_count(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(PackedMesh::VT_VERTEX_COUNT, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn indices(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(PackedMesh::VT_INDICES, None)}
+  }
+  #[inline]
+  pub fn pos_scale(&self) -> Option<&'a Vec3> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vec3>(PackedMesh::VT_POS_SCALE, None)}
+  }
+  #[inline]
+  pub fn pos_offset(&self) -> Option<&'a Vec3> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vec3>(PackedMesh::VT_POS_OFFSET, None)}
+  }
+  #[inline]
+  pub fn perm_seeds(&self) -> Option<flatbuffers::Vector<'a, u64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u64>>>(PackedMesh::VT_PERM_SEEDS, None)}
+  }
+  #[inline]
+  pub fn pos_bits(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(PackedMesh::VT_POS_BITS, None)}
+  }
+  #[inline]
+  pub fn stride(&self) -> u16 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u16>(PackedMesh::VT_STRIDE, Some(0)).unwrap()}
+  }
+}
+
+impl flatbuffers::Verifiable for PackedMesh<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("interleaved", Self::VT_INTERLEAVED, false)?
+     .visit_field::<u32>("vertex_count", Self::VT_VERTEX_COUNT, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u32>>>("indices", Self::VT_INDICES, false)?
+     .visit_field::<Vec3>("pos_scale", Self::VT_POS_SCALE, false)?
+     .visit_field::<Vec3>("pos_offset", Self::VT_POS_OFFSET, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u64>>>("perm_seeds", Self::VT_PERM_SEEDS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("pos_bits", Self::VT_POS_BITS, false)?
+     .visit_field::<u16>("stride", Self::VT_STRIDE, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct PackedMeshArgs<'a> {
+    pub interleaved: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+    pub vertex_count: u32,
+    pub indices: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u32>>>,
+    pub pos_scale: Option<&'a Vec3>,
+    pub pos_offset: Option<&'a Vec3>,
+    pub perm_seeds: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u64>>>,
+    pub pos_bits: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+    pub stride: u16,
+}
+impl<'a> Default for PackedMeshArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    PackedMeshArgs {
+      interleaved: None,
+      vertex_count: 0,
+      indices: None,
+      pos_scale: None,
+      pos_offset: None,
+      perm_seeds: None,
+      pos_bits: None,
+      stride: 0,
+    }
+  }
+}
+
+pub struct PackedMeshBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> PackedMeshBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_interleaved(&mut self, interleaved: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(PackedMesh::VT_INTERLEAVED, interleaved);
+  }
+  #[inline]
+  pub fn add_vertex_count(&mut self, vertex_count: u32) {
+    self.fbb_.push_slot::<u32>(PackedMesh::VT_VERTEX_COUNT, vertex_count, 0);
+  }
+  #[inline]
+  pub fn add_indices(&mut self, indices: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(PackedMesh::VT_INDICES, indices);
+  }
+  #[inline]
+  pub fn add_pos_scale(&mut self, pos_scale: &Vec3) {
+    self.fbb_.push_slot_always::<&Vec3>(PackedMesh::VT_POS_SCALE, pos_scale);
+  }
+  #[inline]
+  pub fn add_pos_offset(&mut self, pos_offset: &Vec3) {
+    self.fbb_.push_slot_always::<&Vec3>(PackedMesh::VT_POS_OFFSET, pos_offset);
+  }
+  #[inline]
+  pub fn add_perm_seeds(&mut self, perm_seeds: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u64>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(PackedMesh::VT_PERM_SEEDS, perm_seeds);
+  }
+  #[inline]
+  pub fn add_pos_bits(&mut self, pos_bits: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(PackedMesh::VT_POS_BITS, pos_bits);
+  }
+  #[inline]
+  pub fn add_stride(&mut self, stride: u16) {
+    self.fbb_.push_slot::<u16>(PackedMesh::VT_STRIDE, stride, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> PackedMeshBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    PackedMeshBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<PackedMesh<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl core::fmt::Debug for PackedMesh<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("PackedMesh");
+      ds.field("interleaved", &self.interleaved());
+      ds.field("vertex_count", &self.vertex_count());
+      ds.field("indices", &self.indices());
+      ds.field("pos_scale", &self.pos_scale());
+      ds.field("pos_offset", &self.pos_offset());
+      ds.field("perm_seeds", &self.perm_seeds());
+      ds.field("pos_bits", &self.pos_bits());
+      ds.field("stride", &self.stride());
+      ds.finish()
+  }
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a `PackedMesh`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_packed_mesh_unchecked`.
+pub fn root_as_packed_mesh(buf: &[u8]) -> Result<PackedMesh, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root::<PackedMesh>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `PackedMesh` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_packed_mesh_unchecked`.
+pub fn size_prefixed_root_as_packed_mesh(buf: &[u8]) -> Result<PackedMesh, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root::<PackedMesh>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `PackedMesh` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_packed_mesh_unchecked`.
+pub fn root_as_packed_mesh_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<PackedMesh<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root_with_opts::<PackedMesh<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `PackedMesh` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_packed_mesh_unchecked`.
+pub fn size_prefixed_root_as_packed_mesh_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<PackedMesh<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root_with_opts::<PackedMesh<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a PackedMesh and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `PackedMesh`.
+pub unsafe fn root_as_packed_mesh_unchecked(buf: &[u8]) -> PackedMesh {
+  flatbuffers::root_unchecked::<PackedMesh>(buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed PackedMesh and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `PackedMesh`.
+pub unsafe fn size_prefixed_root_as_packed_mesh_unchecked(buf: &[u8]) -> PackedMesh {
+  flatbuffers::size_prefixed_root_unchecked::<PackedMesh>(buf)
+}
+pub const PACKED_MESH_IDENTIFIER: &str = "MGFB";
+
+#[inline]
+pub fn packed_mesh_buffer_has_identifier(buf: &[u8]) -> bool {
+  flatbuffers::buffer_has_identifier(buf, PACKED_MESH_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn packed_mesh_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+  flatbuffers::buffer_has_identifier(buf, PACKED_MESH_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_packed_mesh_buffer<'a, 'b>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+    root: flatbuffers::WIPOffset<PackedMesh<'a>>) {
+  fbb.finish(root, Some(PACKED_MESH_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_packed_mesh_buffer<'a, 'b>(fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>, root: flatbuffers::WIPOffset<PackedMesh<'a>>) {
+  fbb.finish_size_prefixed(root, Some(PACKED_MESH_IDENTIFIER));
+}
+}  // pub mod meshguard
+
@@ -0,0 +1,389 @@
+//! meshguard独自のバイナリ形式 (.mgb)
+//!
+//! ```text
+//! header   16B  "MESHGUARD" + major u8 + minor u8 + 予約5B
+//! meta          vertex_count u32, index_count u32, attribute_flags u32, stride u32,
+//!               scale f32x3, offset f32x3, pos_bits u8x3, index_width u8,
+//!               seed_count u32, perm_seeds u64 x seed_count,
+//!               属性ごとに kind u8, format u8, offset u16 (レイアウト順)
+//! vertices      vertex_count * stride
+//! indices       index_count * index_width
+//! tag      32B  FLAG_INTEGRITY のときだけ
+//! submeshes     FLAG_SUBMESHES のときだけ count u32 + (index_offset u32, index_count u32, material_id u32) x count
+//! ```
+//! メタデータはすべてリトルエンディアン。頂点データのバイト順は FLAG_BIG_ENDIAN で示す
+
+use anyhow::{ensure, Context, Result};
+use meshguard_pack::{
+    AttributeKind, Endianness, IndexBuffer, MeshGuardError, PackedMesh, PrimitiveTopology, SubmeshRange, VertexAttribute, VertexFormat, VertexLayout,
+};
+use std::fmt;
+use std::io::{self, Read, Write};
+
+pub const MGB_MAGIC: &[u8; 9] = b"MESHGUARD";
+pub const MGB_VERSION: (u8, u8) = (1, 0);
+
+/// attribute_flags のbit0〜8は AttributeKind の有無
+pub const FLAG_BIG_ENDIAN: u32 = 1 << 16;
+pub const FLAG_INTEGRITY: u32 = 1 << 17;
+pub const FLAG_SUBMESHES: u32 = 1 << 18;
+/// インデックスがトライアングルストリップ
+pub const FLAG_TRIANGLE_STRIP: u32 = 1 << 19;
+
+/// 後から増えた種類は末尾に足す (既存ファイルのbitを変えない)
+const KINDS: [AttributeKind; 9] = [
+    AttributeKind::Position,
+    AttributeKind::Normal,
+    AttributeKind::Uv,
+    AttributeKind::Tangent,
+    AttributeKind::Color,
+    AttributeKind::JointWeights,
+    AttributeKind::JointIndices,
+    AttributeKind::Uv2,
+    AttributeKind::AmbientOcclusion,
+];
+const KIND_MASK: u32 = (1 << KINDS.len()) - 1;
+
+const FORMATS: [VertexFormat; 9] = [
+    VertexFormat::Sint16x3,
+    VertexFormat::Float16x3,
+    VertexFormat::Unorm16x2,
+    VertexFormat::Snorm10_10_10_2,
+    VertexFormat::Snorm16x4,
+    VertexFormat::Unorm8x4,
+    VertexFormat::Uint8x4,
+    VertexFormat::Uint16x4,
+    VertexFormat::Unorm8,
+];
+
+/// read_mgb が返すエラー (`anyhow::Error::downcast_ref` で取り出せる)
+#[derive(Debug)]
+pub enum MgbError {
+    /// 先頭が "MESHGUARD" ではない
+    BadMagic,
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// 途中でデータが尽きた
+    Truncated,
+    /// ヘッダは読めたが中身が矛盾している
+    Invalid(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for MgbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MgbError::BadMagic => write!(f, "not a meshguard binary"),
+            MgbError::UnsupportedVersion { major, minor } => write!(f, "unsupported mgb version {}.{}", major, minor),
+            MgbError::Truncated => write!(f, "unexpected end of mgb"),
+            MgbError::Invalid(msg) => write!(f, "invalid mgb: {}", msg),
+            MgbError::Io(e) => write!(f, "failed to read mgb: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MgbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MgbError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MgbError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof { MgbError::Truncated } else { MgbError::Io(e) }
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> MgbError {
+    MgbError::Invalid(msg.into())
+}
+
+fn kind_code(kind: AttributeKind) -> u8 {
+    KINDS.iter().position(|&k| k == kind).unwrap() as u8
+}
+
+fn format_code(format: VertexFormat) -> u8 {
+    FORMATS.iter().position(|&f| f == format).unwrap() as u8
+}
+
+fn u32_field(v: usize, what: &str) -> Result<[u8; 4]> {
+    Ok(u32::try_from(v).with_context(|| format!("{} {} does not fit in u32", what, v))?.to_le_bytes())
+}
+
+/// PackedMeshを .mgb として書き出す
+pub fn write_mgb(mesh: &PackedMesh, mut writer: impl Write) -> Result<()> {
+    let layout = &mesh.layout;
+    ensure!(mesh.interleaved.len() == mesh.vertex_count * layout.stride, "interleaved buffer is {} bytes, expected {}", mesh.interleaved.len(), mesh.vertex_count * layout.stride);
+
+    let mut flags = layout.attributes.iter().fold(0u32, |f, a| f | 1 << kind_code(a.kind));
+    if layout.endianness == Endianness::Big { flags |= FLAG_BIG_ENDIAN; }
+    if mesh.integrity_tag.is_some() { flags |= FLAG_INTEGRITY; }
+    if !mesh.submeshes.is_empty() { flags |= FLAG_SUBMESHES; }
+    if mesh.topology == PrimitiveTopology::TriangleStrip { flags |= FLAG_TRIANGLE_STRIP; }
+
+    let mut out = Vec::with_capacity(64 + mesh.interleaved.len() + mesh.indices.len() * 4);
+    out.extend_from_slice(MGB_MAGIC);
+    out.extend_from_slice(&[MGB_VERSION.0, MGB_VERSION.1, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&u32_field(mesh.vertex_count, "vertex count")?);
+    out.extend_from_slice(&u32_field(mesh.indices.len(), "index count")?);
+    out.extend_from_slice(&flags.to_le_bytes());
+    out.extend_from_slice(&u32_field(layout.stride, "stride")?);
+    for v in mesh.pos_scale.iter().chain(&mesh.pos_offset) {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out.extend_from_slice(&mesh.pos_bits);
+    out.push(match mesh.indices { IndexBuffer::U16(_) => 2, IndexBuffer::U32(_) => 4 });
+    out.extend_from_slice(&u32_field(mesh.perm_seeds.len(), "seed count")?);
+    for s in &mesh.perm_seeds {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    for a in &layout.attributes {
+        let offset = u16::try_from(a.offset).with_context(|| format!("attribute offset {} does not fit in u16", a.offset))?;
+        out.extend_from_slice(&[kind_code(a.kind), format_code(a.format)]);
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    out.extend_from_slice(&mesh.interleaved);
+    match &mesh.indices {
+        IndexBuffer::U16(v) => v.iter().for_each(|i| out.extend_from_slice(&i.to_le_bytes())),
+        IndexBuffer::U32(v) => v.iter().for_each(|i| out.extend_from_slice(&i.to_le_bytes())),
+    }
+    if let Some(tag) = &mesh.integrity_tag {
+        out.extend_from_slice(tag);
+    }
+    if !mesh.submeshes.is_empty() {
+        out.extend_from_slice(&u32_field(mesh.submeshes.len(), "submesh count")?);
+        for sm in &mesh.submeshes {
+            out.extend_from_slice(&u32_field(sm.index_offset, "submesh index offset")?);
+            out.extend_from_slice(&u32_field(sm.index_count, "submesh index count")?);
+            out.extend_from_slice(&sm.material_id.to_le_bytes());
+        }
+    }
+    writer.write_all(&out).context("failed to write mgb")
+}
+
+fn take<const N: usize>(r: &mut impl Read) -> Result<[u8; N], MgbError> {
+    let mut b = [0u8; N];
+    r.read_exact(&mut b)?;
+    Ok(b)
+}
+
+fn take_vec(r: &mut impl Read, len: usize) -> Result<Vec<u8>, MgbError> {
+    // 長さはファイル由来なので先に確保しない
+    let mut v = Vec::new();
+    r.take(len as u64).read_to_end(&mut v)?;
+    if v.len() != len {
+        return Err(MgbError::Truncated);
+    }
+    Ok(v)
+}
+
+/// `write_mgb` で書いたものを読む
+/// 壊れた入力では `MgbError` を返し、panicはしない
+pub fn read_mgb(reader: impl Read) -> Result<PackedMesh> {
+    Ok(read_mgb_inner(reader)?)
+}
+
+fn read_mgb_inner(mut reader: impl Read) -> Result<PackedMesh, MgbError> {
+    let r = &mut reader;
+    let header = take::<16>(r)?;
+    if &header[..9] != MGB_MAGIC {
+        return Err(MgbError::BadMagic);
+    }
+    if header[9] != MGB_VERSION.0 {
+        return Err(MgbError::UnsupportedVersion { major: header[9], minor: header[10] });
+    }
+    let u32_le = |b: [u8; 4]| u32::from_le_bytes(b) as usize;
+    let vertex_count = u32_le(take(r)?);
+    let index_count = u32_le(take(r)?);
+    let flags = u32::from_le_bytes(take(r)?);
+    let stride = u32_le(take(r)?);
+    let mut floats = [0f32; 6];
+    for f in &mut floats {
+        *f = f32::from_le_bytes(take(r)?);
+    }
+    let [b0, b1, b2, index_width] = take::<4>(r)?;
+    let seed_count = u32_le(take(r)?);
+    let perm_seeds = (0..seed_count).map(|_| take(r).map(u64::from_le_bytes)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut attributes: Vec<VertexAttribute> = Vec::new();
+    for _ in 0..(flags & KIND_MASK).count_ones() {
+        let [k, f, o0, o1] = take::<4>(r)?;
+        let kind = *KINDS.get(k as usize).ok_or_else(|| invalid(format!("unknown attribute kind {}", k)))?;
+        let format = *FORMATS.get(f as usize).ok_or_else(|| invalid(format!("unknown vertex format {}", f)))?;
+        if flags & (1 << k) == 0 || attributes.iter().any(|a| a.kind == kind) {
+            return Err(invalid("attribute table does not match the flags"));
+        }
+        attributes.push(VertexAttribute { kind, format, offset: u16::from_le_bytes([o0, o1]) as usize });
+    }
+    let endianness = if flags & FLAG_BIG_ENDIAN != 0 { Endianness::Big } else { Endianness::Little };
+
+    let interleaved = take_vec(r, vertex_count.checked_mul(stride).ok_or_else(|| invalid("vertex buffer size overflows"))?)?;
+    let raw = take_vec(r, index_count.checked_mul(index_width as usize).ok_or_else(|| invalid("index buffer size overflows"))?)?;
+    let indices = match index_width {
+        2 => IndexBuffer::U16(raw.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect()),
+        4 => IndexBuffer::U32(raw.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()),
+        w => return Err(invalid(format!("unsupported index width {}", w))),
+    };
+    let integrity_tag = if flags & FLAG_INTEGRITY != 0 { Some(take::<32>(r)?) } else { None };
+    let mut submeshes = Vec::new();
+    if flags & FLAG_SUBMESHES != 0 {
+        for _ in 0..u32_le(take(r)?) {
+            let (index_offset, index_count) = (u32_le(take(r)?), u32_le(take(r)?));
+            let material_id = u32::from_le_bytes(take(r)?);
+            submeshes.push(SubmeshRange { index_offset, index_count, material_id });
+        }
+    }
+
+    let mesh = PackedMesh {
+        interleaved,
+        vertex_count,
+        indices,
+        pos_scale: [floats[0], floats[1], floats[2]],
+        pos_offset: [floats[3], floats[4], floats[5]],
+        pos_bits: [b0, b1, b2],
+        perm_seeds,
+        layout: VertexLayout { attributes, stride, endianness },
+        integrity_tag,
+        submeshes,
+        topology: if flags & FLAG_TRIANGLE_STRIP != 0 { PrimitiveTopology::TriangleStrip } else { PrimitiveTopology::Triangles },
+    };
+    mesh.validate().map_err(|e| match e {
+        MeshGuardError::InvalidInput(msg) => invalid(msg),
+        e => invalid(e.to_string()),
+    })?;
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::{extract_positions, pack_interleave_permute, pack_multi_permute, pack_with_config, unpack_mesh, MeshAttributes, PackConfig};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs, quantize_vertex_colors};
+
+    fn assert_same(a: &PackedMesh, b: &PackedMesh) {
+        assert_eq!(a.interleaved, b.interleaved);
+        assert_eq!((a.vertex_count, &a.indices, &a.perm_seeds, &a.layout), (b.vertex_count, &b.indices, &b.perm_seeds, &b.layout));
+        assert_eq!((a.pos_scale, a.pos_offset, a.pos_bits, a.integrity_tag), (b.pos_scale, b.pos_offset, b.pos_bits, b.integrity_tag));
+        assert_eq!((&a.submeshes, a.topology), (&b.submeshes, b.topology));
+    }
+
+    #[test]
+    fn mgb_round_trip() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let qcol = quantize_vertex_colors(&[[1.0,0.5,0.0,1.0]; 3]);
+
+        let mut tagged = pack_multi_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2, 2, 1, 0]), &[5, 6]).unwrap();
+        tagged.integrity_tag = Some([7; 32]);
+        tagged.submeshes = vec![SubmeshRange { index_offset: 0, index_count: 3, material_id: 1 }, SubmeshRange { index_offset: 3, index_count: 3, material_id: 9 }];
+        let meshes = [
+            pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 42).unwrap(),
+            tagged,
+            pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv).with_colors(&qcol), None, 1,
+                &PackConfig::new().colors(true).align_attributes(true).endianness(Endianness::Big)).unwrap(),
+            pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), Some(&[0, 1, 2, u32::MAX, 2, 1, 0]), 2,
+                &PackConfig::new().topology(PrimitiveTopology::TriangleStrip)).unwrap(),
+            pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv).with_uvs2(&quv), None, 3, &PackConfig::new().uv_channels(2)).unwrap(),
+        ];
+        for mesh in &meshes {
+            let mut buf = Vec::new();
+            write_mgb(mesh, &mut buf).unwrap();
+            assert_eq!(&buf[..9], MGB_MAGIC);
+            let back = read_mgb(&buf[..]).unwrap();
+            assert_same(mesh, &back);
+
+            let mut again = Vec::new();
+            write_mgb(&back, &mut again).unwrap();
+            assert_eq!(buf, again);
+            assert!(read_mgb(&buf[..buf.len() - 1]).is_err());
+        }
+    }
+
+    fn mgb_error(data: &[u8]) -> MgbError {
+        let err = read_mgb(data).unwrap_err();
+        err.downcast::<MgbError>().unwrap()
+    }
+
+    #[test]
+    fn typed_errors() {
+        assert!(matches!(mgb_error(b"NOTAMESHGUARD\0\0\0"), MgbError::BadMagic));
+        let mut buf = MGB_MAGIC.to_vec();
+        buf.extend_from_slice(&[9, 1, 0, 0, 0, 0, 0]);
+        assert!(matches!(mgb_error(&buf), MgbError::UnsupportedVersion { major: 9, minor: 1 }));
+        assert!(matches!(mgb_error(&MGB_MAGIC[..5]), MgbError::Truncated));
+    }
+
+    /// 書き出しは通るが読み込みで弾かれるメッシュ
+    fn rejected(mesh: &PackedMesh) -> String {
+        let mut buf = Vec::new();
+        write_mgb(mesh, &mut buf).unwrap();
+        match mgb_error(&buf) {
+            MgbError::Invalid(msg) => msg,
+            e => panic!("expected Invalid, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn inconsistent_metadata_is_rejected() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let good = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 42).unwrap();
+
+        let mut m = good.clone();
+        m.pos_bits = [16, 0, 16];
+        assert_eq!(rejected(&m), "position bit depth 0 is outside 1..=16");
+        m.pos_bits = [17, 16, 16];
+        assert_eq!(rejected(&m), "position bit depth 17 is outside 1..=16");
+
+        let mut m = good.clone();
+        m.layout = VertexLayout { attributes: Vec::new(), stride: 0, endianness: Endianness::Little };
+        m.interleaved.clear();
+        assert_eq!(rejected(&m), "stride is 0");
+
+        let mut m = good.clone();
+        m.layout.stride = 24;
+        m.interleaved = vec![0; 3 * 24];
+        assert_eq!(rejected(&m), "stride 24 does not match the attribute table (14 bytes)");
+        // アラインメント分の詰め物は読める
+        m.layout.stride = 16;
+        m.interleaved = vec![0; 3 * 16];
+        let mut buf = Vec::new();
+        write_mgb(&m, &mut buf).unwrap();
+        assert!(read_mgb(&buf[..]).is_ok());
+
+        let mut m = good.clone();
+        m.indices = IndexBuffer::U16(vec![0, 1, 3]);
+        assert_eq!(rejected(&m), "index 3 at position 2 out of bounds for 3 vertices");
+        m.indices = IndexBuffer::U16(vec![0, 1, 2, u16::MAX, 2, 1, 0]);
+        assert!(rejected(&m).starts_with("index 65535 at position 3"));
+        // ストリップならリスタート値は通す
+        m.topology = PrimitiveTopology::TriangleStrip;
+        let mut buf = Vec::new();
+        write_mgb(&m, &mut buf).unwrap();
+        assert_eq!(read_mgb(&buf[..]).unwrap().indices_u32()[3], u32::MAX);
+    }
+
+    #[test]
+    fn corrupted_input_never_panics() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let mut buf = Vec::new();
+        write_mgb(&pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 42).unwrap(), &mut buf).unwrap();
+        for len in 0..buf.len() {
+            assert!(matches!(mgb_error(&buf[..len]), MgbError::Truncated | MgbError::BadMagic), "len {}", len);
+        }
+        // 1バイトずつ壊しても読み終えるかエラーになるだけ
+        for i in 0..buf.len() {
+            for x in [0x01, 0x80, 0xff] {
+                let mut bad = buf.clone();
+                bad[i] ^= x;
+                // 読めたものは復号してもpanicしない
+                if let Ok(m) = read_mgb(&bad[..]) {
+                    let _ = (m.iter_vertices().count(), extract_positions(&m), unpack_mesh(&m));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use meshguard_pack::PackedMesh;
+
+/// MessagePackにする。interleavedはbin型 (生バイト) で入る
+/// 構造体はフィールド名を持たない配列形式なので、読む側も同じバージョンの `PackedMesh` を使うこと
+pub fn to_msgpack(mesh: &PackedMesh) -> Result<Vec<u8>> {
+    rmp_serde::to_vec(mesh).context("failed to encode PackedMesh as MessagePack")
+}
+
+/// 外から来たバイト列を想定し、`PackedMesh::validate` を通らないものはエラーにする
+pub fn from_msgpack(data: &[u8]) -> Result<PackedMesh> {
+    let mesh: PackedMesh = rmp_serde::from_slice(data).context("failed to decode PackedMesh from MessagePack")?;
+    mesh.validate().context("decoded MessagePack mesh is inconsistent")?;
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::pack_interleave_permute;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn msgpack_round_trip() {
+        // 70000頂点はu32インデックスになる
+        for n in [3usize, 100, 70_000] {
+            let pos: Vec<[f32; 3]> = (0..n).map(|i| { let t = i as f32; [t.sin(), t * 0.01, t.cos()] }).collect();
+            let idx: Vec<u32> = (0..n as u32 / 3 * 3).collect();
+            let mesh = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&vec![[0.0, 0.0, 1.0]; n]), &quantize_uvs(&vec![[0.5, 0.5]; n]), Some(&idx), n as u64).unwrap();
+            let data = to_msgpack(&mesh).unwrap();
+            let back = from_msgpack(&data).unwrap();
+            assert_eq!(back.interleaved, mesh.interleaved);
+            assert_eq!(back.indices, mesh.indices);
+            assert_eq!(back.layout, mesh.layout);
+            assert_eq!((back.vertex_count, back.pos_scale, back.pos_offset, back.pos_bits, &back.perm_seeds), (mesh.vertex_count, mesh.pos_scale, mesh.pos_offset, mesh.pos_bits, &mesh.perm_seeds));
+
+            // bin型なら interleaved のバイト列がそのまま並ぶ
+            let raw = &mesh.interleaved;
+            assert!(data.windows(raw.len()).any(|w| w == &raw[..]));
+            assert!(data.len() < raw.len() + mesh.indices.len() * 5 + 256);
+        }
+        assert!(from_msgpack(&[0xc1]).is_err());
+    }
+
+    #[test]
+    fn corrupted_payload_is_rejected() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let good = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&[[0.0, 0.0, 1.0]; 3]), &quantize_uvs(&[[0.5, 0.5]; 3]), Some(&[0, 1, 2]), 7).unwrap();
+        let mut zero_stride = good.clone();
+        zero_stride.layout.stride = 0;
+        let mut bad_offset = good.clone();
+        bad_offset.layout.attributes[2].offset = 200;
+        let mut bad_index = good.clone();
+        bad_index.indices = meshguard_pack::IndexBuffer::U16(vec![0, 1, 9]);
+        for m in [zero_stride, bad_offset, bad_index] {
+            // 書く側は検査しないので、壊れたメッシュもそのままバイト列になる
+            let data = to_msgpack(&m).unwrap();
+            assert!(from_msgpack(&data).is_err());
+        }
+        assert!(from_msgpack(&to_msgpack(&good).unwrap()).is_ok());
+    }
+}
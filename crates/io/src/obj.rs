@@ -0,0 +1,154 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// 位置, 法線, UV, 三角形インデックス
+pub type ObjMesh = (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<u32>);
+
+/// OBJを読んで (位置, 法線, UV, インデックス) を返す
+/// 面は `v`, `v/t`, `v//n`, `v/t/n` に対応し、多角形は扇状に三角形化する
+/// 同じ (v, t, n) の組は1頂点にまとめ、組が違えば別の頂点として展開する
+pub fn parse_obj(path: &Path) -> Result<ObjMesh> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    parse_obj_reader(BufReader::new(file)).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn parse_obj_reader(mut reader: impl BufRead) -> Result<ObjMesh> {
+    let mut src_pos: Vec<[f32; 3]> = Vec::new();
+    let mut src_nor: Vec<[f32; 3]> = Vec::new();
+    let mut src_uv: Vec<[f32; 2]> = Vec::new();
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    let mut has_normals = false;
+    let mut has_uvs = false;
+    let mut unique: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+
+    // コメントがUTF-8とは限らないので行はバイト列で読む
+    let mut buf = Vec::new();
+    let mut line_no = 0;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        line_no += 1;
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        let Some(tag) = tokens.next() else { continue };
+        match tag {
+            "v" => src_pos.push(parse_floats::<3>(tokens, line_no)?),
+            "vn" => src_nor.push(parse_floats::<3>(tokens, line_no)?),
+            "vt" => src_uv.push(parse_floats::<2>(tokens, line_no)?),
+            "f" => {
+                let mut face = Vec::new();
+                for tok in tokens {
+                    let (v, t, n) = parse_face_vertex(tok, src_pos.len(), src_uv.len(), src_nor.len())
+                        .with_context(|| format!("line {}: bad face vertex '{}'", line_no, tok))?;
+                    has_uvs |= t.is_some();
+                    has_normals |= n.is_some();
+                    let idx = *unique.entry((v, t, n)).or_insert_with(|| {
+                        positions.push(src_pos[v]);
+                        normals.push(n.map_or([0.0; 3], |n| src_nor[n]));
+                        uvs.push(t.map_or([0.0; 2], |t| src_uv[t]));
+                        (positions.len() - 1) as u32
+                    });
+                    face.push(idx);
+                }
+                if face.len() < 3 {
+                    bail!("line {}: face needs at least 3 vertices", line_no);
+                }
+                for i in 1..face.len() - 1 {
+                    indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_normals { normals.clear(); }
+    if !has_uvs { uvs.clear(); }
+    Ok((positions, normals, uvs, indices))
+}
+
+fn parse_floats<'a, const N: usize>(tokens: impl Iterator<Item = &'a str>, line_no: usize) -> Result<[f32; N]> {
+    let mut out = [0.0; N];
+    let mut count = 0;
+    for (slot, tok) in out.iter_mut().zip(tokens) {
+        *slot = tok.parse().with_context(|| format!("line {}: bad number '{}'", line_no, tok))?;
+        count += 1;
+    }
+    if count < N {
+        bail!("line {}: expected {} components, got {}", line_no, N, count);
+    }
+    Ok(out)
+}
+
+/// `v/t/n` の1要素を0始まりの番号にする (負の番号は末尾からの相対)
+fn parse_face_vertex(tok: &str, n_pos: usize, n_uv: usize, n_nor: usize) -> Result<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = tok.split('/');
+    let v = resolve(parts.next(), n_pos)?.context("missing position index")?;
+    let t = resolve(parts.next(), n_uv)?;
+    let n = resolve(parts.next(), n_nor)?;
+    Ok((v, t, n))
+}
+
+fn resolve(part: Option<&str>, len: usize) -> Result<Option<usize>> {
+    let Some(part) = part.filter(|p| !p.is_empty()) else { return Ok(None) };
+    let i: i64 = part.parse()?;
+    let idx = if i > 0 { i - 1 } else { len as i64 + i };
+    if i == 0 || idx < 0 || idx >= len as i64 {
+        bail!("index {} out of range (have {})", i, len);
+    }
+    Ok(Some(idx as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_all_face_formats() {
+        let src = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+f 1//1 3//1 4//1
+f 1/1 -2/3 -1/2
+";
+        let (pos, nor, uv, idx) = parse_obj_reader(src.as_bytes()).unwrap();
+        assert_eq!(idx.len(), 9);
+        // 1/1/1 と 1//1 と 1/1 は別の頂点になる
+        assert_eq!(pos.len(), 9);
+        assert_eq!(nor.len(), pos.len());
+        assert_eq!(uv.len(), pos.len());
+        assert_eq!(pos[idx[8] as usize], [0.0, 1.0, 0.0]);
+        assert_eq!(uv[idx[7] as usize], [1.0, 1.0]);
+    }
+
+    #[test]
+    fn parses_cube_sample() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../cube.obj");
+        let (pos, nor, uv, idx) = parse_obj(&path).unwrap();
+        assert_eq!(pos.len(), 24);
+        assert_eq!(nor.len(), 24);
+        assert!(uv.is_empty());
+        assert_eq!(idx.len(), 36);
+    }
+
+    #[test]
+    fn rejects_out_of_range_index() {
+        assert!(parse_obj_reader("v 0 0 0\nf 1 2 3\n".as_bytes()).is_err());
+    }
+}
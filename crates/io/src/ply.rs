@@ -0,0 +1,300 @@
+use anyhow::{bail, Context, Result};
+use std::io::{BufRead, BufReader, Read};
+
+/// PLYから取り出したメッシュ
+/// 法線やUVがないファイルでは空のVecになる
+#[derive(Clone, Debug, Default)]
+pub struct PlyMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLe,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Scalar {
+    I8, U8, I16, U16, I32, U32, F32, F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => bail!("unknown property type '{}'", name),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    fn decode_le(self, b: &[u8]) -> f64 {
+        match self {
+            Scalar::I8 => b[0] as i8 as f64,
+            Scalar::U8 => b[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(b[..8].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Scalar { name: String, ty: Scalar },
+    List { name: String, count: Scalar, item: Scalar },
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// PLY本体の読み出し位置
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], pos: usize },
+}
+
+impl Body<'_> {
+    fn next(&mut self, ty: Scalar) -> Result<f64> {
+        match self {
+            Body::Ascii(tokens) => {
+                let tok = tokens.next().context("unexpected end of data")?;
+                tok.parse().with_context(|| format!("bad number '{}'", tok))
+            }
+            Body::Binary { data, pos } => {
+                let end = *pos + ty.size();
+                let bytes = data.get(*pos..end).context("unexpected end of data")?;
+                *pos = end;
+                Ok(ty.decode_le(bytes))
+            }
+        }
+    }
+}
+
+/// ASCIIとbinary_little_endianのPLYを読む
+/// 頂点の x, y, z は必須、nx, ny, nz と s, t (u, v) は任意
+pub fn parse_ply(reader: impl Read) -> Result<PlyMesh> {
+    let mut reader = BufReader::new(reader);
+    let (format, elements) = parse_header(&mut reader)?;
+
+    // 本体はまとめて読み込み、バイナリはスライスから直接デコードする
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let text;
+    let mut body = match format {
+        Format::Ascii => {
+            text = String::from_utf8_lossy(&data);
+            Body::Ascii(text.split_ascii_whitespace())
+        }
+        Format::BinaryLe => Body::Binary { data: &data, pos: 0 },
+    };
+
+    let mut mesh = PlyMesh::default();
+    for el in &elements {
+        match el.name.as_str() {
+            "vertex" => read_vertices(el, &mut body, &mut mesh)?,
+            "face" => read_faces(el, &mut body, &mut mesh)?,
+            _ => skip_element(el, &mut body)?,
+        }
+    }
+    Ok(mesh)
+}
+
+fn parse_header(reader: &mut impl BufRead) -> Result<(Format, Vec<Element>)> {
+    let mut line = String::new();
+    let mut next_line = |reader: &mut dyn BufRead| -> Result<String> {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("unexpected end of header");
+        }
+        Ok(line.trim().to_string())
+    };
+
+    if next_line(reader)? != "ply" {
+        bail!("missing 'ply' magic");
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let l = next_line(reader)?;
+        let tokens: Vec<&str> = l.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["end_header"] => break,
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::BinaryLe),
+            ["format", other, _] => bail!("unsupported PLY format '{}'", other),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().with_context(|| format!("bad element count '{}'", count))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let el = elements.last_mut().context("property before element")?;
+                el.properties.push(Property::List { name: name.to_string(), count: Scalar::parse(count)?, item: Scalar::parse(item)? });
+            }
+            ["property", ty, name] => {
+                let el = elements.last_mut().context("property before element")?;
+                el.properties.push(Property::Scalar { name: name.to_string(), ty: Scalar::parse(ty)? });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => bail!("unrecognized header line '{}'", l),
+        }
+    }
+    Ok((format.context("missing format line")?, elements))
+}
+
+fn read_vertices(el: &Element, body: &mut Body, mesh: &mut PlyMesh) -> Result<()> {
+    let slot = |names: &[&str]| el.properties.iter().position(|p| matches!(p, Property::Scalar { name, .. } if names.contains(&name.as_str())));
+    let pos = [slot(&["x"]), slot(&["y"]), slot(&["z"])];
+    let nor = [slot(&["nx"]), slot(&["ny"]), slot(&["nz"])];
+    let uv = [slot(&["s", "u", "texture_u"]), slot(&["t", "v", "texture_v"])];
+    if pos.iter().any(Option::is_none) {
+        bail!("vertex element needs x, y and z");
+    }
+    let has_nor = nor.iter().all(Option::is_some);
+    let has_uv = uv.iter().all(Option::is_some);
+
+    let mut values = vec![0.0f64; el.properties.len()];
+    for _ in 0..el.count {
+        for (i, p) in el.properties.iter().enumerate() {
+            values[i] = match p {
+                Property::Scalar { ty, .. } => body.next(*ty)?,
+                Property::List { count, item, .. } => {
+                    let n = body.next(*count)? as usize;
+                    for _ in 0..n { body.next(*item)?; }
+                    0.0
+                }
+            };
+        }
+        let get = |s: Option<usize>| values[s.unwrap()] as f32;
+        mesh.positions.push(pos.map(get));
+        if has_nor { mesh.normals.push(nor.map(get)); }
+        if has_uv { mesh.uvs.push(uv.map(get)); }
+    }
+    Ok(())
+}
+
+fn read_faces(el: &Element, body: &mut Body, mesh: &mut PlyMesh) -> Result<()> {
+    let mut face = Vec::new();
+    for _ in 0..el.count {
+        for p in &el.properties {
+            match p {
+                Property::List { name, count, item } if name == "vertex_indices" || name == "vertex_index" => {
+                    let n = body.next(*count)? as usize;
+                    face.clear();
+                    for _ in 0..n {
+                        let i = body.next(*item)?;
+                        if i < 0.0 || i as usize >= mesh.positions.len() {
+                            bail!("face index {} out of range", i);
+                        }
+                        face.push(i as u32);
+                    }
+                    for i in 1..face.len().saturating_sub(1) {
+                        mesh.indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                    }
+                }
+                Property::List { count, item, .. } => {
+                    let n = body.next(*count)? as usize;
+                    for _ in 0..n { body.next(*item)?; }
+                }
+                Property::Scalar { ty, .. } => { body.next(*ty)?; }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn skip_element(el: &Element, body: &mut Body) -> Result<()> {
+    for _ in 0..el.count {
+        for p in &el.properties {
+            match p {
+                Property::Scalar { ty, .. } => { body.next(*ty)?; }
+                Property::List { count, item, .. } => {
+                    let n = body.next(*count)? as usize;
+                    for _ in 0..n { body.next(*item)?; }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii_with_normals_and_uvs() {
+        let src = "\
+ply
+format ascii 1.0
+comment made by hand
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 0 0 1 0 0
+1 0 0 0 0 1 1 0
+1 1 0 0 0 1 1 1
+0 1 0 0 0 1 0 1
+4 0 1 2 3
+";
+        let mesh = parse_ply(src.as_bytes()).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.normals, vec![[0.0, 0.0, 1.0]; 4]);
+        assert_eq!(mesh.uvs[2], [1.0, 1.0]);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn parses_binary_without_normals() {
+        let mut data = b"ply\nformat binary_little_endian 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nproperty uchar red\nelement face 1\nproperty list uchar uint vertex_indices\nend_header\n".to_vec();
+        for p in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.5]] {
+            for c in p { data.extend_from_slice(&c.to_le_bytes()); }
+            data.push(200);
+        }
+        data.push(3);
+        for i in [0u32, 1, 2] { data.extend_from_slice(&i.to_le_bytes()); }
+
+        let mesh = parse_ply(&data[..]).unwrap();
+        assert_eq!(mesh.positions[2], [0.0, 2.0, 0.5]);
+        assert!(mesh.normals.is_empty());
+        assert!(mesh.uvs.is_empty());
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+
+        assert!(parse_ply(&data[..data.len() - 2]).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{bail, Context, Result};
+use meshguard_process::weld_vertices;
+use std::io::Read;
+
+/// 位置, 法線, 三角形インデックス
+pub type StlMesh = (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<u32>);
+
+/// 溶接のデフォルト距離
+pub const DEFAULT_WELD_EPSILON: f32 = 1e-5;
+
+/// バイナリSTLを読み、近い頂点を溶接してインデックス付きメッシュにする
+pub fn parse_stl_binary(reader: impl Read) -> Result<StlMesh> {
+    parse_stl_binary_with_epsilon(reader, DEFAULT_WELD_EPSILON)
+}
+
+/// 距離epsilon以内の頂点を同じ頂点とみなす
+/// 法線は溶接された三角形の面法線の平均
+pub fn parse_stl_binary_with_epsilon(mut reader: impl Read, epsilon: f32) -> Result<StlMesh> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() < 84 {
+        bail!("STL too short: {} bytes", data.len());
+    }
+    let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+    let expected = 84 + count * 50;
+    if data.len() < expected {
+        bail!("STL truncated: header says {} triangles ({} bytes), got {} bytes", count, expected, data.len());
+    }
+
+    let f = |b: &[u8], i: usize| f32::from_le_bytes(b[i * 4..i * 4 + 4].try_into().unwrap());
+    let mut triangles = Vec::with_capacity(count);
+    for rec in data[84..expected].chunks_exact(50) {
+        let normal = [f(rec, 0), f(rec, 1), f(rec, 2)];
+        let v = [0, 1, 2].map(|k| [f(rec, 3 + k * 3), f(rec, 4 + k * 3), f(rec, 5 + k * 3)]);
+        triangles.push((normal, v));
+    }
+    weld(&triangles, epsilon)
+}
+
+/// ASCII STL (`solid ... facet normal ... vertex ...`)
+pub fn parse_stl_ascii(mut reader: impl Read, epsilon: f32) -> Result<StlMesh> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut triangles = Vec::new();
+    let mut normal = [0.0; 3];
+    let mut verts = Vec::with_capacity(3);
+    for (line_no, line) in text.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let floats = |t: &[&str]| -> Result<[f32; 3]> {
+            let mut out = [0.0; 3];
+            for (o, s) in out.iter_mut().zip(t) {
+                *o = s.parse().with_context(|| format!("line {}: bad number '{}'", line_no + 1, s))?;
+            }
+            if t.len() < 3 { bail!("line {}: expected 3 components", line_no + 1); }
+            Ok(out)
+        };
+        match tokens.as_slice() {
+            ["facet", "normal", rest @ ..] => { normal = floats(rest)?; verts.clear(); }
+            ["vertex", rest @ ..] => verts.push(floats(rest)?),
+            ["endfacet"] => {
+                if verts.len() != 3 {
+                    bail!("line {}: facet has {} vertices", line_no + 1, verts.len());
+                }
+                triangles.push((normal, [verts[0], verts[1], verts[2]]));
+            }
+            _ => {}
+        }
+    }
+    weld(&triangles, epsilon)
+}
+
+/// `meshguard_process::weld_vertices` で位置を溶接し、面法線を溶接後の頂点ごとに足し合わせる
+fn weld(triangles: &[([f32; 3], [[f32; 3]; 3])], epsilon: f32) -> Result<StlMesh> {
+    // epsilonが0以下ならビット一致のみで溶接する
+    let soup: Vec<[f32; 3]> = triangles.iter().flat_map(|(_, v)| *v).collect();
+    let welded = weld_vertices(&soup, &[], &[], epsilon)?;
+    let mut normal_sum = vec![[0.0f32; 3]; welded.positions.len()];
+    for ((stored, v), tri) in triangles.iter().zip(welded.remap.chunks_exact(3)) {
+        let n = face_normal(stored, v);
+        for &i in tri {
+            for a in 0..3 { normal_sum[i as usize][a] += n[a]; }
+        }
+    }
+
+    let normals = normal_sum
+        .into_iter()
+        .map(|n| {
+            let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+            if len > 0.0 { n.map(|c| c / len) } else { [0.0, 0.0, 1.0] }
+        })
+        .collect();
+    Ok((welded.positions, normals, welded.remap))
+}
+
+/// 保存された法線が0なら頂点から計算し直す
+fn face_normal(stored: &[f32; 3], v: &[[f32; 3]; 3]) -> [f32; 3] {
+    let len = (stored[0]*stored[0] + stored[1]*stored[1] + stored[2]*stored[2]).sqrt();
+    if len > 1e-12 {
+        return stored.map(|c| c / len);
+    }
+    let e1 = [0, 1, 2].map(|a| v[1][a] - v[0][a]);
+    let e2 = [0, 1, 2].map(|a| v[2][a] - v[0][a]);
+    let c = [e1[1]*e2[2] - e1[2]*e2[1], e1[2]*e2[0] - e1[0]*e2[2], e1[0]*e2[1] - e1[1]*e2[0]];
+    let len = (c[0]*c[0] + c[1]*c[1] + c[2]*c[2]).sqrt();
+    if len > 0.0 { c.map(|x| x / len) } else { [0.0; 3] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary_quad() -> Vec<u8> {
+        let mut data = vec![0u8; 80];
+        data.extend_from_slice(&2u32.to_le_bytes());
+        let tris = [
+            [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+            [[0.0, 0.0, 0.0], [1.0, 1.0, 1e-7], [0.0, 1.0, 0.0]],
+        ];
+        for t in tris {
+            for c in [0.0f32, 0.0, 0.0] { data.extend_from_slice(&c.to_le_bytes()); }
+            for v in t { for c in v { data.extend_from_slice(&c.to_le_bytes()); } }
+            data.extend_from_slice(&0u16.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn welds_binary_triangles() {
+        let (pos, nor, idx) = parse_stl_binary(&binary_quad()[..]).unwrap();
+        assert_eq!(pos.len(), 4);
+        assert_eq!(idx, vec![0, 1, 2, 0, 2, 3]);
+        assert!(nor.iter().all(|n| (n[2] - 1.0).abs() < 1e-5));
+
+        // epsilonを0にすると微妙にずれた頂点は別になる
+        let (pos, _, _) = parse_stl_binary_with_epsilon(&binary_quad()[..], 0.0).unwrap();
+        assert_eq!(pos.len(), 5);
+
+        let data = binary_quad();
+        assert!(parse_stl_binary(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn parses_ascii() {
+        let src = "solid t\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\nendsolid t\n";
+        let (pos, nor, idx) = parse_stl_ascii(src.as_bytes(), DEFAULT_WELD_EPSILON).unwrap();
+        assert_eq!(pos.len(), 3);
+        assert_eq!(nor[0], [0.0, 0.0, 1.0]);
+        assert_eq!(idx, vec![0, 1, 2]);
+    }
+}
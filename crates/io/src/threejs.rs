@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use meshguard_pack::{AttributeKind, Endianness, IndexBuffer, PackedMesh, PrimitiveTopology, VertexAttribute, VertexFormat};
+use serde_json::{json, Map, Value};
+
+/// 属性の生バイトを頂点順に取り出し、リトルエンディアン (JSのTypedArray) に揃える
+fn attribute_bytes(mesh: &PackedMesh, attr: &VertexAttribute) -> Vec<u8> {
+    let c = attr.format.component_size();
+    let mut out = Vec::with_capacity(mesh.vertex_count * attr.format.size());
+    for v in mesh.interleaved.chunks_exact(mesh.layout.stride) {
+        let b = &v[attr.offset..attr.offset + attr.format.size()];
+        match mesh.layout.endianness {
+            Endianness::Little => out.extend_from_slice(b),
+            Endianness::Big => b.chunks_exact(c).for_each(|comp| out.extend(comp.iter().rev())),
+        }
+    }
+    out
+}
+
+fn buffer_attribute(ty: &str, item_size: usize, normalized: bool, bytes: &[u8]) -> Value {
+    json!({ "itemSize": item_size, "type": ty, "array": BASE64.encode(bytes), "normalized": normalized })
+}
+
+/// Three.jsの `BufferGeometry` JSON (`data.attributes` / `data.index`) の形で出す
+/// `array` は数値の配列ではなく、リトルエンディアンのTypedArrayをbase64にした文字列なので
+/// 読む側で `Int16Array` などに戻してから `BufferAttribute` を作ること
+/// 座標はi16のまま出し、`userData` の `positionScale` / `positionOffset` をメッシュの scale / position にすれば元の大きさになる
+/// 法線は形式に関わらず復号してsnorm16 x3にする。oct接線とAOは出さない
+/// Three.jsは三角形リストしか描けないのでストリップはエラー
+pub fn export_threejs_json(mesh: &PackedMesh) -> Result<Value> {
+    if mesh.topology != PrimitiveTopology::Triangles {
+        bail!("Three.js BufferGeometry only supports triangle lists");
+    }
+    let mut attributes = Map::new();
+    let mut user_data = json!({ "positionScale": [1.0, 1.0, 1.0], "positionOffset": [0.0, 0.0, 0.0] });
+    for attr in &mesh.layout.attributes {
+        let (name, value) = match (attr.kind, attr.format) {
+            (AttributeKind::Position, VertexFormat::Sint16x3) => {
+                // world = q * scale + (offset + 2^(bits-1) * scale)
+                let offset: Vec<f32> = (0..3).map(|a| (mesh.pos_offset[a] as f64 + (1i64 << (mesh.pos_bits[a] - 1)) as f64 * mesh.pos_scale[a] as f64) as f32).collect();
+                user_data = json!({ "positionScale": mesh.pos_scale, "positionOffset": offset });
+                ("position", buffer_attribute("Int16Array", 3, false, &attribute_bytes(mesh, attr)))
+            }
+            (AttributeKind::Position, _) => {
+                let bytes: Vec<u8> = mesh.iter_vertices().flat_map(|v| v.position).flat_map(f32::to_le_bytes).collect();
+                ("position", buffer_attribute("Float32Array", 3, false, &bytes))
+            }
+            (AttributeKind::Normal, _) => {
+                let bytes: Vec<u8> = mesh.iter_vertices().flat_map(|v| v.normal)
+                    .flat_map(|c| ((c.clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes()).collect();
+                ("normal", buffer_attribute("Int16Array", 3, true, &bytes))
+            }
+            (AttributeKind::Uv, VertexFormat::Unorm16x2) => ("uv", buffer_attribute("Uint16Array", 2, true, &attribute_bytes(mesh, attr))),
+            (AttributeKind::Uv2, VertexFormat::Unorm16x2) => ("uv1", buffer_attribute("Uint16Array", 2, true, &attribute_bytes(mesh, attr))),
+            (AttributeKind::Color, VertexFormat::Unorm8x4) => ("color", buffer_attribute("Uint8Array", 4, true, &attribute_bytes(mesh, attr))),
+            (AttributeKind::JointWeights, VertexFormat::Unorm8x4) => ("skinWeight", buffer_attribute("Uint8Array", 4, true, &attribute_bytes(mesh, attr))),
+            (AttributeKind::JointIndices, VertexFormat::Uint8x4) => ("skinIndex", buffer_attribute("Uint8Array", 4, false, &attribute_bytes(mesh, attr))),
+            (AttributeKind::JointIndices, VertexFormat::Uint16x4) => ("skinIndex", buffer_attribute("Uint16Array", 4, false, &attribute_bytes(mesh, attr))),
+            _ => continue,
+        };
+        attributes.insert(name.to_string(), value);
+    }
+    let index = match &mesh.indices {
+        IndexBuffer::U16(v) => json!({ "type": "Uint16Array", "array": BASE64.encode(v.iter().flat_map(|i| i.to_le_bytes()).collect::<Vec<u8>>()) }),
+        IndexBuffer::U32(v) => json!({ "type": "Uint32Array", "array": BASE64.encode(v.iter().flat_map(|i| i.to_le_bytes()).collect::<Vec<u8>>()) }),
+    };
+    Ok(json!({
+        "metadata": { "version": 4.6, "type": "BufferGeometry", "generator": "meshguard" },
+        "type": "BufferGeometry",
+        "data": { "attributes": attributes, "index": index },
+        "userData": user_data,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::{pack_with_config, MeshAttributes, PackConfig};
+    use meshguard_quantize::{dequantize_positions, encode_normals_oct, quantize_positions, quantize_uvs};
+
+    fn decode_i16(v: &Value) -> Vec<i16> {
+        BASE64.decode(v.as_str().unwrap()).unwrap().chunks_exact(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect()
+    }
+
+    #[test]
+    fn threejs_buffers_decode_to_mesh() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0],[1.0,0.0,0.0],[0.0,-1.0,0.0]]), quantize_uvs(&[[0.0,0.0],[0.5,0.75],[1.0,1.0]]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let little = pack_with_config(&attrs, Some(&[0, 1, 2]), 3, &PackConfig::new()).unwrap();
+        let big = pack_with_config(&attrs, Some(&[0, 1, 2]), 3, &PackConfig::new().endianness(Endianness::Big)).unwrap();
+        let json = export_threejs_json(&little).unwrap();
+        // エンディアンはTypedArray側 (リトル) に揃うので同じJSONになる
+        assert_eq!(json, export_threejs_json(&big).unwrap());
+
+        let a = &json["data"]["attributes"];
+        assert_eq!((a["position"]["itemSize"].as_u64(), a["position"]["type"].as_str()), (Some(3), Some("Int16Array")));
+        assert_eq!((a["uv"]["itemSize"].as_u64(), a["uv"]["normalized"].as_bool()), (Some(2), Some(true)));
+        let q = decode_i16(&a["position"]["array"]);
+        let n = decode_i16(&a["normal"]["array"]);
+        let (s, o) = (&json["userData"]["positionScale"], &json["userData"]["positionOffset"]);
+        let world = dequantize_positions(&qpos);
+        for (new, v) in little.iter_vertices().enumerate() {
+            for c in 0..3 {
+                let p = q[new * 3 + c] as f64 * s[c].as_f64().unwrap() + o[c].as_f64().unwrap();
+                assert!((p - v.position[c] as f64).abs() < 1e-3);
+                assert!((n[new * 3 + c] as f32 / 32767.0 - v.normal[c]).abs() < 1e-4);
+            }
+            assert!(world.iter().any(|w| (0..3).all(|c| (w[c] - v.position[c]).abs() < 1e-6)));
+        }
+        let idx = BASE64.decode(json["data"]["index"]["array"].as_str().unwrap()).unwrap();
+        assert_eq!(json["data"]["index"]["type"], "Uint16Array");
+        assert_eq!(idx.len(), 6);
+    }
+}
@@ -0,0 +1,128 @@
+//! 簡略化 → 量子化 → pack をまとめて行うLODピラミッド
+
+use meshguard_pack::{content_hash_seed, pack_interleave_permute, MeshGuardError, PackedMesh};
+use meshguard_process::simplify;
+use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+/// LOD1段分
+#[derive(Clone, Debug)]
+pub struct LodLevel {
+    pub mesh: PackedMesh,
+    /// 簡略化による誤差 (ワールド単位)。割合1.0の段は0
+    pub world_space_error: f32,
+}
+
+/// levels は残す三角形の割合 (例: [1.0, 0.5, 0.25, 0.1])。細かい順に並べること
+/// 各段は元メッシュから簡略化するので、誤差は割合が小さいほど大きくなる
+/// 置換シードは段ごとの内容から作る (`content_hash_seed`)
+pub fn build_lod_pyramid(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    uvs: &[[f32; 2]],
+    indices: &[u32],
+    levels: &[f32],
+) -> Result<Vec<LodLevel>, MeshGuardError> {
+    if normals.len() != positions.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "normal", expected: positions.len(), got: normals.len() });
+    }
+    if uvs.len() != positions.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "uv", expected: positions.len(), got: uvs.len() });
+    }
+    levels.iter().map(|&ratio| {
+        let s = simplify(positions, normals, indices, ratio)?;
+        let uv: Vec<[f32; 2]> = s.source.iter().map(|&i| uvs[i as usize]).collect();
+        let seed = content_hash_seed(&s.positions, &s.normals, &uv);
+        let mesh = pack_interleave_permute(&quantize_positions(&s.positions)?, &encode_normals_oct(&s.normals), &quantize_uvs(&uv), Some(&s.indices), seed)?;
+        Ok(LodLevel { mesh, world_space_error: s.error })
+    }).collect()
+}
+
+/// world_space_error を画面上のピクセル数に投影し、予算に収まる中で最も粗いLODの番号を返す
+/// lods は `build_lod_pyramid` と同じく細かい順。どれも収まらなければ0
+/// カメラが中心に重なっている (距離0) ときも0
+pub fn select_lod(lods: &[LodLevel], object_center: [f32; 3], camera_pos: [f32; 3], fov_y_rad: f32, screen_height_px: u32, pixel_error_budget: f32) -> usize {
+    let d = (0..3).map(|a| (object_center[a] - camera_pos[a]).powi(2)).sum::<f32>().sqrt();
+    if d <= 0.0 {
+        return 0;
+    }
+    // 距離dで画面の高さがワールド単位で 2 d tan(fov/2) になる
+    let px_per_unit = screen_height_px as f32 / (2.0 * d * (fov_y_rad * 0.5).tan());
+    lods.iter().rposition(|l| l.world_space_error * px_per_unit <= pixel_error_budget).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// z = 0.3 * sin(x) のうねった格子
+    fn wavy_grid(n: u32) -> (Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<u32>) {
+        let mut pos = Vec::new();
+        let mut uv = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                pos.push([x as f32, y as f32, (x as f32 * 0.7).sin() * 0.3]);
+                uv.push([x as f32 / (n - 1) as f32, y as f32 / (n - 1) as f32]);
+            }
+        }
+        let mut idx = Vec::new();
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                idx.extend_from_slice(&[i, i + 1, i + n + 1, i, i + n + 1, i + n]);
+            }
+        }
+        (pos, uv, idx)
+    }
+
+    #[test]
+    fn pyramid_gets_coarser() {
+        let (pos, uv, idx) = wavy_grid(12);
+        let nor = vec![[0.0, 0.0, 1.0]; pos.len()];
+        let lods = build_lod_pyramid(&pos, &nor, &uv, &idx, &[1.0, 0.5, 0.25, 0.1]).unwrap();
+        assert_eq!(lods.len(), 4);
+        assert_eq!(lods[0].world_space_error, 0.0);
+        assert_eq!(lods[0].mesh.vertex_count, pos.len());
+        for w in lods.windows(2) {
+            assert!(w[1].mesh.indices.len() < w[0].mesh.indices.len());
+            assert!(w[1].world_space_error >= w[0].world_space_error);
+        }
+        // 残った頂点のUVは元の頂点から引き継ぐので [0, 1] に収まる
+        for v in lods[3].mesh.iter_vertices() {
+            assert!(v.uv.iter().all(|&c| (0.0..=1.0).contains(&c)));
+        }
+
+        assert!(build_lod_pyramid(&pos, &nor, &uv[1..], &idx, &[1.0]).is_err());
+
+        // 遠いほど粗いLODが選ばれる
+        let pick = |z: f32| select_lod(&lods, [5.5, 5.5, 0.0], [5.5, 5.5, z], 60f32.to_radians(), 1080, 1.0);
+        let picks: Vec<usize> = [1.0, 10.0, 100.0, 1e5].iter().map(|&z| pick(z)).collect();
+        assert!(picks.windows(2).all(|w| w[0] <= w[1]), "{:?}", picks);
+        assert_eq!(picks[3], 3);
+        assert_eq!(select_lod(&lods, [0.0; 3], [0.0; 3], 1.0, 1080, 1.0), 0);
+
+        // 距離10, 画角90度, 高さ1000pxなら1単位 = 50px
+        let mut fixed = lods.clone();
+        for (l, e) in fixed.iter_mut().zip([0.0, 0.01, 0.1, 1.0]) {
+            l.world_space_error = e;
+        }
+        let at = |budget: f32| select_lod(&fixed, [0.0; 3], [0.0, 0.0, 10.0], std::f32::consts::FRAC_PI_2, 1000, budget);
+        assert_eq!((at(0.1), at(1.0), at(10.0), at(50.0)), (0, 1, 2, 3));
+        assert!(build_lod_pyramid(&pos, &nor, &uv, &idx, &[0.0]).is_err());
+    }
+
+    #[test]
+    fn error_is_in_world_units() {
+        // 半幅1・高さ1の四角錐を半分にすると頂点が角へ寄り、誤差は角から向かいの面までの距離 sqrt(2) になる
+        let pos = [[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let nor = [[0.0, 0.0, 1.0]; 5];
+        let uv = [[0.5, 0.5]; 5];
+        let lods = build_lod_pyramid(&pos, &nor, &uv, &[0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4], &[1.0, 0.5]).unwrap();
+        assert_eq!(lods[1].mesh.indices.len(), 6);
+        let e = lods[1].world_space_error;
+        assert!((e - std::f32::consts::SQRT_2).abs() < 0.02, "{}", e);
+
+        // 画角90度・高さ1000pxで距離dなら誤差は 500 e / d ピクセル。1px以内になるのは d >= 約707
+        let at = |d: f32| select_lod(&lods, [0.0; 3], [0.0, 0.0, d], std::f32::consts::FRAC_PI_2, 1000, 1.0);
+        assert_eq!((at(650.0), at(760.0)), (0, 1));
+    }
+}
@@ -0,0 +1,26 @@
+//! pack_interleave_permute のスループット計測 (頂点/秒)
+//! 量子化は前もって済ませ、packだけを測る
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meshguard_pack::pack_interleave_permute;
+use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+use std::hint::black_box;
+
+fn bench_pack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pack_interleave_permute");
+    for n in [1_000usize, 100_000, 1_000_000] {
+        let positions: Vec<[f32; 3]> = (0..n).map(|i| { let t = i as f32 * 0.001; [t.sin() * 10.0, t.cos() * 5.0, t] }).collect();
+        let qpos = quantize_positions(&positions).unwrap();
+        let qnor = encode_normals_oct(&vec![[0.0, 0.0, 1.0]; n]);
+        let quv = quantize_uvs(&vec![[0.5, 0.5]; n]);
+        let indices: Vec<u32> = (0..n as u32).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter(|| pack_interleave_permute(black_box(&qpos), &qnor, &quv, Some(&indices), 0xDEAD_BEEF))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pack);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+use crate::{pack_interleave_permute, MeshGuardError, PackConfig, VertexLayout};
+use meshguard_quantize::{QuantizedNormalsOct, QuantizedPositions, QuantizedUVs};
+
+/// `pack_batch` の入力1つ分
+pub type BatchInput<'a> = (QuantizedPositions, QuantizedNormalsOct, QuantizedUVs, Option<&'a [u32]>);
+
+/// 共有バッファ内の1メッシュ分の範囲
+/// インデックスはこのメッシュの先頭頂点からの相対値 (描画時に base vertex として vertex_offset を渡す)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchSubmeshRange {
+    /// 頂点単位 (バイトオフセットは vertex_offset * stride)
+    pub vertex_offset: u32,
+    pub vertex_count: u32,
+    pub index_offset: u32,
+    pub index_count: u32,
+    pub pos_scale: [f32; 3],
+    pub pos_offset: [f32; 3],
+    pub pos_bits: [u8; 3],
+}
+
+/// 複数メッシュを1本の頂点バッファとインデックスバッファにまとめたもの
+#[derive(Clone, Debug)]
+pub struct BatchPackedMesh {
+    pub interleaved: Vec<u8>,
+    pub index_buffer: Vec<u32>,
+    pub submeshes: Vec<BatchSubmeshRange>,
+    /// 全サブメッシュ共通 (PNUV)
+    pub layout: VertexLayout,
+}
+
+/// 各メッシュをPNUVでpackして後ろに連結する
+/// i番目のメッシュは `perm_seed.wrapping_add(i)` で置換する (同じ形のメッシュでも並びが揃わないように)
+pub fn pack_batch(meshes: &[BatchInput], perm_seed: u64) -> Result<BatchPackedMesh, MeshGuardError> {
+    let mut out = BatchPackedMesh { interleaved: Vec::new(), index_buffer: Vec::new(), submeshes: Vec::with_capacity(meshes.len()), layout: PackConfig::new().layout() };
+    for (i, (qpos, qnor, quv, indices)) in meshes.iter().enumerate() {
+        let packed = pack_interleave_permute(qpos, qnor, quv, *indices, perm_seed.wrapping_add(i as u64))?;
+        let vertex_offset = out.interleaved.len() / out.layout.stride;
+        let index_offset = out.index_buffer.len();
+        let index_count = packed.indices.len();
+        if vertex_offset + packed.vertex_count > u32::MAX as usize || index_offset + index_count > u32::MAX as usize {
+            return Err(MeshGuardError::InvalidInput("batch exceeds u32 vertex or index range".into()));
+        }
+        out.interleaved.extend_from_slice(&packed.interleaved);
+        out.index_buffer.extend(packed.indices.to_u32());
+        out.submeshes.push(BatchSubmeshRange {
+            vertex_offset: vertex_offset as u32,
+            vertex_count: packed.vertex_count as u32,
+            index_offset: index_offset as u32,
+            index_count: index_count as u32,
+            pos_scale: packed.pos_scale,
+            pos_offset: packed.pos_offset,
+            pos_bits: packed.pos_bits,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn batch_concatenates_submeshes() {
+        let a = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let b = vec![[5.0,5.0,5.0],[6.0,5.0,5.0],[5.0,6.0,5.0],[5.0,5.0,6.0]];
+        let input = |p: &[[f32; 3]]| (quantize_positions(p).unwrap(), encode_normals_oct(&vec![[0.0,0.0,1.0]; p.len()]), quantize_uvs(&vec![[0.5,0.5]; p.len()]));
+        let (ia, ib) = ([0u32, 1, 2], [0u32, 1, 2, 0, 2, 3]);
+        let ((pa, na, ua), (pb, nb, ub)) = (input(&a), input(&b));
+        let batch = pack_batch(&[(pa.clone(), na.clone(), ua.clone(), Some(&ia[..])), (pb.clone(), nb.clone(), ub.clone(), Some(&ib[..]))], 7).unwrap();
+
+        assert_eq!(batch.interleaved.len(), 7 * 14);
+        assert_eq!(batch.index_buffer.len(), 9);
+        let s = batch.submeshes[1];
+        assert_eq!((s.vertex_offset, s.vertex_count, s.index_offset, s.index_count), (3, 4, 3, 6));
+        assert_eq!(s.pos_offset, pb.offset);
+
+        let single = pack_interleave_permute(&pb, &nb, &ub, Some(&ib), 8).unwrap();
+        assert_eq!(&batch.interleaved[3 * 14..], &single.interleaved[..]);
+        assert_eq!(&batch.index_buffer[3..], &single.indices_u32()[..]);
+        assert!(batch.index_buffer[3..].iter().all(|&i| i < s.vertex_count));
+    }
+}
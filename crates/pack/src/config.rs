@@ -0,0 +1,171 @@
+use crate::layout::{AttributeKind, AttributeOrder, Endianness, VertexFormat, VertexLayout};
+use crate::PrimitiveTopology;
+
+/// interleavedレイアウトの設定
+/// デフォルトは従来通りのPNUV (14バイト/頂点)
+#[derive(Clone, Debug)]
+pub struct PackConfig {
+    attributes: AttributeOrder,
+    joint_index_format: VertexFormat,
+    use_f16_positions: bool,
+    normal_format: VertexFormat,
+    align_attributes: bool,
+    endianness: Endianness,
+    topology: PrimitiveTopology,
+}
+
+impl Default for PackConfig {
+    fn default() -> Self {
+        PackConfig {
+            attributes: AttributeOrder::pnuv(),
+            joint_index_format: VertexFormat::Uint16x4,
+            use_f16_positions: false,
+            normal_format: VertexFormat::Unorm16x2,
+            align_attributes: false,
+            endianness: Endianness::Little,
+            topology: PrimitiveTopology::Triangles,
+        }
+    }
+}
+
+impl PackConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 属性の並び順をまるごと指定する
+    /// 後から tangents() などで有効にした属性は末尾に付く
+    pub fn order(mut self, order: AttributeOrder) -> Self {
+        self.attributes = order;
+        self
+    }
+
+    /// 接線を末尾に追加する
+    pub fn tangents(self, enabled: bool) -> Self {
+        self.toggle(AttributeKind::Tangent, enabled)
+    }
+
+    /// 頂点カラー(4バイト)を末尾に追加する
+    pub fn colors(self, enabled: bool) -> Self {
+        self.toggle(AttributeKind::Color, enabled)
+    }
+
+    /// AO(1バイト)を末尾に追加する
+    pub fn ambient_occlusion(self, enabled: bool) -> Self {
+        self.toggle(AttributeKind::AmbientOcclusion, enabled)
+    }
+
+    /// ジョイント番号とウェイトを末尾に追加する
+    pub fn skinning(self, enabled: bool) -> Self {
+        self.toggle(AttributeKind::JointIndices, enabled).toggle(AttributeKind::JointWeights, enabled)
+    }
+
+    /// ジョイント番号をu8 x4で書く (デフォルトはu16 x4)
+    pub fn joint_indices_u8(mut self, enabled: bool) -> Self {
+        self.joint_index_format = if enabled { VertexFormat::Uint8x4 } else { VertexFormat::Uint16x4 };
+        self
+    }
+
+    /// 座標をi16のまま書かずにf16のワールド座標で書く
+    pub fn f16_positions(mut self, enabled: bool) -> Self {
+        self.use_f16_positions = enabled;
+        self
+    }
+
+    /// 法線をoctではなく10-10-10-2で書く
+    pub fn normals_10_10_10_2(mut self, enabled: bool) -> Self {
+        self.normal_format = if enabled { VertexFormat::Snorm10_10_10_2 } else { VertexFormat::Unorm16x2 };
+        self
+    }
+
+    /// 属性を自然アラインメントに揃えて隙間を0で埋める
+    /// strideは最大アラインメントの倍数になる (PNUVなら14ではなく16バイト)
+    pub fn align_attributes(mut self, enabled: bool) -> Self {
+        self.align_attributes = enabled;
+        self
+    }
+
+    /// 多バイト成分のバイト順 (デフォルトはリトルエンディアン)
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// インデックスを三角形リストとストリップのどちらとして扱うか
+    /// ストリップならSTRIP_RESTART_INDEXをそのまま通す
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// UVを何セット書くか (0〜2、3以上は2扱い)
+    /// 2セット目はUVの直後に入る
+    pub fn uv_channels(mut self, channels: usize) -> Self {
+        self.attributes.0.retain(|&k| k != AttributeKind::Uv2);
+        if channels == 0 {
+            return self.toggle(AttributeKind::Uv, false);
+        }
+        if !self.attributes.0.contains(&AttributeKind::Uv) {
+            self.attributes.0.push(AttributeKind::Uv);
+        }
+        if channels >= 2 {
+            let at = self.attributes.0.iter().position(|&k| k == AttributeKind::Uv).unwrap() + 1;
+            self.attributes.0.insert(at, AttributeKind::Uv2);
+        }
+        self
+    }
+
+    fn toggle(mut self, kind: AttributeKind, enabled: bool) -> Self {
+        self.attributes.0.retain(|&k| k != kind);
+        if enabled {
+            self.attributes.0.push(kind);
+        }
+        self
+    }
+
+    pub fn attributes(&self) -> &[AttributeKind] {
+        &self.attributes.0
+    }
+
+    pub fn attribute_order(&self) -> &AttributeOrder {
+        &self.attributes
+    }
+
+    pub fn primitive_topology(&self) -> PrimitiveTopology {
+        self.topology
+    }
+
+    pub fn format_of(&self, kind: AttributeKind) -> VertexFormat {
+        match kind {
+            AttributeKind::Position if self.use_f16_positions => VertexFormat::Float16x3,
+            AttributeKind::Position => VertexFormat::Sint16x3,
+            AttributeKind::Normal => self.normal_format,
+            AttributeKind::Uv | AttributeKind::Uv2 => VertexFormat::Unorm16x2,
+            AttributeKind::Tangent => VertexFormat::Snorm16x4,
+            AttributeKind::Color | AttributeKind::JointWeights => VertexFormat::Unorm8x4,
+            AttributeKind::JointIndices => self.joint_index_format,
+            AttributeKind::AmbientOcclusion => VertexFormat::Unorm8,
+        }
+    }
+
+    /// `layout().stride` と同じ値をVecを作らずに求める
+    pub fn stride(&self) -> usize {
+        let (mut offset, mut max_align) = (0usize, 1usize);
+        for &kind in &self.attributes.0 {
+            let format = self.format_of(kind);
+            if self.align_attributes {
+                offset = offset.next_multiple_of(format.alignment());
+                max_align = max_align.max(format.alignment());
+            }
+            offset += format.size();
+        }
+        offset.next_multiple_of(max_align)
+    }
+
+    pub fn layout(&self) -> VertexLayout {
+        let attrs: Vec<_> = self.attributes.0.iter().map(|&k| (k, self.format_of(k))).collect();
+        let mut layout = if self.align_attributes { VertexLayout::aligned(&attrs) } else { VertexLayout::packed(&attrs) };
+        layout.endianness = self.endianness;
+        layout
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{read_u16, read_u32, AttributeKind, MeshGuardError, PackedMesh, VertexFormat};
+use meshguard_quantize::{decode_normal_10_10_10_2, decode_normal_oct, dequantize_position};
+
+/// interleavedから復号した1頂点
+/// レイアウトにない属性は0で埋める
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DecodedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl PackedMesh {
+    /// 置換後の並び順で1頂点ずつ復号する
+    /// バッファはコピーせず、保存されているレイアウトとエンディアンに従って読む
+    pub fn iter_vertices(&self) -> impl Iterator<Item = DecodedVertex> + '_ {
+        self.interleaved.chunks_exact(self.layout.stride).map(|v| self.decode_vertex(v))
+    }
+
+    /// idx番目 (置換後の番号) の頂点だけを復号する。読むのはstrideバイトだけ
+    pub fn vertex_at(&self, idx: usize) -> Result<DecodedVertex, MeshGuardError> {
+        if idx >= self.vertex_count {
+            return Err(MeshGuardError::IndexOutOfBounds { index: u32::try_from(idx).unwrap_or(u32::MAX), position: None, vertex_count: self.vertex_count });
+        }
+        let stride = self.layout.stride;
+        let v = self.interleaved.get(idx * stride..(idx + 1) * stride)
+            .ok_or_else(|| MeshGuardError::InvalidInput(format!("interleaved buffer is too short for vertex {}", idx)))?;
+        Ok(self.decode_vertex(v))
+    }
+
+    /// 1頂点分 (strideバイト) を復号する
+    fn decode_vertex(&self, v: &[u8]) -> DecodedVertex {
+        let e = self.layout.endianness;
+        let mut out = DecodedVertex::default();
+        for attr in &self.layout.attributes {
+            let b = &v[attr.offset..attr.offset + attr.format.size()];
+            match (attr.kind, attr.format) {
+                (AttributeKind::Position, VertexFormat::Sint16x3) => {
+                    let q = [0, 1, 2].map(|a| read_u16(&b[a * 2..], e) as i16);
+                    out.position = dequantize_position(q, self.pos_bits, self.pos_scale, self.pos_offset);
+                }
+                (AttributeKind::Position, VertexFormat::Float16x3) => {
+                    out.position = [0, 1, 2].map(|a| half::f16::from_bits(read_u16(&b[a * 2..], e)).to_f32());
+                }
+                (AttributeKind::Normal, VertexFormat::Snorm10_10_10_2) => out.normal = decode_normal_10_10_10_2(read_u32(b, e)),
+                (AttributeKind::Normal, _) => out.normal = decode_normal_oct(read_u16(b, e), read_u16(&b[2..], e)),
+                (AttributeKind::Uv, _) => out.uv = [read_u16(b, e) as f32 / 65535.0, read_u16(&b[2..], e) as f32 / 65535.0],
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+/// 座標だけを置換後の並び順で復号する (物理やレイキャスト用)
+/// 法線やUVのバイトは読まない。レイアウトに座標がなければ空
+pub fn extract_positions(mesh: &PackedMesh) -> Vec<[f32; 3]> {
+    let Some(attr) = mesh.layout.attribute(AttributeKind::Position) else { return Vec::new() };
+    let e = mesh.layout.endianness;
+    let f16 = attr.format == VertexFormat::Float16x3;
+    mesh.interleaved.chunks_exact(mesh.layout.stride).map(|v| {
+        let raw = [0, 1, 2].map(|a| read_u16(&v[attr.offset + a * 2..], e));
+        if f16 {
+            raw.map(|h| half::f16::from_bits(h).to_f32())
+        } else {
+            dequantize_position(raw.map(|q| q as i16), mesh.pos_bits, mesh.pos_scale, mesh.pos_offset)
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_positions;
+    use crate::{pack_with_config, MeshGuardError, vertex_permutation, AttributeOrder, Endianness, MeshAttributes, PackConfig, XorShift64Rng};
+    use meshguard_quantize::{dequantize_normals_oct, dequantize_positions, dequantize_uvs, encode_normals_10_10_10_2, encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn decode_follows_layout() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = [[0.0,0.0,1.0],[1.0,0.0,0.0],[0.0,-1.0,0.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&[[0.25,0.5],[0.0,1.0],[0.75,0.125]]));
+        let (p, n, u) = (dequantize_positions(&qpos), dequantize_normals_oct(&qnor), dequantize_uvs(&quv));
+
+        let configs = [
+            PackConfig::new(),
+            PackConfig::new().order(AttributeOrder::puvn()).endianness(Endianness::Big).align_attributes(true),
+        ];
+        for config in &configs {
+            let mesh = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 7, config).unwrap();
+            let perm = vertex_permutation(3, XorShift64Rng::new(7));
+            let verts: Vec<_> = mesh.iter_vertices().collect();
+            assert_eq!(verts.len(), 3);
+            for (new, v) in verts.iter().enumerate() {
+                let old = perm[new] as usize;
+                assert_eq!((v.position, v.normal, v.uv), (p[old], n[old], u[old]));
+            }
+        }
+
+        let mesh = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 7, &configs[1]).unwrap();
+        let all: Vec<_> = mesh.iter_vertices().collect();
+        for (i, v) in all.iter().enumerate() {
+            assert_eq!(mesh.vertex_at(i).unwrap(), *v);
+        }
+        assert_eq!(extract_positions(&mesh), all.iter().map(|v| v.position).collect::<Vec<_>>());
+        assert_eq!(mesh.vertex_at(3).unwrap_err(), MeshGuardError::IndexOutOfBounds { index: 3, position: None, vertex_count: 3 });
+
+        let half = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 7, &PackConfig::new().f16_positions(true)).unwrap();
+        let got = extract_positions(&half);
+        for (new, v) in half.iter_vertices().enumerate() {
+            assert_eq!(got[new], v.position);
+            let want = p[vertex_permutation(3, XorShift64Rng::new(7))[new] as usize];
+            assert!((0..3).all(|a| (got[new][a] - want[a]).abs() <= want[a].abs() * 1e-3 + 1e-3));
+        }
+
+        let packed10 = encode_normals_10_10_10_2(&nor);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_normals_10_10_10_2(&packed10);
+        let mesh = pack_with_config(&attrs, None, 7, &PackConfig::new().normals_10_10_10_2(true)).unwrap();
+        let perm = vertex_permutation(3, XorShift64Rng::new(7));
+        for (new, v) in mesh.iter_vertices().enumerate() {
+            let want = nor[perm[new] as usize];
+            assert!((0..3).all(|a| (v.normal[a] - want[a]).abs() < 4e-3));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::planar::split;
+use crate::{pack_interleave_permute, AttributeKind, IndexBuffer, MeshGuardError};
+use meshguard_quantize::{QuantizedNormalsOct, QuantizedPositions, QuantizedUVs};
+
+/// 座標だけのストリームと、それ以外の属性のストリームに分けたもの
+/// 深度プリパスや影は `position_stream` だけをバインドすればよい
+/// 2つのストリームは同じ置換を受けるので、同じ頂点番号で対応する
+#[derive(Clone, Debug)]
+pub struct DualStreamMesh {
+    /// i16 x3 (6バイト/頂点)
+    pub position_stream: Vec<u8>,
+    /// oct法線 u16 x2 + UV u16 x2 (8バイト/頂点)
+    pub attribute_stream: Vec<u8>,
+    pub vertex_count: usize,
+    pub indices: IndexBuffer,
+    pub pos_scale: [f32; 3],
+    pub pos_offset: [f32; 3],
+    pub pos_bits: [u8; 3],
+    pub perm_seeds: Vec<u64>,
+}
+
+impl DualStreamMesh {
+    pub const POSITION_STRIDE: usize = 6;
+    pub const ATTRIBUTE_STRIDE: usize = 8;
+}
+
+/// PNUVでpackしてから座標とNUVに切り分ける
+pub fn pack_dual_stream(
+    qpos: &QuantizedPositions,
+    qnor: &QuantizedNormalsOct,
+    quv:  &QuantizedUVs,
+    indices: Option<&[u32]>,
+    perm_seed: u64,
+) -> Result<DualStreamMesh, MeshGuardError> {
+    let packed = pack_interleave_permute(qpos, qnor, quv, indices, perm_seed)?;
+    Ok(DualStreamMesh {
+        position_stream: split(&packed, &[AttributeKind::Position]),
+        attribute_stream: split(&packed, &[AttributeKind::Normal, AttributeKind::Uv]),
+        vertex_count: packed.vertex_count,
+        indices: packed.indices,
+        pos_scale: packed.pos_scale,
+        pos_offset: packed.pos_offset,
+        pos_bits: packed.pos_bits,
+        perm_seeds: packed.perm_seeds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn streams_share_vertex_order() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0],[3.0,3.0,3.0]];
+        let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577],[0.0,-1.0,0.0]];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0],[0.25,0.125]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+        let dual = pack_dual_stream(&qpos, &qnor, &quv, Some(&[0, 1, 2, 0, 2, 3]), 5).unwrap();
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2, 0, 2, 3]), 5).unwrap();
+        assert_eq!((dual.position_stream.len(), dual.attribute_stream.len()), (4 * 6, 4 * 8));
+        assert_eq!((&dual.indices, &dual.perm_seeds), (&packed.indices, &packed.perm_seeds));
+        for (k, v) in packed.interleaved.chunks_exact(14).enumerate() {
+            assert_eq!(&v[0..6], &dual.position_stream[k * 6..k * 6 + 6]);
+            assert_eq!(&v[6..14], &dual.attribute_stream[k * 8..k * 8 + 8]);
+        }
+    }
+}
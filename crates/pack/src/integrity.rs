@@ -0,0 +1,55 @@
+use crate::PackedMesh;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 格納形式 (u16/u32) に依らないよう、インデックスは常にu32 LEで入れる
+fn mac(mesh: &PackedMesh, secret: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&mesh.interleaved);
+    for i in mesh.indices.to_u32() {
+        mac.update(&i.to_le_bytes());
+    }
+    mac
+}
+
+/// タグを計算して `integrity_tag` に入れる
+pub fn sign_packed_mesh(mesh: &mut PackedMesh, secret: &[u8]) {
+    mesh.integrity_tag = Some(mac(mesh, secret).finalize().into_bytes().into());
+}
+
+/// タグがない、または一致しなければfalse (比較は定数時間)
+pub fn verify_packed_mesh(mesh: &PackedMesh, secret: &[u8]) -> bool {
+    match &mesh.integrity_tag {
+        Some(tag) => mac(mesh, secret).verify_slice(tag).is_ok(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack_interleave_permute;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn sign_and_detect_tampering() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0]; 3];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
+        let mut mesh = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&nor), &quantize_uvs(&uv), Some(&[0, 1, 2]), 5).unwrap();
+        assert!(!verify_packed_mesh(&mesh, b"secret"));
+
+        sign_packed_mesh(&mut mesh, b"secret");
+        assert!(verify_packed_mesh(&mesh, b"secret"));
+        assert!(!verify_packed_mesh(&mesh, b"other"));
+
+        let mut bad = mesh.clone();
+        bad.interleaved[3] ^= 1;
+        assert!(!verify_packed_mesh(&bad, b"secret"));
+        let mut bad = mesh.clone();
+        bad.indices = crate::IndexBuffer::U16(vec![0, 2, 1]);
+        assert!(!verify_packed_mesh(&bad, b"secret"));
+    }
+}
@@ -0,0 +1,162 @@
+/// 頂点属性の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeKind {
+    Position,
+    Normal,
+    Uv,
+    /// 2つ目のUV (ライトマップ用)
+    Uv2,
+    Tangent,
+    Color,
+    JointWeights,
+    JointIndices,
+    /// ベイク済みAO (1バイト)
+    AmbientOcclusion,
+}
+
+/// 多バイト成分のバイト順
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// interleavedレイアウトに書く属性の並び順
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeOrder(pub Vec<AttributeKind>);
+
+impl AttributeOrder {
+    /// Position → Normal → UV (デフォルト)
+    pub fn pnuv() -> Self {
+        AttributeOrder(vec![AttributeKind::Position, AttributeKind::Normal, AttributeKind::Uv])
+    }
+
+    /// Position → UV → Normal
+    pub fn puvn() -> Self {
+        AttributeOrder(vec![AttributeKind::Position, AttributeKind::Uv, AttributeKind::Normal])
+    }
+
+    /// Positionだけ
+    pub fn position_only() -> Self {
+        AttributeOrder(vec![AttributeKind::Position])
+    }
+
+    /// 同じ属性が2回出てくるならその属性
+    pub fn duplicate(&self) -> Option<AttributeKind> {
+        self.0.iter().enumerate().find(|&(i, k)| self.0[..i].contains(k)).map(|(_, &k)| k)
+    }
+}
+
+/// interleavedバッファ上の1属性の型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VertexFormat {
+    /// i16 x3 (量子化座標)
+    Sint16x3,
+    /// f16 x3 (復号済みのワールド座標)
+    Float16x3,
+    /// u16 x2 (oct法線, UV)
+    Unorm16x2,
+    /// 10-10-10-2 SNORM (法線)
+    Snorm10_10_10_2,
+    /// i16 x4 (oct接線xy + 符号w + 0)
+    Snorm16x4,
+    /// u8 x4 (RGBA8カラー, ジョイントウェイト)
+    Unorm8x4,
+    /// u8 x4 (ジョイント番号)
+    Uint8x4,
+    /// u16 x4 (ジョイント番号)
+    Uint16x4,
+    /// u8 x1 (AO)
+    Unorm8,
+}
+
+impl VertexFormat {
+    pub fn size(self) -> usize {
+        match self {
+            VertexFormat::Sint16x3 | VertexFormat::Float16x3 => 6,
+            VertexFormat::Unorm16x2 | VertexFormat::Snorm10_10_10_2 => 4,
+            VertexFormat::Snorm16x4 => 8,
+            VertexFormat::Unorm8x4 | VertexFormat::Uint8x4 => 4,
+            VertexFormat::Uint16x4 => 8,
+            VertexFormat::Unorm8 => 1,
+        }
+    }
+
+    /// 1成分のバイト数 (エンディアン変換の単位)
+    pub fn component_size(self) -> usize {
+        match self {
+            VertexFormat::Snorm10_10_10_2 => 4,
+            VertexFormat::Unorm8x4 | VertexFormat::Uint8x4 | VertexFormat::Unorm8 => 1,
+            _ => 2,
+        }
+    }
+
+    /// 自然アラインメント
+    /// 3要素の型はvec4と同じ扱いにする
+    pub fn alignment(self) -> usize {
+        match self {
+            VertexFormat::Sint16x3 | VertexFormat::Float16x3 | VertexFormat::Snorm16x4 | VertexFormat::Uint16x4 => 8,
+            VertexFormat::Unorm16x2 | VertexFormat::Snorm10_10_10_2 | VertexFormat::Unorm8x4 | VertexFormat::Uint8x4 => 4,
+            VertexFormat::Unorm8 => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexAttribute {
+    pub kind: AttributeKind,
+    pub format: VertexFormat,
+    /// 頂点先頭からのバイトオフセット
+    pub offset: usize,
+}
+
+/// 1頂点分のバイト配置
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexLayout {
+    pub attributes: Vec<VertexAttribute>,
+    pub stride: usize,
+    pub endianness: Endianness,
+}
+
+impl VertexLayout {
+    /// 属性を並び順どおりに隙間なく詰める
+    pub fn packed(attrs: &[(AttributeKind, VertexFormat)]) -> Self {
+        let mut attributes = Vec::with_capacity(attrs.len());
+        let mut offset = 0;
+        for &(kind, format) in attrs {
+            attributes.push(VertexAttribute { kind, format, offset });
+            offset += format.size();
+        }
+        VertexLayout { attributes, stride: offset, endianness: Endianness::Little }
+    }
+
+    /// 各属性を自然アラインメントに揃え、strideも最大アラインメントの倍数にする
+    /// PNUVなら P:0 N:8 U:12 でstrideは16バイト
+    pub fn aligned(attrs: &[(AttributeKind, VertexFormat)]) -> Self {
+        let mut attributes = Vec::with_capacity(attrs.len());
+        let mut offset = 0usize;
+        let mut max_align = 1;
+        for &(kind, format) in attrs {
+            let align = format.alignment();
+            offset = offset.next_multiple_of(align);
+            max_align = max_align.max(align);
+            attributes.push(VertexAttribute { kind, format, offset });
+            offset += format.size();
+        }
+        VertexLayout { attributes, stride: offset.next_multiple_of(max_align), endianness: Endianness::Little }
+    }
+
+    pub fn attribute(&self, kind: AttributeKind) -> Option<&VertexAttribute> {
+        self.attributes.iter().find(|a| a.kind == kind)
+    }
+
+    pub fn is_position_only(&self) -> bool {
+        matches!(self.attributes.as_slice(), [a] if a.kind == AttributeKind::Position)
+    }
+}
@@ -1,32 +1,211 @@
-use meshguard_quantize::{QuantizedPositions, QuantizedNormalsOct, QuantizedUVs};
+use meshguard_quantize::{
+    QuantizedPositions, QuantizedNormalsOct, QuantizedUVs, QuantizedTangents, QuantizedColors,
+    QuantizedJointWeights, QuantizedJointIndices, Packed10_10_10_2Normals, QuantizedAO,
+};
+use std::ops::Range;
+
+mod batch;
+mod config;
+mod decode;
+mod dual;
+#[cfg(feature = "hmac")]
+mod integrity;
+mod layout;
+mod merge;
+mod morph;
+mod obfuscate;
+mod planar;
+mod report;
+mod rng;
+mod split;
+mod unpack;
+mod validate;
+mod vertex;
+mod vulkan;
+pub use batch::{pack_batch, BatchInput, BatchPackedMesh, BatchSubmeshRange};
+pub use config::PackConfig;
+pub use decode::{extract_positions, DecodedVertex};
+pub use dual::{pack_dual_stream, DualStreamMesh};
+pub use meshguard_core::{validate_indices, MeshGuardError, STRIP_RESTART_INDEX};
+#[cfg(feature = "hmac")]
+pub use integrity::{sign_packed_mesh, verify_packed_mesh};
+pub use layout::{AttributeKind, AttributeOrder, Endianness, VertexAttribute, VertexFormat, VertexLayout};
+pub use merge::{merge_packed_meshes, merge_packed_meshes_with_tolerance, DEFAULT_MERGE_TOLERANCE};
+pub use morph::pack_morph_deltas;
+pub use obfuscate::{deobfuscate, obfuscate};
+pub use planar::{pack_planar, PlanarMesh};
+pub use report::{compression_stats, CompressionStats};
+pub use rng::{content_hash_seed, ChaChaRng, MeshRng, XorShift64Rng};
+pub use split::split_by_material;
+pub use unpack::{unpack_mesh, unpack_mesh_with_permutation, UnpackedMesh};
+pub use vertex::VertexPNUV;
+pub use vulkan::{to_vulkan_vertex_input, VkFormat, VkVertexInputAttributeDescriptionLike};
+
+/// インデックスバッファ
+/// 頂点数が65535以下ならu16で持つ
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexBuffer {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl IndexBuffer {
+    /// 頂点数に合わせて小さい方の型を選ぶ
+    pub fn from_u32(indices: Vec<u32>, vertex_count: usize) -> Self {
+        if vertex_count <= u16::MAX as usize {
+            IndexBuffer::U16(indices.into_iter().map(|i| i as u16).collect())
+        } else {
+            IndexBuffer::U32(indices)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IndexBuffer::U16(v) => v.len(),
+            IndexBuffer::U32(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn to_u32(&self) -> Vec<u32> {
+        match self {
+            IndexBuffer::U16(v) => v.iter().map(|&i| i as u32).collect(),
+            IndexBuffer::U32(v) => v.clone(),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackedMesh {
+    /// JSONなど人間向け形式ではbase64、バイナリ形式では生バイト
+    #[cfg_attr(feature = "serde", serde(with = "serde_with::As::<serde_with::IfIsHumanReadable<serde_with::base64::Base64, serde_with::Bytes>>"))]
     pub interleaved: Vec<u8>,
     pub vertex_count: usize,
-    pub indices: Vec<u32>,
+    pub indices: IndexBuffer,
     pub pos_scale: [f32; 3],
     pub pos_offset: [f32; 3],
-    pub perm_seed: u64,
+    /// 軸ごとの座標量子化ビット深度 (格納は常にi16)
+    pub pos_bits: [u8; 3],
+    /// 適用した順の置換シード。任意RNGで置換した場合は空
+    pub perm_seeds: Vec<u64>,
+    pub layout: VertexLayout,
+    /// interleaved + インデックスに対するHMAC-SHA256 (`hmac` feature で付与)
+    pub integrity_tag: Option<[u8; 32]>,
+    /// マテリアルごとのインデックス範囲。指定しなければ空
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub submeshes: Vec<SubmeshRange>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub topology: PrimitiveTopology,
+}
+
+/// インデックスバッファの解釈
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrimitiveTopology {
+    #[default]
+    Triangles,
+    /// STRIP_RESTART_INDEX でストリップを区切れる
+    TriangleStrip,
+}
+
+/// マテリアル1つ分のインデックス範囲
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubmeshRange {
+    pub index_offset: usize,
+    pub index_count: usize,
+    pub material_id: u32,
+}
+
+impl PackedMesh {
+    /// 内部表現に関わらずu32で返す
+    /// ストリップのリスタート値 (u16なら0xFFFF) はu32::MAXに戻す
+    pub fn indices_u32(&self) -> Vec<u32> {
+        match (&self.indices, self.topology) {
+            (IndexBuffer::U16(v), PrimitiveTopology::TriangleStrip) => {
+                v.iter().map(|&i| if i == u16::MAX { STRIP_RESTART_INDEX } else { i as u32 }).collect()
+            }
+            _ => self.indices.to_u32(),
+        }
+    }
+
+    /// 座標しか入っていない (法線やUVを読もうとしないこと)
+    pub fn is_position_only(&self) -> bool {
+        self.layout.is_position_only()
+    }
+}
+
+/// 量子化前のAABBをメタデータだけから戻す (頂点を復号しなくてよいのでカリング用に使える)
+/// 量子化値は符号なしで 0..2^bits-1 の範囲なので max = offset + scale * (2^bits - 1)
+pub fn packed_mesh_aabb(mesh: &PackedMesh) -> ([f32; 3], [f32; 3]) {
+    let max = std::array::from_fn(|a| {
+        let levels = ((1u32 << mesh.pos_bits[a]) - 1) as f64;
+        (mesh.pos_offset[a] as f64 + mesh.pos_scale[a] as f64 * levels) as f32
+    });
+    (mesh.pos_offset, max)
+}
+
+/// packした結果の interleaved + インデックスのバイト数を、packせずに求める
+/// インデックスを渡さないpackでは頂点数ぶんのインデックスが作られるので index_count に vertex_count を渡す
+pub fn estimate_packed_bytes(vertex_count: usize, index_count: usize, config: &PackConfig) -> usize {
+    let index_size = if vertex_count <= u16::MAX as usize { 2 } else { 4 };
+    vertex_count * config.stride() + index_count * index_size
 }
 
 /// ランダム順列作るだけ（シード保存用）
-fn permutation_fy(n: usize, seed: u64) -> Vec<u32> {
+fn permutation_fy(n: usize, rng: &mut impl MeshRng) -> Vec<u32> {
     let mut p: Vec<u32> = (0..n as u32).collect();
-    let mut s = if seed == 0 { 0x9E3779B97F4A7C15 } else { seed };
-    let mut next_u64 = || {
-        s ^= s >> 12;
-        s ^= s << 25;
-        s ^= s >> 27;
-        s.wrapping_mul(0x2545F4914F6CDD1D)
-    };
     for i in (1..n).rev() {
-        let r = next_u64() as usize % (i + 1);
+        let r = rng.next_u64() as usize % (i + 1);
         p.swap(i, r);
     }
     p
 }
 
+/// packと同じ手順で置換 (新しい番号 -> 元の番号) を作り直す
+/// 同じRNG系列を渡せば展開時に元の頂点順へ戻せる
+pub fn vertex_permutation(vertex_count: usize, mut rng: impl MeshRng) -> Vec<u32> {
+    permutation_fy(vertex_count, &mut rng)
+}
+
+/// `pack_multi_permute` と同じ合成置換
+/// k番目の頂点は元の `perm[k]` 番目
+pub fn multi_permutation(vertex_count: usize, seeds: &[u64]) -> Vec<u32> {
+    let mut total: Vec<u32> = (0..vertex_count as u32).collect();
+    for &seed in seeds {
+        let p = permutation_fy(vertex_count, &mut XorShift64Rng::new(seed));
+        total = p.iter().map(|&k| total[k as usize]).collect();
+    }
+    total
+}
+
+/// 三角形 (3インデックス単位) の順序だけを入れ替える
+/// インデックスの値は変えないので頂点置換と組み合わせても壊れない
+/// 返り値のu64は使ったシード
+pub fn permute_triangles(indices: &[u32], seed: u64) -> (Vec<u32>, u64) {
+    let perm = permutation_fy(indices.len() / 3, &mut XorShift64Rng::new(seed));
+    let mut out = Vec::with_capacity(indices.len());
+    for &t in &perm {
+        out.extend_from_slice(&indices[t as usize * 3..t as usize * 3 + 3]);
+    }
+    (out, seed)
+}
+
+/// `permute_triangles` の逆
+pub fn unpermute_triangles(indices: &[u32], seed: u64) -> Vec<u32> {
+    let perm = permutation_fy(indices.len() / 3, &mut XorShift64Rng::new(seed));
+    let mut out = vec![0u32; indices.len() / 3 * 3];
+    for (new, &old) in perm.iter().enumerate() {
+        out[old as usize * 3..old as usize * 3 + 3].copy_from_slice(&indices[new * 3..new * 3 + 3]);
+    }
+    out
+}
+
 /// 逆写像
 fn inverse_permutation(perm: &[u32]) -> Vec<u32> {
     let mut inv = vec![0u32; perm.len()];
@@ -36,8 +215,118 @@ fn inverse_permutation(perm: &[u32]) -> Vec<u32> {
     inv
 }
 
-#[inline] fn push_i16_le(buf: &mut Vec<u8>, v: i16) { buf.extend_from_slice(&v.to_le_bytes()); }
-#[inline] fn push_u16_le(buf: &mut Vec<u8>, v: u16) { buf.extend_from_slice(&v.to_le_bytes()); }
+#[inline]
+fn check_len(attribute: &'static str, got: usize, expected: usize) -> Result<(), MeshGuardError> {
+    if got == expected { Ok(()) } else { Err(MeshGuardError::LengthMismatch { attribute, expected, got }) }
+}
+
+#[inline] fn push_i16(buf: &mut Vec<u8>, v: i16, e: Endianness) { push_u16(buf, v as u16, e); }
+#[inline] fn push_u16(buf: &mut Vec<u8>, v: u16, e: Endianness) {
+    buf.extend_from_slice(&match e { Endianness::Little => v.to_le_bytes(), Endianness::Big => v.to_be_bytes() });
+}
+#[inline] fn push_u32(buf: &mut Vec<u8>, v: u32, e: Endianness) {
+    buf.extend_from_slice(&match e { Endianness::Little => v.to_le_bytes(), Endianness::Big => v.to_be_bytes() });
+}
+#[inline] fn read_u16(b: &[u8], e: Endianness) -> u16 {
+    let b = [b[0], b[1]];
+    match e { Endianness::Little => u16::from_le_bytes(b), Endianness::Big => u16::from_be_bytes(b) }
+}
+#[inline] fn read_u32(b: &[u8], e: Endianness) -> u32 {
+    let b = [b[0], b[1], b[2], b[3]];
+    match e { Endianness::Little => u32::from_le_bytes(b), Endianness::Big => u32::from_be_bytes(b) }
+}
+
+/// packに渡す量子化済み頂点属性
+/// どれを書き出すかはPackConfig側で決める
+#[derive(Clone, Copy, Debug)]
+pub struct MeshAttributes<'a> {
+    pub positions: &'a QuantizedPositions,
+    pub normals: Option<&'a QuantizedNormalsOct>,
+    pub normals_10_10_10_2: Option<&'a Packed10_10_10_2Normals>,
+    pub uvs: Option<&'a QuantizedUVs>,
+    pub uvs2: Option<&'a QuantizedUVs>,
+    pub tangents: Option<&'a QuantizedTangents>,
+    pub colors: Option<&'a QuantizedColors>,
+    pub joint_weights: Option<&'a QuantizedJointWeights>,
+    pub joint_indices: Option<&'a QuantizedJointIndices>,
+    pub ao: Option<&'a QuantizedAO>,
+}
+
+impl<'a> MeshAttributes<'a> {
+    pub fn new(positions: &'a QuantizedPositions, normals: &'a QuantizedNormalsOct, uvs: &'a QuantizedUVs) -> Self {
+        MeshAttributes {
+            positions,
+            normals: Some(normals),
+            normals_10_10_10_2: None,
+            uvs: Some(uvs),
+            uvs2: None,
+            tangents: None,
+            colors: None,
+            joint_weights: None,
+            joint_indices: None,
+            ao: None,
+        }
+    }
+
+    /// 座標だけ (深度プリパスやシャドウ用)
+    pub fn positions_only(positions: &'a QuantizedPositions) -> Self {
+        MeshAttributes {
+            positions,
+            normals: None,
+            normals_10_10_10_2: None,
+            uvs: None,
+            uvs2: None,
+            tangents: None,
+            colors: None,
+            joint_weights: None,
+            joint_indices: None,
+            ao: None,
+        }
+    }
+
+    /// 2つ目のUV (`PackConfig::uv_channels(2)` と合わせて使う)
+    pub fn with_uvs2(mut self, uvs2: &'a QuantizedUVs) -> Self {
+        self.uvs2 = Some(uvs2);
+        self
+    }
+
+    pub fn with_normals_10_10_10_2(mut self, normals: &'a Packed10_10_10_2Normals) -> Self {
+        self.normals_10_10_10_2 = Some(normals);
+        self
+    }
+
+    pub fn with_tangents(mut self, tangents: &'a QuantizedTangents) -> Self {
+        self.tangents = Some(tangents);
+        self
+    }
+
+    pub fn with_colors(mut self, colors: &'a QuantizedColors) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    pub fn with_ao(mut self, ao: &'a QuantizedAO) -> Self {
+        self.ao = Some(ao);
+        self
+    }
+
+    pub fn with_skinning(mut self, weights: &'a QuantizedJointWeights, indices: &'a QuantizedJointIndices) -> Self {
+        self.joint_weights = Some(weights);
+        self.joint_indices = Some(indices);
+        self
+    }
+}
+
+/// `validate_indices` のトポロジー指定版。ストリップでは STRIP_RESTART_INDEX を範囲外として扱わない
+pub fn validate_indices_with_topology(indices: &[u32], vertex_count: usize, topology: PrimitiveTopology) -> Result<(), MeshGuardError> {
+    if topology == PrimitiveTopology::Triangles {
+        return validate_indices(indices, vertex_count);
+    }
+    match indices.iter().position(|&i| i as usize >= vertex_count && i != STRIP_RESTART_INDEX) {
+        Some(position) => Err(MeshGuardError::IndexOutOfBounds { index: indices[position], position: Some(position), vertex_count }),
+        None => Ok(()),
+    }
+}
 
 pub fn pack_interleave_permute(
     qpos: &QuantizedPositions,
@@ -45,41 +334,201 @@ pub fn pack_interleave_permute(
     quv:  &QuantizedUVs,
     indices: Option<&[u32]>,
     perm_seed: u64,
-) -> PackedMesh {
-    let vertex_count = qpos.data.len() / 3;
-    assert_eq!(qnor.data.len(), vertex_count * 2, "normal length mismatch");
-    assert_eq!(quv.data.len(),  vertex_count * 2, "uv length mismatch");
+) -> Result<PackedMesh, MeshGuardError> {
+    pack_with_config(&MeshAttributes::new(qpos, qnor, quv), indices, perm_seed, &PackConfig::default())
+}
 
-    let perm = permutation_fy(vertex_count, perm_seed);
-    let inv  = inverse_permutation(&perm);
+/// 座標(6バイト)だけのinterleavedバッファを作る
+/// 深度プリパスやシャドウパス用。結果は `is_position_only()` がtrueになる
+pub fn pack_positions_only(
+    qpos: &QuantizedPositions,
+    indices: Option<&[u32]>,
+    perm_seed: u64,
+) -> Result<PackedMesh, MeshGuardError> {
+    let config = PackConfig::new().order(AttributeOrder::position_only());
+    pack_with_config(&MeshAttributes::positions_only(qpos), indices, perm_seed, &config)
+}
+
+/// 置換の乱数源を差し替える版
+/// シードが分からないので `perm_seeds` は空になる
+pub fn pack_interleave_permute_with_rng(
+    qpos: &QuantizedPositions,
+    qnor: &QuantizedNormalsOct,
+    quv:  &QuantizedUVs,
+    indices: Option<&[u32]>,
+    rng: impl MeshRng,
+) -> Result<PackedMesh, MeshGuardError> {
+    let mut rng = rng;
+    pack_permuted(&MeshAttributes::new(qpos, qnor, quv), indices, |n| permutation_fy(n, &mut rng), Vec::new(), &PackConfig::default())
+}
+
+/// 256bit鍵と64bitノンスによるChaCha20置換
+/// `perm_seeds` は空にする (ノンスを入れるとXorShiftのシードと区別できない)。鍵とノンスは呼び出し側で持つこと
+pub fn pack_interleave_permute_secure(
+    qpos: &QuantizedPositions,
+    qnor: &QuantizedNormalsOct,
+    quv:  &QuantizedUVs,
+    indices: Option<&[u32]>,
+    key: &[u8; 32],
+    nonce: u64,
+) -> Result<PackedMesh, MeshGuardError> {
+    let mut rng = ChaChaRng::new(key, nonce);
+    pack_permuted(&MeshAttributes::new(qpos, qnor, quv), indices, |n| permutation_fy(n, &mut rng), Vec::new(), &PackConfig::default())
+}
+
+/// seedsの順に置換を重ねがけする
+pub fn pack_multi_permute(
+    qpos: &QuantizedPositions,
+    qnor: &QuantizedNormalsOct,
+    quv:  &QuantizedUVs,
+    indices: Option<&[u32]>,
+    seeds: &[u64],
+) -> Result<PackedMesh, MeshGuardError> {
+    pack_permuted(&MeshAttributes::new(qpos, qnor, quv), indices, |n| multi_permutation(n, seeds), seeds.to_vec(), &PackConfig::default())
+}
+
+/// PackConfigのレイアウトで頂点を並べ替えつつinterleaveする
+pub fn pack_with_config(
+    attrs: &MeshAttributes,
+    indices: Option<&[u32]>,
+    perm_seed: u64,
+    config: &PackConfig,
+) -> Result<PackedMesh, MeshGuardError> {
+    pack_permuted(attrs, indices, |n| permutation_fy(n, &mut XorShift64Rng::new(perm_seed)), vec![perm_seed], config)
+}
+
+/// サブメッシュ付きのpack
+/// submeshesは (material_id, インデックス範囲) で、範囲は三角形単位 (3の倍数) であること
+/// 置換は頂点だけでインデックスの並びは変えないので、範囲はpack後もそのまま使える
+pub fn pack_with_submeshes(
+    attrs: &MeshAttributes,
+    indices: Option<&[u32]>,
+    submeshes: &[(u32, Range<usize>)],
+    perm_seed: u64,
+    config: &PackConfig,
+) -> Result<PackedMesh, MeshGuardError> {
+    let index_count = indices.map_or(attrs.positions.data.len() / 3, |i| i.len());
+    let mut ranges = Vec::with_capacity(submeshes.len());
+    for (material_id, range) in submeshes {
+        if range.start > range.end || range.end > index_count {
+            return Err(MeshGuardError::InvalidInput(format!("submesh range {:?} is outside {} indices", range, index_count)));
+        }
+        if !range.start.is_multiple_of(3) || !range.len().is_multiple_of(3) {
+            return Err(MeshGuardError::InvalidInput(format!("submesh range {:?} does not cover whole triangles", range)));
+        }
+        ranges.push(SubmeshRange { index_offset: range.start, index_count: range.len(), material_id: *material_id });
+    }
+    let mut mesh = pack_with_config(attrs, indices, perm_seed, config)?;
+    mesh.submeshes = ranges;
+    Ok(mesh)
+}
 
-    let mut interleaved = Vec::with_capacity(vertex_count * (3*2 + 2*2 + 2*2));
-    for new_idx in 0..vertex_count {
-        let old_idx = perm[new_idx] as usize;
+/// permuteは頂点数を受け取って置換 (新しい番号 -> 元の番号) を返す
+fn pack_permuted(
+    attrs: &MeshAttributes,
+    indices: Option<&[u32]>,
+    permute: impl FnOnce(usize) -> Vec<u32>,
+    perm_seeds: Vec<u64>,
+    config: &PackConfig,
+) -> Result<PackedMesh, MeshGuardError> {
+    let qpos = attrs.positions;
+    let vertex_count = qpos.data.len() / 3;
+    if vertex_count == 0 {
+        return Err(MeshGuardError::EmptyMesh);
+    }
+    check_len("position", qpos.data.len(), vertex_count * 3)?;
 
-        let px = qpos.data[old_idx * 3 + 0];
-        let py = qpos.data[old_idx * 3 + 1];
-        let pz = qpos.data[old_idx * 3 + 2];
-        push_i16_le(&mut interleaved, px);
-        push_i16_le(&mut interleaved, py);
-        push_i16_le(&mut interleaved, pz);
+    if let Some(kind) = config.attribute_order().duplicate() {
+        return Err(MeshGuardError::InvalidInput(format!("{:?} appears twice in the attribute order", kind)));
+    }
+    // 座標のない並び (空を含む) はstrideやAABBが意味を持たない
+    if !config.attributes().contains(&AttributeKind::Position) {
+        return Err(MeshGuardError::InvalidInput("attribute order has no Position".into()));
+    }
+    let layout = config.layout();
+    let mut writers = Vec::with_capacity(layout.attributes.len());
+    for attr in &layout.attributes {
+        let w = match attr.kind {
+            AttributeKind::Position if attr.format == VertexFormat::Float16x3 => AttributeWriter::PositionF16(qpos),
+            AttributeKind::Position => AttributeWriter::Position(qpos),
+            AttributeKind::Normal if attr.format == VertexFormat::Snorm10_10_10_2 => {
+                let q = attrs.normals_10_10_10_2.ok_or(MeshGuardError::MissingAttribute("10-10-10-2 normal"))?;
+                check_len("10-10-10-2 normal", q.data.len(), vertex_count)?;
+                AttributeWriter::Packed32(&q.data)
+            }
+            AttributeKind::Normal => {
+                let q = attrs.normals.ok_or(MeshGuardError::MissingAttribute("normal"))?;
+                check_len("normal", q.data.len(), vertex_count * 2)?;
+                AttributeWriter::Unorm16x2(&q.data)
+            }
+            AttributeKind::Uv => {
+                let q = attrs.uvs.ok_or(MeshGuardError::MissingAttribute("uv"))?;
+                check_len("uv", q.data.len(), vertex_count * 2)?;
+                AttributeWriter::Unorm16x2(&q.data)
+            }
+            AttributeKind::Uv2 => {
+                let q = attrs.uvs2.ok_or(MeshGuardError::MissingAttribute("uv2"))?;
+                check_len("uv2", q.data.len(), vertex_count * 2)?;
+                AttributeWriter::Unorm16x2(&q.data)
+            }
+            AttributeKind::Tangent => {
+                let q = attrs.tangents.ok_or(MeshGuardError::MissingAttribute("tangent"))?;
+                check_len("tangent", q.data.len(), vertex_count * 2)?;
+                check_len("tangent handedness", q.handedness.len(), vertex_count)?;
+                AttributeWriter::Tangent(q)
+            }
+            AttributeKind::Color => {
+                let q = attrs.colors.ok_or(MeshGuardError::MissingAttribute("color"))?;
+                check_len("color", q.data.len(), vertex_count * 4)?;
+                AttributeWriter::Bytes4(&q.data)
+            }
+            AttributeKind::JointWeights => {
+                let q = attrs.joint_weights.ok_or(MeshGuardError::MissingAttribute("joint weights"))?;
+                check_len("joint weights", q.data.len(), vertex_count * 4)?;
+                AttributeWriter::Bytes4(&q.data)
+            }
+            AttributeKind::JointIndices => {
+                let q = attrs.joint_indices.ok_or(MeshGuardError::MissingAttribute("joint indices"))?;
+                check_len("joint indices", q.len(), vertex_count * 4)?;
+                let wide = attr.format == VertexFormat::Uint16x4;
+                if !wide && matches!(q, QuantizedJointIndices::U16(_)) {
+                    return Err(MeshGuardError::InvalidInput("joint index exceeds 255 but u8 joint indices were requested".into()));
+                }
+                AttributeWriter::JointIndices(q, wide)
+            }
+            AttributeKind::AmbientOcclusion => {
+                let q = attrs.ao.ok_or(MeshGuardError::MissingAttribute("ambient occlusion"))?;
+                check_len("ambient occlusion", q.data.len(), vertex_count)?;
+                AttributeWriter::Byte(&q.data)
+            }
+        };
+        writers.push(w);
+    }
+    let topology = config.primitive_topology();
+    let restart = |i: u32| topology == PrimitiveTopology::TriangleStrip && i == STRIP_RESTART_INDEX;
+    if let Some(idx) = indices {
+        validate_indices_with_topology(idx, vertex_count, topology)?;
+    }
 
-        let nu = qnor.data[old_idx * 2 + 0];
-        let nv = qnor.data[old_idx * 2 + 1];
-        push_u16_le(&mut interleaved, nu);
-        push_u16_le(&mut interleaved, nv);
+    let perm = permute(vertex_count);
+    let inv  = inverse_permutation(&perm);
 
-        let uu = quv.data[old_idx * 2 + 0];
-        let vv = quv.data[old_idx * 2 + 1];
-        push_u16_le(&mut interleaved, uu);
-        push_u16_le(&mut interleaved, vv);
+    let mut interleaved = Vec::with_capacity(vertex_count * layout.stride);
+    for &old in &perm {
+        let old_idx = old as usize;
+        let base = interleaved.len();
+        for (w, attr) in writers.iter().zip(&layout.attributes) {
+            interleaved.resize(base + attr.offset, 0);
+            w.write(&mut interleaved, old_idx, layout.endianness);
+        }
+        interleaved.resize(base + layout.stride, 0);
     }
 
     // 逆写像を元に壊れたインデックスを治す必要がある
     let remapped_indices = if let Some(idx) = indices {
         let mut out = Vec::with_capacity(idx.len());
         for &i in idx {
-            let newi = inv[i as usize];
+            let newi = if restart(i) { i } else { inv[i as usize] };
             out.push(newi);
         }
         out
@@ -87,35 +536,566 @@ pub fn pack_interleave_permute(
         (0..vertex_count as u32).collect()
     };
 
-    PackedMesh {
+    Ok(PackedMesh {
         interleaved,
         vertex_count,
-        indices: remapped_indices,
+        indices: IndexBuffer::from_u32(remapped_indices, vertex_count),
         pos_scale: qpos.scale,
         pos_offset: qpos.offset,
-        perm_seed,
+        pos_bits: qpos.bits,
+        perm_seeds,
+        layout,
+        integrity_tag: None,
+        submeshes: Vec::new(),
+        topology,
+    })
+}
+
+/// 属性ごとの書き出し
+enum AttributeWriter<'a> {
+    Position(&'a QuantizedPositions),
+    PositionF16(&'a QuantizedPositions),
+    Unorm16x2(&'a [u16]),
+    Packed32(&'a [u32]),
+    Tangent(&'a QuantizedTangents),
+    Bytes4(&'a [u8]),
+    Byte(&'a [u8]),
+    /// trueならu16 x4、falseならu8 x4
+    JointIndices(&'a QuantizedJointIndices, bool),
+}
+
+impl AttributeWriter<'_> {
+    fn write(&self, buf: &mut Vec<u8>, i: usize, e: Endianness) {
+        match self {
+            AttributeWriter::Position(q) => {
+                push_i16(buf, q.data.get(i * 3), e);
+                push_i16(buf, q.data.get(i * 3 + 1), e);
+                push_i16(buf, q.data.get(i * 3 + 2), e);
+            }
+            AttributeWriter::PositionF16(q) => {
+                for a in 0..3 {
+                    let half_range = (1i64 << (q.bits[a] - 1)) as f64;
+                    let v = (q.data.get(i * 3 + a) as f64 + half_range) * q.scale[a] as f64 + q.offset[a] as f64;
+                    push_u16(buf, half::f16::from_f64(v).to_bits(), e);
+                }
+            }
+            AttributeWriter::Unorm16x2(data) => {
+                push_u16(buf, data[i * 2], e);
+                push_u16(buf, data[i * 2 + 1], e);
+            }
+            AttributeWriter::Packed32(data) => {
+                push_u32(buf, data[i], e);
+            }
+            AttributeWriter::Tangent(q) => {
+                push_i16(buf, q.data[i * 2], e);
+                push_i16(buf, q.data[i * 2 + 1], e);
+                push_i16(buf, q.handedness[i] as i16 * 32767, e);
+                push_i16(buf, 0, e);
+            }
+            AttributeWriter::Bytes4(data) => {
+                buf.extend_from_slice(&data[i * 4..i * 4 + 4]);
+            }
+            AttributeWriter::Byte(data) => buf.push(data[i]),
+            AttributeWriter::JointIndices(q, wide) => {
+                for c in 0..4 {
+                    let j = q.get(i * 4 + c);
+                    if *wide { push_u16(buf, j, e); } else { buf.push(j as u8); }
+                }
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use meshguard_quantize::{quantize_positions, encode_normals_oct, quantize_uvs};
+    use meshguard_quantize::{PositionData, quantize_positions, encode_normals_oct, quantize_uvs, quantize_tangents, quantize_vertex_colors,
+        quantize_joint_weights, quantize_joint_indices, encode_normals_10_10_10_2};
 
     #[test]
     fn pack_round_lengths() {
         let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
         let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
         let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
-        let qpos = quantize_positions(&pos);
+        let qpos = quantize_positions(&pos).unwrap();
         let qnor = encode_normals_oct(&nor);
         let quv  = quantize_uvs(&uv);
 
         let idx  = vec![0u32, 1, 2];
-        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 0x1234_5678_9ABC_DEF0);
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 0x1234_5678_9ABC_DEF0).unwrap();
 
         assert_eq!(packed.vertex_count, 3);
         assert_eq!(packed.interleaved.len(), 3 * 14);
         assert_eq!(packed.indices.len(), 3);
     }
+
+    #[test]
+    fn small_meshes_use_u16_indices() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let qpos = quantize_positions(&pos).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0]; 3]);
+        let quv  = quantize_uvs(&[[0.0,0.0]; 3]);
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[2, 0, 1]), 7).unwrap();
+        assert!(matches!(packed.indices, IndexBuffer::U16(_)));
+        let idx = packed.indices_u32();
+        assert_eq!(idx.len(), 3);
+        assert!(idx.iter().all(|&i| i < 3));
+
+        assert!(matches!(IndexBuffer::from_u32(vec![0, 65535], 65535), IndexBuffer::U16(_)));
+        let big = IndexBuffer::from_u32(vec![0, 65536], 65537);
+        assert_eq!(big, IndexBuffer::U32(vec![0, 65536]));
+        assert_eq!(big.to_u32(), vec![0, 65536]);
+    }
+
+    #[test]
+    fn pack_rejects_bad_input() {
+        let qpos = quantize_positions(&[[0.0,0.0,0.0],[1.0,1.0,1.0]]).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0],[0.0,1.0,0.0]]);
+        let quv  = quantize_uvs(&[[0.0,0.0]]);
+        let err = pack_interleave_permute(&qpos, &qnor, &quv, None, 1).unwrap_err();
+        assert_eq!(err, MeshGuardError::LengthMismatch { attribute: "uv", expected: 4, got: 2 });
+
+        let quv  = quantize_uvs(&[[0.0,0.0],[1.0,1.0]]);
+        let err = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 1).unwrap_err();
+        assert_eq!(err, MeshGuardError::IndexOutOfBounds { index: 2, position: Some(2), vertex_count: 2 });
+        assert_eq!(err.to_string(), "index 2 at position 2 out of bounds for 2 vertices");
+        assert_eq!(validate_indices(&[0, 1, 1, 0, 5, 9], 2).unwrap_err(), MeshGuardError::IndexOutOfBounds { index: 5, position: Some(4), vertex_count: 2 });
+        assert!(validate_indices(&[0, 1, 1], 2).is_ok());
+        assert!(validate_indices(&[0, 1, STRIP_RESTART_INDEX], 2).is_err());
+        assert!(validate_indices_with_topology(&[0, 1, STRIP_RESTART_INDEX, 1, 0], 2, PrimitiveTopology::TriangleStrip).is_ok());
+
+        // 空入力は量子化の段階で弾かれるが、手で組んだ0頂点でもpanicしない
+        assert_eq!(quantize_positions(&[]).unwrap_err(), MeshGuardError::EmptyMesh);
+        let empty = QuantizedPositions { data: PositionData::I16(Vec::new()), bits: [16; 3], scale: [1.0; 3], offset: [0.0; 3] };
+        let err = pack_interleave_permute(&empty, &encode_normals_oct(&[]), &quantize_uvs(&[]), None, 1).unwrap_err();
+        assert_eq!(err, MeshGuardError::EmptyMesh);
+    }
+
+    #[test]
+    fn aabb_from_metadata() {
+        let pos = vec![[-1.5,0.0,2.0],[10.0,20.0,30.0],[3.0,-4.0,5.0]];
+        for bits in [16u8, 11] {
+            let qpos = meshguard_quantize::quantize_positions_with_bits(&pos, bits).unwrap();
+            let (min, max) = packed_mesh_aabb(&pack_positions_only(&qpos, None, 1).unwrap());
+            assert_eq!(min, [-1.5, -4.0, 2.0]);
+            for (a, want) in [10.0f32, 20.0, 30.0].into_iter().enumerate() {
+                assert!((max[a] - want).abs() < 1e-4, "{} bits axis {}: {}", bits, a, max[a]);
+            }
+        }
+    }
+
+    #[test]
+    fn pack_with_tangents() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let qpos = quantize_positions(&pos).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0]; 3]);
+        let quv  = quantize_uvs(&[[0.25,0.5]; 3]);
+        let qtan = quantize_tangents(&[[1.0,0.0,0.0,-1.0]; 3]);
+
+        let config = PackConfig::new().tangents(true);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        assert_eq!(pack_with_config(&attrs, None, 3, &config).unwrap_err(), MeshGuardError::MissingAttribute("tangent"));
+
+        let packed = pack_with_config(&attrs.with_tangents(&qtan), None, 3, &config).unwrap();
+        assert_eq!(packed.layout.stride, 22);
+        assert_eq!(packed.interleaved.len(), 3 * 22);
+        let tan = packed.layout.attribute(AttributeKind::Tangent).unwrap();
+        assert_eq!((tan.offset, tan.format), (14, VertexFormat::Snorm16x4));
+        let v = &packed.interleaved[tan.offset..tan.offset + 8];
+        let w = i16::from_le_bytes([v[4], v[5]]);
+        assert_eq!(i16::from_le_bytes([v[0], v[1]]), qtan.data[0]);
+        assert_eq!(w, -32767);
+    }
+
+    #[test]
+    fn pack_with_colors() {
+        let pos = vec![[0.0,0.0,0.0],[1.0,1.0,1.0]];
+        let qpos = quantize_positions(&pos).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0]; 2]);
+        let quv  = quantize_uvs(&[[0.0,0.0]; 2]);
+        let qcol = quantize_vertex_colors(&[[1.0,0.0,0.5,1.0]; 2]);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_colors(&qcol);
+        let packed = pack_with_config(&attrs, None, 9, &PackConfig::new().colors(true)).unwrap();
+        assert_eq!(packed.layout.stride, 18);
+        let col = packed.layout.attribute(AttributeKind::Color).unwrap();
+        assert_eq!(col.offset, 14);
+        assert_eq!(&packed.interleaved[14..18], &[255, 0, 128, 255]);
+    }
+
+    #[test]
+    fn estimate_matches_allocation() {
+        let configs = [
+            PackConfig::new(),
+            PackConfig::new().align_attributes(true),
+            PackConfig::new().order(AttributeOrder::position_only()),
+            PackConfig::new().colors(true).tangents(true).skinning(true).joint_indices_u8(true).align_attributes(true),
+            PackConfig::new().ambient_occlusion(true).uv_channels(2).normals_10_10_10_2(true).align_attributes(true),
+            PackConfig::new().f16_positions(true).skinning(true),
+        ];
+        for config in &configs {
+            assert_eq!(config.stride(), config.layout().stride, "{:?}", config);
+        }
+        for n in [3usize, 70_000] {
+            let pos: Vec<[f32; 3]> = (0..n).map(|i| [i as f32, 0.0, 1.0]).collect();
+            let qpos = quantize_positions(&pos).unwrap();
+            let (qnor, quv) = (encode_normals_oct(&vec![[0.0,0.0,1.0]; n]), quantize_uvs(&vec![[0.0,0.0]; n]));
+            let idx: Vec<u32> = (0..n as u32).chain(0..3).collect();
+            let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 1).unwrap();
+            let index_bytes = match &packed.indices { IndexBuffer::U16(v) => v.len() * 2, IndexBuffer::U32(v) => v.len() * 4 };
+            assert_eq!(estimate_packed_bytes(n, idx.len(), &PackConfig::new()), packed.interleaved.len() + index_bytes);
+        }
+    }
+
+    #[test]
+    fn pack_with_ao() {
+        let qpos = quantize_positions(&[[0.0,0.0,0.0],[1.0,1.0,1.0]]).unwrap();
+        let (qnor, quv) = (encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.0,0.0]; 2]));
+        let qao = meshguard_quantize::quantize_ao(&[0.25, 0.25]);
+        let config = PackConfig::new().ambient_occlusion(true);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        assert_eq!(pack_with_config(&attrs, None, 9, &config).unwrap_err(), MeshGuardError::MissingAttribute("ambient occlusion"));
+        let packed = pack_with_config(&attrs.with_ao(&qao), None, 9, &config).unwrap();
+        assert_eq!(packed.layout.stride, 15);
+        assert_eq!(packed.layout.attribute(AttributeKind::AmbientOcclusion).unwrap().format, VertexFormat::Unorm8);
+        assert_eq!((packed.interleaved[14], packed.interleaved[29]), (64, 64));
+        assert_eq!(pack_with_config(&attrs.with_ao(&qao), None, 9, &config.align_attributes(true)).unwrap().layout.stride, 24);
+    }
+
+    #[test]
+    fn pack_with_skinning() {
+        let qpos = quantize_positions(&[[0.0,0.0,0.0],[1.0,1.0,1.0]]).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0]; 2]);
+        let quv  = quantize_uvs(&[[0.0,0.0]; 2]);
+        let qw = quantize_joint_weights(&[[0.5,0.5,0.0,0.0]; 2]);
+        let qj = quantize_joint_indices(&[[3,7,0,0]; 2]);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_skinning(&qw, &qj);
+
+        let packed = pack_with_config(&attrs, None, 1, &PackConfig::new().skinning(true)).unwrap();
+        assert_eq!(packed.layout.stride, 14 + 8 + 4);
+        let j = packed.layout.attribute(AttributeKind::JointIndices).unwrap();
+        assert_eq!(&packed.interleaved[j.offset..j.offset + 4], &[3, 0, 7, 0]);
+
+        let config = PackConfig::new().skinning(true).joint_indices_u8(true);
+        let packed = pack_with_config(&attrs, None, 1, &config).unwrap();
+        assert_eq!(packed.layout.stride, 14 + 4 + 4);
+        let w = packed.layout.attribute(AttributeKind::JointWeights).unwrap();
+        assert_eq!(&packed.interleaved[w.offset..w.offset + 4], &[127, 128, 0, 0]);
+
+        let big = quantize_joint_indices(&[[300,0,0,0]; 2]);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_skinning(&qw, &big);
+        assert!(matches!(pack_with_config(&attrs, None, 1, &config), Err(MeshGuardError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn pack_f16_positions() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let qpos = quantize_positions(&pos).unwrap();
+        let qnor = encode_normals_oct(&[[0.0,0.0,1.0]; 3]);
+        let quv  = quantize_uvs(&[[0.0,0.0]; 3]);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let packed = pack_with_config(&attrs, Some(&[0, 1, 2]), 5, &PackConfig::new().f16_positions(true)).unwrap();
+        assert_eq!(packed.layout.stride, 14);
+        assert_eq!(packed.layout.attributes[0].format, VertexFormat::Float16x3);
+
+        let idx = packed.indices_u32();
+        for (old, &new) in idx.iter().enumerate() {
+            let v = &packed.interleaved[new as usize * 14..];
+            for a in 0..3 {
+                let h = half::f16::from_le_bytes([v[a * 2], v[a * 2 + 1]]).to_f32();
+                assert!((h - pos[old][a]).abs() <= pos[old][a].abs() * 1e-3 + 1e-3, "{} vs {}", h, pos[old][a]);
+            }
+        }
+    }
+
+    #[test]
+    fn pack_10_10_10_2_normals() {
+        let qpos = quantize_positions(&[[0.0,0.0,0.0],[1.0,1.0,1.0]]).unwrap();
+        let nor = [[0.0,0.0,1.0],[0.0,1.0,0.0]];
+        let qnor = encode_normals_oct(&nor);
+        let qn10 = encode_normals_10_10_10_2(&nor);
+        let quv  = quantize_uvs(&[[0.0,0.0]; 2]);
+        let config = PackConfig::new().normals_10_10_10_2(true);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        assert_eq!(pack_with_config(&attrs, None, 0, &config).unwrap_err(), MeshGuardError::MissingAttribute("10-10-10-2 normal"));
+
+        let packed = pack_with_config(&attrs.with_normals_10_10_10_2(&qn10), Some(&[0, 1]), 0, &config).unwrap();
+        assert_eq!(packed.layout.stride, 14);
+        let n = packed.layout.attribute(AttributeKind::Normal).unwrap();
+        assert_eq!((n.offset, n.format), (6, VertexFormat::Snorm10_10_10_2));
+        let v0 = packed.indices_u32()[0] as usize * 14 + n.offset;
+        let word = u32::from_le_bytes(packed.interleaved[v0..v0 + 4].try_into().unwrap());
+        assert_eq!(word, qn10.data[0]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
+        let packed = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&nor), &quantize_uvs(&uv), None, 9).unwrap();
+        let same = |m: &PackedMesh| {
+            assert_eq!(m.interleaved, packed.interleaved);
+            assert_eq!(m.indices, packed.indices);
+            assert_eq!(m.layout, packed.layout);
+            assert_eq!((m.pos_scale, m.pos_offset, m.pos_bits, &m.perm_seeds), (packed.pos_scale, packed.pos_offset, packed.pos_bits, &packed.perm_seeds));
+        };
+
+        let json = serde_json::to_value(&packed).unwrap();
+        assert!(json["interleaved"].is_string());
+        same(&serde_json::from_value(json).unwrap());
+        same(&rmp_serde::from_slice(&rmp_serde::to_vec(&packed).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn pack_with_custom_rng() {
+        struct Zero;
+        impl MeshRng for Zero {
+            fn next_u64(&mut self) -> u64 { 0 }
+        }
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0]; 3];
+        let uv  = vec![[0.0,0.0]; 3];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+
+        // 既定RNGと同じ系列なら結果も同じ
+        let a = pack_interleave_permute(&qpos, &qnor, &quv, None, 77).unwrap();
+        let b = pack_interleave_permute_with_rng(&qpos, &qnor, &quv, None, XorShift64Rng::new(77)).unwrap();
+        assert_eq!((a.interleaved, a.indices), (b.interleaved, b.indices));
+
+        // 常に0を返すRNGだと [1, 2, 0] の順になる
+        let z = pack_interleave_permute_with_rng(&qpos, &qnor, &quv, Some(&[0, 1, 2]), Zero).unwrap();
+        assert_eq!(z.indices_u32(), vec![2, 0, 1]);
+        assert!(z.perm_seeds.is_empty());
+    }
+
+    #[test]
+    fn secure_permutation_is_reproducible() {
+        let pos: Vec<[f32; 3]> = (0..50).map(|i| [i as f32, (i * 7 % 13) as f32, 0.5 * i as f32]).collect();
+        let nor = vec![[0.0,0.0,1.0]; 50];
+        let uv  = vec![[0.0,0.0]; 50];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+        let key = [0x42u8; 32];
+        let idx: Vec<u32> = (0..48).collect();
+        let packed = pack_interleave_permute_secure(&qpos, &qnor, &quv, Some(&idx), &key, 9).unwrap();
+        assert!(packed.perm_seeds.is_empty());
+
+        // 同じ鍵とノンスから置換を作り直して元の順に戻せる
+        let perm = vertex_permutation(50, ChaChaRng::new(&key, 9));
+        for (new, &old) in perm.iter().enumerate() {
+            let x = i16::from_le_bytes(packed.interleaved[new * 14..new * 14 + 2].try_into().unwrap());
+            assert_eq!(x, qpos.data.get(old as usize * 3));
+        }
+        let restored: Vec<u32> = packed.indices_u32().iter().map(|&i| perm[i as usize]).collect();
+        assert_eq!(restored, idx);
+
+        let other = pack_interleave_permute_secure(&qpos, &qnor, &quv, Some(&idx), &[0x43u8; 32], 9).unwrap();
+        assert_ne!(other.interleaved, packed.interleaved);
+    }
+
+    #[test]
+    fn triangle_and_vertex_permutations_invert() {
+        let pos: Vec<[f32; 3]> = (0..6).map(|i| [i as f32, 0.0, (i * i) as f32]).collect();
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 6]), quantize_uvs(&[[0.0,0.0]; 6]));
+        let idx = vec![0u32, 1, 2, 2, 1, 3, 3, 4, 5, 5, 4, 0];
+
+        let (tri, seed) = permute_triangles(&idx, 31);
+        assert_eq!(seed, 31);
+        assert_ne!(tri, idx);
+        assert_eq!(unpermute_triangles(&tri, seed), idx);
+
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&tri), 8).unwrap();
+        let vperm = vertex_permutation(6, XorShift64Rng::new(8));
+        let back: Vec<u32> = packed.indices_u32().iter().map(|&i| vperm[i as usize]).collect();
+        assert_eq!(unpermute_triangles(&back, seed), idx);
+    }
+
+    #[test]
+    fn multi_permute_composes() {
+        let pos: Vec<[f32; 3]> = (0..40).map(|i| [i as f32, (i % 7) as f32, 0.0]).collect();
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 40]), quantize_uvs(&[[0.0,0.0]; 40]));
+        let idx: Vec<u32> = (0..39).collect();
+        let seeds = [3u64, 99, 12345];
+        let packed = pack_multi_permute(&qpos, &qnor, &quv, Some(&idx), &seeds).unwrap();
+        assert_eq!(packed.perm_seeds, seeds.to_vec());
+
+        let perm = multi_permutation(40, &seeds);
+        for (new, &old) in perm.iter().enumerate() {
+            let x = i16::from_le_bytes(packed.interleaved[new * 14..new * 14 + 2].try_into().unwrap());
+            assert_eq!(x, qpos.data.get(old as usize * 3));
+        }
+        let back: Vec<u32> = packed.indices_u32().iter().map(|&i| perm[i as usize]).collect();
+        assert_eq!(back, idx);
+
+        // 1回だけなら通常のpackと同じ
+        let single = pack_multi_permute(&qpos, &qnor, &quv, Some(&idx), &[3]).unwrap();
+        assert_eq!(single.interleaved, pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 3).unwrap().interleaved);
+    }
+
+    #[test]
+    fn second_uv_channel() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.25,0.5]; 2]));
+        let quv2 = meshguard_quantize::quantize_uvs2(&[[1.0,0.0],[0.5,0.5]]);
+
+        assert_eq!(PackConfig::new().uv_channels(0).attributes(), &[AttributeKind::Position, AttributeKind::Normal]);
+        assert_eq!(PackConfig::new().uv_channels(1).layout().stride, 14);
+        let cfg = PackConfig::new().order(AttributeOrder::puvn()).uv_channels(2);
+        assert_eq!(cfg.attributes(), &[AttributeKind::Position, AttributeKind::Uv, AttributeKind::Uv2, AttributeKind::Normal]);
+        assert_eq!(cfg.clone().uv_channels(1).attributes(), AttributeOrder::puvn().0.as_slice());
+
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        assert_eq!(pack_with_config(&attrs, None, 1, &cfg).unwrap_err(), MeshGuardError::MissingAttribute("uv2"));
+        let packed = pack_with_config(&attrs.with_uvs2(&quv2), None, 1, &cfg).unwrap();
+        assert_eq!(packed.layout.stride, 18);
+        let uv2 = packed.layout.attribute(AttributeKind::Uv2).unwrap().offset;
+        assert_eq!(uv2, 10);
+        let perm = vertex_permutation(2, XorShift64Rng::new(1));
+        for (new, v) in packed.interleaved.chunks(18).enumerate() {
+            let old = perm[new] as usize;
+            assert_eq!(u16::from_le_bytes([v[uv2], v[uv2 + 1]]), quv2.data[old * 2]);
+        }
+    }
+
+    #[test]
+    fn custom_attribute_order() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.25,0.5]; 2]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let pnuv = pack_with_config(&attrs, None, 4, &PackConfig::new()).unwrap();
+        let puvn = pack_with_config(&attrs, None, 4, &PackConfig::new().order(AttributeOrder::puvn())).unwrap();
+        assert_eq!(puvn.layout.stride, 14);
+        assert_eq!(puvn.layout.attribute(AttributeKind::Uv).unwrap().offset, 6);
+        assert_eq!(puvn.layout.attribute(AttributeKind::Normal).unwrap().offset, 10);
+        for (a, b) in pnuv.interleaved.chunks(14).zip(puvn.interleaved.chunks(14)) {
+            assert_eq!(&a[0..6], &b[0..6]);
+            assert_eq!(&a[6..10], &b[10..14]);
+            assert_eq!(&a[10..14], &b[6..10]);
+        }
+
+        // 座標だけを先頭にした並びにも後から属性を足せる
+        let cfg = PackConfig::new().order(AttributeOrder(vec![AttributeKind::Position])).colors(true);
+        assert_eq!(cfg.attributes(), &[AttributeKind::Position, AttributeKind::Color]);
+
+        let dup = PackConfig::new().order(AttributeOrder(vec![AttributeKind::Position, AttributeKind::Uv, AttributeKind::Uv]));
+        assert!(matches!(pack_with_config(&attrs, None, 4, &dup), Err(MeshGuardError::InvalidInput(_))));
+        for order in [vec![], vec![AttributeKind::Normal, AttributeKind::Uv]] {
+            let cfg = PackConfig::new().order(AttributeOrder(order));
+            assert!(matches!(pack_with_config(&attrs, None, 4, &cfg), Err(MeshGuardError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn aligned_layout_pads() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.25,0.5]; 2]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let tight = pack_with_config(&attrs, None, 4, &PackConfig::new()).unwrap();
+        let aligned = pack_with_config(&attrs, None, 4, &PackConfig::new().align_attributes(true)).unwrap();
+        assert_eq!(aligned.layout.stride, 16);
+        assert_eq!(aligned.layout.attributes.iter().map(|a| a.offset).collect::<Vec<_>>(), vec![0, 8, 12]);
+        assert_eq!(aligned.interleaved.len(), 32);
+        for (t, a) in tight.interleaved.chunks(14).zip(aligned.interleaved.chunks(16)) {
+            assert_eq!(&t[0..6], &a[0..6]);
+            assert_eq!(&a[6..8], &[0, 0]);
+            assert_eq!(&t[6..14], &a[8..16]);
+        }
+
+        // 接線(i16 x4)は8バイト境界に置かれる
+        let layout = PackConfig::new().tangents(true).align_attributes(true).layout();
+        assert_eq!(layout.attribute(AttributeKind::Tangent).unwrap().offset, 16);
+        assert_eq!(layout.stride, 24);
+    }
+
+    #[test]
+    fn big_endian_swaps_components() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+        let qn10 = encode_normals_10_10_10_2(&nor);
+        let qtan = quantize_tangents(&[[1.0,0.0,0.0,1.0], [0.0,1.0,0.0,-1.0], [0.0,0.0,1.0,1.0]]);
+        let qcol = quantize_vertex_colors(&[[1.0,0.5,0.0,1.0]; 3]);
+        let qw = quantize_joint_weights(&[[0.5,0.5,0.0,0.0]; 3]);
+        let qj = quantize_joint_indices(&[[1,2,3,300]; 3]);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_tangents(&qtan).with_colors(&qcol).with_skinning(&qw, &qj)
+            .with_normals_10_10_10_2(&qn10);
+
+        let configs = [
+            PackConfig::new(),
+            PackConfig::new().tangents(true).colors(true).skinning(true),
+            PackConfig::new().f16_positions(true).normals_10_10_10_2(true),
+        ];
+        for cfg in configs {
+            let le = pack_with_config(&attrs, Some(&[0, 1, 2]), 6, &cfg).unwrap();
+            let be = pack_with_config(&attrs, Some(&[0, 1, 2]), 6, &cfg.clone().endianness(Endianness::Big)).unwrap();
+            assert_eq!(be.layout.endianness, Endianness::Big);
+            assert_eq!(le.indices, be.indices);
+            for (l, b) in le.interleaved.chunks(le.layout.stride).zip(be.interleaved.chunks(be.layout.stride)) {
+                for attr in &le.layout.attributes {
+                    let c = attr.format.component_size();
+                    let range = attr.offset..attr.offset + attr.format.size();
+                    for (lc, bc) in l[range.clone()].chunks(c).zip(b[range].chunks(c)) {
+                        let mut swapped = lc.to_vec();
+                        swapped.reverse();
+                        assert_eq!(swapped, bc, "{:?}", attr.kind);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn positions_only_matches_full_pack_positions() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let depth = pack_positions_only(&qpos, Some(&[0, 1, 2]), 9).unwrap();
+        let full = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 9).unwrap();
+        assert!(depth.is_position_only() && !full.is_position_only());
+        assert_eq!(depth.layout.stride, 6);
+        assert_eq!(depth.interleaved.len(), 18);
+        assert_eq!(depth.indices, full.indices);
+        for (d, f) in depth.interleaved.chunks(6).zip(full.interleaved.chunks(14)) {
+            assert_eq!(d, &f[..6]);
+        }
+    }
+
+    #[test]
+    fn submesh_ranges_survive_permutation() {
+        let pos = vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0],[1.0,1.0,0.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 4]), quantize_uvs(&[[0.5,0.5]; 4]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let idx = [0, 1, 2, 2, 1, 3];
+        let mesh = pack_with_submeshes(&attrs, Some(&idx), &[(4, 0..3), (7, 3..6)], 5, &PackConfig::new()).unwrap();
+        assert_eq!(mesh.submeshes, vec![
+            SubmeshRange { index_offset: 0, index_count: 3, material_id: 4 },
+            SubmeshRange { index_offset: 3, index_count: 3, material_id: 7 },
+        ]);
+        // インデックスの並びは変わらないので範囲はそのまま同じ三角形を指す
+        let plain = pack_with_config(&attrs, Some(&idx), 5, &PackConfig::new()).unwrap();
+        assert_eq!(mesh.indices, plain.indices);
+
+        assert!(pack_with_submeshes(&attrs, Some(&idx), &[(0, 0..9)], 5, &PackConfig::new()).is_err());
+        assert!(pack_with_submeshes(&attrs, Some(&idx), &[(0, 1..4)], 5, &PackConfig::new()).is_err());
+    }
+
+    #[test]
+    fn strip_indices_flow_through() {
+        let pos = vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0],[1.0,1.0,0.0],[2.0,0.0,0.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 5]), quantize_uvs(&[[0.5,0.5]; 5]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+        let strip = [0, 1, 2, 3, STRIP_RESTART_INDEX, 1, 4, 3];
+        let config = PackConfig::new().topology(PrimitiveTopology::TriangleStrip);
+        let mesh = pack_with_config(&attrs, Some(&strip), 8, &config).unwrap();
+        assert_eq!(mesh.topology, PrimitiveTopology::TriangleStrip);
+        assert!(matches!(&mesh.indices, IndexBuffer::U16(v) if v[4] == u16::MAX));
+        assert_eq!(mesh.indices_u32()[4], STRIP_RESTART_INDEX);
+        // リスタート以外は三角形リストと同じ付け替え
+        let list = pack_with_config(&attrs, Some(&[0, 1, 2, 3, 1, 4, 3, 0]), 8, &PackConfig::new()).unwrap();
+        let (s, l) = (mesh.indices_u32(), list.indices_u32());
+        assert_eq!([s[0], s[1], s[2], s[3], s[5], s[6], s[7]], [l[0], l[1], l[2], l[3], l[4], l[5], l[6]]);
+
+        // 三角形リストではリスタート値は範囲外
+        assert!(pack_with_config(&attrs, Some(&strip), 8, &PackConfig::new()).is_err());
+    }
 }
@@ -0,0 +1,92 @@
+use crate::{IndexBuffer, MeshGuardError, PackedMesh, PrimitiveTopology, SubmeshRange};
+
+/// `merge_packed_meshes` で使う pos_scale / pos_offset の許容差
+pub const DEFAULT_MERGE_TOLERANCE: f32 = 1e-6;
+
+/// 2つのPackedMeshを連結する (bのインデックスは a.vertex_count だけずらす)
+/// 量子化グリッドが違うと同じi16値が別の座標になるので、scale/offsetが合わなければエラー
+pub fn merge_packed_meshes(a: &PackedMesh, b: &PackedMesh) -> Result<PackedMesh, MeshGuardError> {
+    merge_packed_meshes_with_tolerance(a, b, DEFAULT_MERGE_TOLERANCE)
+}
+
+/// 許容差を指定する版 (軸ごとの絶対差)
+/// サブメッシュ表はa, bの表を引き継ぐ。表がない側は全体を1つの範囲にする (material_id は aが0, bが1)
+/// 置換が2種類混ざるので `perm_seeds` は空、署名は付け直しが必要なので `integrity_tag` はNone
+pub fn merge_packed_meshes_with_tolerance(a: &PackedMesh, b: &PackedMesh, tolerance: f32) -> Result<PackedMesh, MeshGuardError> {
+    if a.topology != PrimitiveTopology::Triangles || b.topology != PrimitiveTopology::Triangles {
+        return Err(MeshGuardError::InvalidInput("only triangle lists can be merged".into()));
+    }
+    if a.layout != b.layout {
+        return Err(MeshGuardError::InvalidInput("meshes have different vertex layouts".into()));
+    }
+    if a.pos_bits != b.pos_bits {
+        return Err(MeshGuardError::InvalidInput(format!("position bit depths differ: {:?} vs {:?}", a.pos_bits, b.pos_bits)));
+    }
+    let close = |x: [f32; 3], y: [f32; 3]| (0..3).all(|i| (x[i] - y[i]).abs() <= tolerance);
+    if !close(a.pos_scale, b.pos_scale) || !close(a.pos_offset, b.pos_offset) {
+        return Err(MeshGuardError::InvalidInput(format!(
+            "quantization grids differ: scale {:?} / {:?}, offset {:?} / {:?}", a.pos_scale, b.pos_scale, a.pos_offset, b.pos_offset)));
+    }
+    let vertex_count = a.vertex_count + b.vertex_count;
+    if vertex_count > u32::MAX as usize {
+        return Err(MeshGuardError::InvalidInput("merged mesh exceeds u32 index range".into()));
+    }
+
+    let mut interleaved = Vec::with_capacity(a.interleaved.len() + b.interleaved.len());
+    interleaved.extend_from_slice(&a.interleaved);
+    interleaved.extend_from_slice(&b.interleaved);
+    let mut indices = a.indices_u32();
+    let base = a.vertex_count as u32;
+    indices.extend(b.indices_u32().into_iter().map(|i| i + base));
+
+    let table = |m: &PackedMesh, material_id: u32| {
+        if m.submeshes.is_empty() { vec![SubmeshRange { index_offset: 0, index_count: m.indices.len(), material_id }] } else { m.submeshes.clone() }
+    };
+    let mut submeshes = table(a, 0);
+    submeshes.extend(table(b, 1).into_iter().map(|s| SubmeshRange { index_offset: s.index_offset + a.indices.len(), ..s }));
+
+    Ok(PackedMesh {
+        interleaved,
+        vertex_count,
+        indices: IndexBuffer::from_u32(indices, vertex_count),
+        pos_scale: a.pos_scale,
+        pos_offset: a.pos_offset,
+        pos_bits: a.pos_bits,
+        perm_seeds: Vec::new(),
+        layout: a.layout.clone(),
+        integrity_tag: None,
+        submeshes,
+        topology: PrimitiveTopology::Triangles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack_interleave_permute;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_positions_with_aabb, quantize_uvs};
+
+    #[test]
+    fn merge_offsets_indices_and_checks_grid() {
+        let pa = vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0]];
+        let pb = vec![[1.0,1.0,1.0],[0.5,0.0,1.0],[0.0,0.5,1.0]];
+        let (min, max) = ([0.0; 3], [1.0; 3]);
+        let (qn, qu) = (encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.5,0.5]; 3]));
+        let a = pack_interleave_permute(&quantize_positions_with_aabb(&pa, min, max).unwrap(), &qn, &qu, Some(&[0, 1, 2]), 1).unwrap();
+        let b = pack_interleave_permute(&quantize_positions_with_aabb(&pb, min, max).unwrap(), &qn, &qu, Some(&[0, 2, 1]), 2).unwrap();
+
+        let m = merge_packed_meshes(&a, &b).unwrap();
+        assert_eq!(m.vertex_count, 6);
+        assert_eq!(&m.interleaved[42..], &b.interleaved[..]);
+        assert_eq!(m.indices_u32()[3..], b.indices_u32().iter().map(|i| i + 3).collect::<Vec<_>>()[..]);
+        assert_eq!(m.submeshes, vec![
+            SubmeshRange { index_offset: 0, index_count: 3, material_id: 0 },
+            SubmeshRange { index_offset: 3, index_count: 3, material_id: 1 },
+        ]);
+
+        // 別々のAABBで量子化したものは混ぜられない
+        let c = pack_interleave_permute(&quantize_positions(&pb).unwrap(), &qn, &qu, None, 3).unwrap();
+        assert!(merge_packed_meshes(&a, &c).is_err());
+        assert!(merge_packed_meshes_with_tolerance(&a, &c, 10.0).is_ok());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::unpack::seeded_permutation;
+use crate::{push_i16, MeshGuardError, PackedMesh};
+use meshguard_quantize::QuantizedMorphDeltas;
+
+/// モーフ差分をmeshと同じ頂点順に並べ替えた別ストリーム (i16 x3, 6バイト/頂点) にする
+/// エンディアンはmeshのレイアウトに合わせる。scale/offsetは `deltas` 側のものをそのまま使う
+/// 置換を `perm_seeds` から作り直せないメッシュ (任意RNGや鍵付き置換) はInvalidInput
+pub fn pack_morph_deltas(deltas: &QuantizedMorphDeltas, mesh: &PackedMesh) -> Result<Vec<u8>, MeshGuardError> {
+    let n = mesh.vertex_count;
+    if deltas.data.len() != n * 3 {
+        return Err(MeshGuardError::LengthMismatch { attribute: "morph delta", expected: n * 3, got: deltas.data.len() });
+    }
+    let perm = seeded_permutation(mesh)?;
+    let mut out = Vec::with_capacity(n * 6);
+    for old in perm {
+        for a in 0..3 {
+            push_i16(&mut out, deltas.data[old as usize * 3 + a], mesh.layout.endianness);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{multi_permutation, pack_interleave_permute, pack_interleave_permute_secure};
+    use meshguard_quantize::{encode_normals_oct, quantize_morph_deltas, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn morph_stream_follows_vertex_permutation() {
+        let base: Vec<[f32; 3]> = (0..10).map(|i| [i as f32, 0.0, 1.0]).collect();
+        let target: Vec<[f32; 3]> = base.iter().map(|p| [p[0], p[0] * 0.1, 1.0]).collect();
+        let qpos = quantize_positions(&base).unwrap();
+        let mesh = pack_interleave_permute(&qpos, &encode_normals_oct(&[[0.0,0.0,1.0]; 10]), &quantize_uvs(&[[0.0,0.0]; 10]), None, 5).unwrap();
+        let deltas = quantize_morph_deltas(&base, &target).unwrap();
+        let stream = pack_morph_deltas(&deltas, &mesh).unwrap();
+        assert_eq!(stream.len(), 60);
+
+        // 同じ位置の頂点と差分が対応している
+        let perm = multi_permutation(10, &mesh.perm_seeds);
+        for (new, &old) in perm.iter().enumerate() {
+            let dy = i16::from_le_bytes([stream[new * 6 + 2], stream[new * 6 + 3]]);
+            assert_eq!(dy, deltas.data[old as usize * 3 + 1]);
+            assert!((mesh.vertex_at(new).unwrap().position[0] - old as f32).abs() < 1e-3);
+        }
+        assert!(pack_morph_deltas(&quantize_morph_deltas(&base[1..], &target[1..]).unwrap(), &mesh).is_err());
+
+        // 鍵付き置換はseedsから作り直せないので並べ替えない
+        let secure = pack_interleave_permute_secure(&qpos, &encode_normals_oct(&[[0.0,0.0,1.0]; 10]), &quantize_uvs(&[[0.0,0.0]; 10]), None, &[1; 32], 5).unwrap();
+        assert!(matches!(pack_morph_deltas(&deltas, &secure), Err(MeshGuardError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,39 @@
+//! 16進ダンプでそのまま読めなくする程度の可逆スクランブル (暗号ではない)
+
+const LCG_MUL: u64 = 6364136223846793005;
+const LCG_INC: u64 = 1442695040888963407;
+
+/// バイト位置iの鍵ストリームは key から LCG を i+1 回進めた状態の上位8bit
+/// 割り当てなしでその場で書き換える
+pub fn obfuscate(buf: &mut [u8], key: u64) {
+    let mut state = key;
+    for b in buf.iter_mut() {
+        state = state.wrapping_mul(LCG_MUL).wrapping_add(LCG_INC);
+        *b ^= (state >> 56) as u8;
+    }
+}
+
+/// XORなので `obfuscate` と同じ操作
+pub fn deobfuscate(buf: &mut [u8], key: u64) {
+    obfuscate(buf, key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obfuscate_round_trip() {
+        let original: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut buf = original.clone();
+        obfuscate(&mut buf, 0xDEAD_BEEF);
+        assert_ne!(buf, original);
+        // 同じ値が並んでいても同じバイトにはならない
+        let mut zeros = [0u8; 16];
+        obfuscate(&mut zeros, 1);
+        assert!(zeros.windows(2).any(|w| w[0] != w[1]));
+
+        deobfuscate(&mut buf, 0xDEAD_BEEF);
+        assert_eq!(buf, original);
+    }
+}
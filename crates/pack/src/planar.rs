@@ -0,0 +1,78 @@
+use crate::{pack_interleave_permute, AttributeKind, IndexBuffer, MeshGuardError, PackedMesh};
+use meshguard_quantize::{QuantizedNormalsOct, QuantizedPositions, QuantizedUVs};
+
+/// 属性ごとに別バッファにしたもの (struct-of-arrays)
+/// 各バッファ内の頂点順はinterleaved版と同じ置換を受ける
+#[derive(Clone, Debug)]
+pub struct PlanarMesh {
+    /// i16 x3 (6バイト/頂点)
+    pub positions: Vec<u8>,
+    /// oct u16 x2 (4バイト/頂点)
+    pub normals: Vec<u8>,
+    /// u16 x2 (4バイト/頂点)
+    pub uvs: Vec<u8>,
+    pub vertex_count: usize,
+    pub indices: IndexBuffer,
+    pub pos_scale: [f32; 3],
+    pub pos_offset: [f32; 3],
+    pub pos_bits: [u8; 3],
+    pub perm_seeds: Vec<u64>,
+}
+
+/// PNUVでpackしてから属性ごとに切り出す
+pub fn pack_planar(
+    qpos: &QuantizedPositions,
+    qnor: &QuantizedNormalsOct,
+    quv:  &QuantizedUVs,
+    indices: Option<&[u32]>,
+    perm_seed: u64,
+) -> Result<PlanarMesh, MeshGuardError> {
+    let packed = pack_interleave_permute(qpos, qnor, quv, indices, perm_seed)?;
+    Ok(PlanarMesh {
+        positions: split(&packed, &[AttributeKind::Position]),
+        normals: split(&packed, &[AttributeKind::Normal]),
+        uvs: split(&packed, &[AttributeKind::Uv]),
+        vertex_count: packed.vertex_count,
+        indices: packed.indices,
+        pos_scale: packed.pos_scale,
+        pos_offset: packed.pos_offset,
+        pos_bits: packed.pos_bits,
+        perm_seeds: packed.perm_seeds,
+    })
+}
+
+/// 頂点ごとに kinds の属性をこの順で並べたバッファを切り出す
+pub(crate) fn split(packed: &PackedMesh, kinds: &[AttributeKind]) -> Vec<u8> {
+    let attrs: Vec<_> = kinds.iter().map(|&k| packed.layout.attribute(k).expect("default layout has PNUV")).collect();
+    let size: usize = attrs.iter().map(|a| a.format.size()).sum();
+    let mut out = Vec::with_capacity(packed.vertex_count * size);
+    for v in packed.interleaved.chunks_exact(packed.layout.stride) {
+        for attr in &attrs {
+            out.extend_from_slice(&v[attr.offset..attr.offset + attr.format.size()]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn planar_matches_interleaved() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
+        let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+        let planar = pack_planar(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 11).unwrap();
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 11).unwrap();
+        assert_eq!((planar.positions.len(), planar.normals.len(), planar.uvs.len()), (18, 12, 12));
+        assert_eq!(planar.indices, packed.indices);
+        for (k, v) in packed.interleaved.chunks_exact(14).enumerate() {
+            assert_eq!(&v[0..6], &planar.positions[k * 6..k * 6 + 6]);
+            assert_eq!(&v[6..10], &planar.normals[k * 4..k * 4 + 4]);
+            assert_eq!(&v[10..14], &planar.uvs[k * 4..k * 4 + 4]);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::PackedMesh;
+use std::fmt;
+
+/// f32のPNUV (32バイト/頂点) と量子化後のinterleavedの大きさの比較
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompressionStats {
+    pub float_bytes: usize,
+    pub quantized_bytes: usize,
+    /// float_bytes / quantized_bytes (大きいほど小さくなった)
+    pub ratio: f32,
+}
+
+impl fmt::Display for CompressionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let saved = 100.0 * (1.0 - self.quantized_bytes as f64 / self.float_bytes.max(1) as f64);
+        write!(f, "{} bytes (f32) -> {} bytes (quantized), {:.2}x smaller, {:.1}% saved", self.float_bytes, self.quantized_bytes, self.ratio, saved)
+    }
+}
+
+/// インデックスは量子化前後で変わらないとみなして頂点データだけを比べる
+pub fn compression_stats(vertex_count: usize, q: &PackedMesh) -> CompressionStats {
+    let float_bytes = vertex_count * (12 + 12 + 8);
+    let quantized_bytes = q.interleaved.len();
+    let ratio = if quantized_bytes == 0 { 0.0 } else { float_bytes as f32 / quantized_bytes as f32 };
+    CompressionStats { float_bytes, quantized_bytes, ratio }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack_interleave_permute;
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn pnuv_is_32_to_14() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0],[4.0,4.0,4.0]];
+        let mesh = pack_interleave_permute(&quantize_positions(&pos).unwrap(), &encode_normals_oct(&[[0.0,0.0,1.0]; 4]), &quantize_uvs(&[[0.5,0.5]; 4]), None, 1).unwrap();
+        let s = compression_stats(mesh.vertex_count, &mesh);
+        assert_eq!((s.float_bytes, s.quantized_bytes), (128, 56));
+        assert!((s.ratio - 32.0 / 14.0).abs() < 1e-6);
+        assert_eq!(s.to_string(), "128 bytes (f32) -> 56 bytes (quantized), 2.29x smaller, 56.2% saved");
+    }
+}
@@ -0,0 +1,108 @@
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use chacha20::ChaCha20Legacy;
+
+/// 頂点置換に使う乱数源
+/// randクレートのRNGなどをラップして差し込める
+pub trait MeshRng {
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<R: MeshRng + ?Sized> MeshRng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// 既定のxorshift64*
+#[derive(Clone, Debug)]
+pub struct XorShift64Rng {
+    state: u64,
+}
+
+impl XorShift64Rng {
+    /// seedが0だと状態が0から動かないので固定値に差し替える
+    pub fn new(seed: u64) -> Self {
+        Self { state: if seed == 0 { 0x9E3779B97F4A7C15 } else { seed } }
+    }
+}
+
+impl MeshRng for XorShift64Rng {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        *s ^= *s >> 12;
+        *s ^= *s << 25;
+        *s ^= *s >> 27;
+        s.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+}
+
+/// ChaCha20 (64bitノンス版) の鍵ストリームを乱数として使う
+/// 鍵を知らなければ置換を再現できない
+pub struct ChaChaRng {
+    cipher: ChaCha20Legacy,
+    block: [u8; 64],
+    pos: usize,
+}
+
+impl ChaChaRng {
+    pub fn new(key: &[u8; 32], nonce: u64) -> Self {
+        let cipher = ChaCha20Legacy::new(key.into(), &nonce.to_le_bytes().into());
+        Self { cipher, block: [0; 64], pos: 64 }
+    }
+}
+
+impl MeshRng for ChaChaRng {
+    fn next_u64(&mut self) -> u64 {
+        if self.pos == 64 {
+            self.block = [0; 64];
+            self.cipher.apply_keystream(&mut self.block);
+            self.pos = 0;
+        }
+        let v = u64::from_le_bytes(self.block[self.pos..self.pos + 8].try_into().unwrap());
+        self.pos += 8;
+        v
+    }
+}
+
+/// 座標・法線・UVのf32 (LE) を順に連結したBLAKE3ハッシュを64bitに畳んだシード
+/// メッシュごとに違う置換になり、シードを別に保存しなくても作り直せる
+pub fn content_hash_seed(positions: &[[f32; 3]], normals: &[[f32; 3]], uvs: &[[f32; 2]]) -> u64 {
+    let mut hasher = blake3::Hasher::new();
+    for c in positions.iter().flatten().chain(normals.iter().flatten()).chain(uvs.iter().flatten()) {
+        hasher.update(&c.to_le_bytes());
+    }
+    hasher.finalize().as_bytes().chunks_exact(8).fold(0, |acc, w| acc ^ u64::from_le_bytes(w.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chacha_is_keyed() {
+        let key = [7u8; 32];
+        let a: Vec<u64> = { let mut r = ChaChaRng::new(&key, 1); (0..20).map(|_| r.next_u64()).collect() };
+        let b: Vec<u64> = { let mut r = ChaChaRng::new(&key, 1); (0..20).map(|_| r.next_u64()).collect() };
+        let c: Vec<u64> = { let mut r = ChaChaRng::new(&key, 2); (0..20).map(|_| r.next_u64()).collect() };
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn content_seed_depends_on_data() {
+        let pos = [[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]];
+        let nor = [[0.0, 0.0, 1.0]; 2];
+        let uv = [[0.0, 0.0], [1.0, 1.0]];
+        let seed = content_hash_seed(&pos, &nor, &uv);
+        assert_eq!(seed, content_hash_seed(&pos, &nor, &uv));
+        assert_ne!(seed, content_hash_seed(&pos, &nor, &[[0.0, 0.0], [1.0, 0.5]]));
+
+        let mut expected = blake3::Hasher::new();
+        for c in [0.0f32, 1.0, 2.0, 3.0, 4.0, 5.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0] {
+            expected.update(&c.to_le_bytes());
+        }
+        let h = expected.finalize();
+        let folded = h.as_bytes().chunks(8).map(|w| u64::from_le_bytes(w.try_into().unwrap())).fold(0, |a, b| a ^ b);
+        assert_eq!(seed, folded);
+    }
+}
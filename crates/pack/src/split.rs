@@ -0,0 +1,109 @@
+use crate::{multi_permutation, IndexBuffer, MeshGuardError, PackedMesh, PrimitiveTopology, SubmeshRange, STRIP_RESTART_INDEX};
+
+/// `submeshes` の範囲ごとに、参照している頂点だけを持つPackedMeshに分ける (描画呼び出しを分ける用)
+/// 頂点は元の並び順を保って詰めたあと `perm_seeds` の置換をかけ直すので、`unpack_mesh` はその詰めた順に戻す
+/// 量子化グリッドは元のまま (pos_scale / pos_offset / pos_bits を引き継ぐ) で、復号した座標は分割前と一致する
+/// 範囲がインデックスバッファを越えるとInvalidInput。署名は付け直しが必要なので `integrity_tag` はNone
+/// seedsが空のメッシュ (任意RNG・鍵付き置換・`merge_packed_meshes` の結果) は置換をかけ直せないのでInvalidInput
+pub fn split_by_material(mesh: &PackedMesh) -> Result<Vec<PackedMesh>, MeshGuardError> {
+    if mesh.perm_seeds.is_empty() {
+        return Err(MeshGuardError::InvalidInput("mesh has no permutation seeds; its permutation cannot be re-applied to submeshes".into()));
+    }
+    let stride = mesh.layout.stride;
+    if mesh.interleaved.len() != mesh.vertex_count * stride {
+        return Err(MeshGuardError::LengthMismatch { attribute: "interleaved", expected: mesh.vertex_count * stride, got: mesh.interleaved.len() });
+    }
+    let all = mesh.indices_u32();
+    let strip = mesh.topology == PrimitiveTopology::TriangleStrip;
+    mesh.submeshes.iter().map(|s| {
+        let range = all.get(s.index_offset..s.index_offset.saturating_add(s.index_count)).ok_or_else(|| MeshGuardError::InvalidInput(format!(
+            "submesh {}..{} (material {}) exceeds {} indices", s.index_offset, s.index_offset.saturating_add(s.index_count), s.material_id, all.len())))?;
+
+        // 元の番号 -> 詰めた番号
+        let mut remap = vec![u32::MAX; mesh.vertex_count];
+        for (k, &i) in range.iter().enumerate() {
+            if strip && i == STRIP_RESTART_INDEX {
+                continue;
+            }
+            let slot = remap.get_mut(i as usize)
+                .ok_or(MeshGuardError::IndexOutOfBounds { index: i, position: Some(s.index_offset + k), vertex_count: mesh.vertex_count })?;
+            *slot = 0;
+        }
+        let used: Vec<usize> = (0..mesh.vertex_count).filter(|&v| remap[v] == 0).collect();
+        let n = used.len();
+        let perm = multi_permutation(n, &mesh.perm_seeds);
+        let mut interleaved = Vec::with_capacity(n * stride);
+        for (new, &k) in perm.iter().enumerate() {
+            let old = used[k as usize];
+            remap[old] = new as u32;
+            interleaved.extend_from_slice(&mesh.interleaved[old * stride..(old + 1) * stride]);
+        }
+        let indices = range.iter().map(|&i| if strip && i == STRIP_RESTART_INDEX { i } else { remap[i as usize] }).collect();
+
+        Ok(PackedMesh {
+            interleaved,
+            vertex_count: n,
+            indices: IndexBuffer::from_u32(indices, n),
+            pos_scale: mesh.pos_scale,
+            pos_offset: mesh.pos_offset,
+            pos_bits: mesh.pos_bits,
+            perm_seeds: mesh.perm_seeds.clone(),
+            layout: mesh.layout.clone(),
+            integrity_tag: None,
+            submeshes: vec![SubmeshRange { index_offset: 0, index_count: s.index_count, material_id: s.material_id }],
+            topology: mesh.topology,
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pack_interleave_permute, pack_interleave_permute_secure, unpack_mesh};
+    use meshguard_quantize::{dequantize_positions, encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn split_keeps_only_referenced_vertices() {
+        let pos: Vec<[f32; 3]> = (0..8).map(|i| [i as f32, (i * i) as f32 * 0.1, 1.0]).collect();
+        let uv: Vec<[f32; 2]> = (0..8).map(|i| [i as f32 / 8.0, 0.5]).collect();
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0, 0.0, 1.0]; 8]), quantize_uvs(&uv));
+        let idx = [0, 1, 2, 2, 1, 3, 4, 5, 6, 6, 5, 7];
+        let mut mesh = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 99).unwrap();
+        mesh.submeshes = vec![
+            SubmeshRange { index_offset: 0, index_count: 6, material_id: 3 },
+            SubmeshRange { index_offset: 6, index_count: 6, material_id: 8 },
+        ];
+
+        let parts = split_by_material(&mesh).unwrap();
+        assert_eq!(parts.len(), 2);
+        let whole: Vec<_> = mesh.iter_vertices().collect();
+        let packed = mesh.indices_u32();
+        for (p, s) in parts.iter().zip(&mesh.submeshes) {
+            assert_eq!(p.vertex_count, 4);
+            assert_eq!(p.submeshes, vec![SubmeshRange { index_offset: 0, index_count: 6, material_id: s.material_id }]);
+            assert_eq!((p.pos_scale, p.pos_offset), (mesh.pos_scale, mesh.pos_offset));
+            // 同じ三角形が同じ座標で描ける
+            let verts: Vec<_> = p.iter_vertices().collect();
+            for (k, i) in p.indices_u32().into_iter().enumerate() {
+                assert!(i < 4);
+                assert_eq!(verts[i as usize], whole[packed[s.index_offset + k] as usize]);
+            }
+            // 置換をかけ直しているので、seedsから詰めた順に戻せる
+            let mut used = packed[s.index_offset..s.index_offset + 6].to_vec();
+            used.sort_unstable();
+            used.dedup();
+            let u = unpack_mesh(p).unwrap();
+            let restored = dequantize_positions(&u.positions);
+            assert!(restored.len() == used.len() && used.iter().zip(&restored).all(|(&v, r)| *r == whole[v as usize].position));
+        }
+
+        let mut secure = pack_interleave_permute_secure(&qpos, &qnor, &quv, Some(&idx), &[3; 32], 99).unwrap();
+        secure.submeshes = mesh.submeshes.clone();
+        assert!(matches!(split_by_material(&secure), Err(MeshGuardError::InvalidInput(_))));
+
+        mesh.submeshes.push(SubmeshRange { index_offset: 10, index_count: 6, material_id: 0 });
+        assert!(split_by_material(&mesh).is_err());
+        mesh.submeshes.clear();
+        assert!(split_by_material(&mesh).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{inverse_permutation, multi_permutation, read_u16, AttributeKind, MeshGuardError, PackedMesh, PrimitiveTopology, VertexFormat, STRIP_RESTART_INDEX};
+use meshguard_quantize::{PositionData, QuantizedNormalsOct, QuantizedPositions, QuantizedUVs};
+
+/// pack前の量子化済み属性とインデックス
+/// レイアウトにない法線/UVはNone。接線やカラーなど他の属性は戻さない
+#[derive(Clone, Debug)]
+pub struct UnpackedMesh {
+    pub positions: QuantizedPositions,
+    pub normals: Option<QuantizedNormalsOct>,
+    pub uvs: Option<QuantizedUVs>,
+    pub indices: Vec<u32>,
+}
+
+/// `perm_seeds` から置換を作り直して頂点順とインデックスを元に戻す
+/// 任意RNGや `pack_interleave_permute_secure` で置換したメッシュ (seedsが空) はInvalidInputになるので、
+/// 置換を自分で作って `unpack_mesh_with_permutation` に渡すこと
+pub fn unpack_mesh(mesh: &PackedMesh) -> Result<UnpackedMesh, MeshGuardError> {
+    unpack_mesh_with_permutation(mesh, &seeded_permutation(mesh)?)
+}
+
+/// `perm_seeds` から作り直したpack時の置換。seedsが空 (置換をシードから作れない) ならInvalidInput
+pub(crate) fn seeded_permutation(mesh: &PackedMesh) -> Result<Vec<u32>, MeshGuardError> {
+    if mesh.perm_seeds.is_empty() {
+        return Err(MeshGuardError::InvalidInput("mesh has no permutation seeds; its permutation cannot be rebuilt".into()));
+    }
+    Ok(multi_permutation(mesh.vertex_count, &mesh.perm_seeds))
+}
+
+/// perm は pack時の置換 (新しい番号 -> 元の番号)
+pub fn unpack_mesh_with_permutation(mesh: &PackedMesh, perm: &[u32]) -> Result<UnpackedMesh, MeshGuardError> {
+    let n = mesh.vertex_count;
+    if perm.len() != n {
+        return Err(MeshGuardError::LengthMismatch { attribute: "permutation", expected: n, got: perm.len() });
+    }
+    if let Some(position) = perm.iter().position(|&old| old as usize >= n) {
+        return Err(MeshGuardError::IndexOutOfBounds { index: perm[position], position: Some(position), vertex_count: n });
+    }
+    if inverse_permutation(perm).iter().enumerate().any(|(old, &new)| perm[new as usize] as usize != old) {
+        return Err(MeshGuardError::InvalidInput("permutation maps two vertices to the same index".into()));
+    }
+    let stride = mesh.layout.stride;
+    if mesh.interleaved.len() != n * stride {
+        return Err(MeshGuardError::LengthMismatch { attribute: "interleaved", expected: n * stride, got: mesh.interleaved.len() });
+    }
+
+    let e = mesh.layout.endianness;
+    let mut pos = vec![0i16; n * 3];
+    let mut normals = None;
+    let mut uvs = None;
+    for attr in &mesh.layout.attributes {
+        let mut target = match (attr.kind, attr.format) {
+            (AttributeKind::Position, VertexFormat::Sint16x3) => None,
+            (AttributeKind::Position, format) | (AttributeKind::Normal, format @ VertexFormat::Snorm10_10_10_2) => {
+                return Err(MeshGuardError::InvalidInput(format!("{:?} stored as {:?} cannot be unpacked", attr.kind, format)));
+            }
+            (AttributeKind::Normal, _) => Some(normals.insert(vec![0u16; n * 2])),
+            (AttributeKind::Uv, _) => Some(uvs.insert(vec![0u16; n * 2])),
+            _ => continue,
+        };
+        for (new, v) in mesh.interleaved.chunks_exact(stride).enumerate() {
+            let old = perm[new] as usize;
+            let b = &v[attr.offset..];
+            match target {
+                None => (0..3).for_each(|a| pos[old * 3 + a] = read_u16(&b[a * 2..], e) as i16),
+                Some(ref mut out) => (0..2).for_each(|c| out[old * 2 + c] = read_u16(&b[c * 2..], e)),
+            }
+        }
+    }
+
+    let strip = mesh.topology == PrimitiveTopology::TriangleStrip;
+    let indices = mesh.indices_u32().into_iter().enumerate()
+        .map(|(position, i)| if strip && i == STRIP_RESTART_INDEX {
+            Ok(i)
+        } else {
+            perm.get(i as usize).copied().ok_or(MeshGuardError::IndexOutOfBounds { index: i, position: Some(position), vertex_count: n })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(UnpackedMesh {
+        positions: QuantizedPositions { data: PositionData::I16(pos), bits: mesh.pos_bits, scale: mesh.pos_scale, offset: mesh.pos_offset },
+        normals: normals.map(|data| QuantizedNormalsOct { data }),
+        uvs: uvs.map(|data| QuantizedUVs { data }),
+        indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pack_interleave_permute, pack_interleave_permute_secure, pack_multi_permute, pack_positions_only, vertex_permutation, ChaChaRng, IndexBuffer};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn pack_unpack_pack_roundtrip() {
+        let pos: Vec<[f32; 3]> = (0..40).map(|i| { let t = i as f32; [t.sin() * 3.0, t * 0.25, (t * 0.7).cos()] }).collect();
+        let nor: Vec<[f32; 3]> = (0..40).map(|i| { let t = i as f32 * 0.3; [t.cos(), t.sin(), 0.0] }).collect();
+        let uv: Vec<[f32; 2]> = (0..40).map(|i| [i as f32 / 40.0, 1.0 - i as f32 / 80.0]).collect();
+        let idx: Vec<u32> = (0..38).flat_map(|i| [i, i + 1, i + 2]).collect();
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&nor), quantize_uvs(&uv));
+
+        let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 42).unwrap();
+        let u = unpack_mesh(&packed).unwrap();
+        assert_eq!(u.indices, idx);
+        assert!((0..120).all(|i| u.positions.data.get(i) == qpos.data.get(i)));
+        assert_eq!(u.normals.as_ref().unwrap().data, qnor.data);
+        assert_eq!(u.uvs.as_ref().unwrap().data, quv.data);
+        let again = pack_interleave_permute(&u.positions, u.normals.as_ref().unwrap(), u.uvs.as_ref().unwrap(), Some(&u.indices), 42).unwrap();
+        assert_eq!(again.interleaved, packed.interleaved);
+        assert_eq!(again.indices_u32(), packed.indices_u32());
+
+        let multi = pack_multi_permute(&qpos, &qnor, &quv, Some(&idx), &[3, 5, 8]).unwrap();
+        assert_eq!(unpack_mesh(&multi).unwrap().uvs.unwrap().data, quv.data);
+
+        let only = unpack_mesh(&pack_positions_only(&qpos, None, 9).unwrap()).unwrap();
+        assert!(only.normals.is_none() && only.uvs.is_none());
+        assert!((0..120).all(|i| only.positions.data.get(i) == qpos.data.get(i)));
+
+        // ChaChaの置換は鍵がないと作れない
+        let key = [7u8; 32];
+        let secure = pack_interleave_permute_secure(&qpos, &qnor, &quv, Some(&idx), &key, 11).unwrap();
+        let u = unpack_mesh_with_permutation(&secure, &vertex_permutation(40, ChaChaRng::new(&key, 11))).unwrap();
+        assert_eq!(u.indices, idx);
+        assert!(matches!(unpack_mesh(&secure), Err(MeshGuardError::InvalidInput(_))));
+        assert!(unpack_mesh_with_permutation(&secure, &[0; 40]).is_err());
+
+        // 壊れたインデックスはpanicせずエラー
+        let mut broken = packed.clone();
+        broken.indices = IndexBuffer::U16(vec![0, 1, 40]);
+        assert_eq!(unpack_mesh(&broken).unwrap_err(), MeshGuardError::IndexOutOfBounds { index: 40, position: Some(2), vertex_count: 40 });
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{validate_indices_with_topology, AttributeKind, MeshGuardError, PackedMesh, VertexFormat};
+
+/// packが書く属性と型の組み合わせ
+fn format_allowed(kind: AttributeKind, format: VertexFormat) -> bool {
+    use VertexFormat::*;
+    match kind {
+        AttributeKind::Position => matches!(format, Sint16x3 | Float16x3),
+        AttributeKind::Normal => matches!(format, Unorm16x2 | Snorm10_10_10_2),
+        AttributeKind::Uv | AttributeKind::Uv2 => format == Unorm16x2,
+        AttributeKind::Tangent => format == Snorm16x4,
+        AttributeKind::Color | AttributeKind::JointWeights => format == Unorm8x4,
+        AttributeKind::JointIndices => matches!(format, Uint8x4 | Uint16x4),
+        AttributeKind::AmbientOcclusion => format == Unorm8,
+    }
+}
+
+impl PackedMesh {
+    /// 外から受け取ったメッシュ (ファイル・ネットワーク由来) を復号する前の整合性チェック
+    /// ビット深度、stride と属性表、interleavedの長さ、インデックス、サブメッシュ範囲を見る
+    /// これを通ったメッシュは `iter_vertices` / `unpack_mesh` などでpanicしない
+    pub fn validate(&self) -> Result<(), MeshGuardError> {
+        let invalid = |msg: String| Err(MeshGuardError::InvalidInput(msg));
+        if let Some(bits) = self.pos_bits.into_iter().find(|b| !(1..=16).contains(b)) {
+            return invalid(format!("position bit depth {} is outside 1..=16", bits));
+        }
+        let layout = &self.layout;
+        let stride = layout.stride;
+        if stride == 0 {
+            return invalid("stride is 0".into());
+        }
+        for (i, a) in layout.attributes.iter().enumerate() {
+            if layout.attributes[..i].iter().any(|b| b.kind == a.kind) {
+                return invalid(format!("{:?} appears twice in the layout", a.kind));
+            }
+            if !format_allowed(a.kind, a.format) {
+                return invalid(format!("{:?} cannot be stored as {:?}", a.kind, a.format));
+            }
+            if a.offset.checked_add(a.format.size()).is_none_or(|end| end > stride) {
+                return invalid(format!("{:?} at offset {} overruns stride {}", a.kind, a.offset, stride));
+            }
+        }
+        // 属性の後ろに置けるのはアラインメントの詰め物だけ
+        let end = layout.attributes.iter().map(|a| a.offset + a.format.size()).max().unwrap_or(0);
+        let max_align = layout.attributes.iter().map(|a| a.format.alignment()).max().unwrap_or(1);
+        if stride > end.next_multiple_of(max_align) {
+            return invalid(format!("stride {} does not match the attribute table ({} bytes)", stride, end));
+        }
+        if layout.attribute(AttributeKind::Position).is_none() {
+            return invalid("layout has no Position".into());
+        }
+        let expected = self.vertex_count.checked_mul(stride).ok_or_else(|| MeshGuardError::InvalidInput("vertex buffer size overflows".into()))?;
+        if self.interleaved.len() != expected {
+            return Err(MeshGuardError::LengthMismatch { attribute: "interleaved", expected, got: self.interleaved.len() });
+        }
+        validate_indices_with_topology(&self.indices_u32(), self.vertex_count, self.topology)?;
+        for s in &self.submeshes {
+            if s.index_offset.checked_add(s.index_count).is_none_or(|end| end > self.indices.len()) {
+                return invalid(format!("submesh {}..{} is outside {} indices", s.index_offset, s.index_offset.saturating_add(s.index_count), self.indices.len()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pack_interleave_permute, pack_with_config, IndexBuffer, MeshAttributes, MeshGuardError, PackConfig, SubmeshRange, VertexFormat};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn validate_rejects_broken_meshes() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let good = pack_interleave_permute(&qpos, &qnor, &quv, Some(&[0, 1, 2]), 42).unwrap();
+        assert!(good.validate().is_ok());
+        let aligned = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 1, &PackConfig::new().align_attributes(true)).unwrap();
+        assert!(aligned.validate().is_ok());
+
+        let msg = |m: &crate::PackedMesh| match m.validate() {
+            Err(MeshGuardError::InvalidInput(msg)) => msg,
+            r => panic!("expected InvalidInput, got {:?}", r),
+        };
+        let mut m = good.clone();
+        m.pos_bits[1] = 0;
+        assert_eq!(msg(&m), "position bit depth 0 is outside 1..=16");
+
+        let mut m = good.clone();
+        m.layout.stride = 0;
+        m.interleaved.clear();
+        assert_eq!(msg(&m), "stride is 0");
+
+        let mut m = good.clone();
+        m.layout.attributes[2].offset = 12;
+        assert_eq!(msg(&m), "Uv at offset 12 overruns stride 14");
+        m.layout.attributes[2].offset = usize::MAX;
+        assert!(msg(&m).contains("overruns"));
+
+        let mut m = good.clone();
+        m.layout.attributes[2].format = VertexFormat::Unorm8;
+        assert_eq!(msg(&m), "Uv cannot be stored as Unorm8");
+
+        let mut m = good.clone();
+        m.layout.attributes[1].kind = crate::AttributeKind::Uv;
+        assert!(msg(&m).contains("twice"));
+
+        let mut m = good.clone();
+        m.layout.attributes.remove(0);
+        assert_eq!(msg(&m), "layout has no Position");
+
+        let mut m = good.clone();
+        m.interleaved.pop();
+        assert_eq!(m.validate(), Err(MeshGuardError::LengthMismatch { attribute: "interleaved", expected: 42, got: 41 }));
+
+        let mut m = good.clone();
+        m.indices = IndexBuffer::U16(vec![0, 1, 3]);
+        assert_eq!(m.validate(), Err(MeshGuardError::IndexOutOfBounds { index: 3, position: Some(2), vertex_count: 3 }));
+
+        let mut m = good.clone();
+        m.submeshes = vec![SubmeshRange { index_offset: 2, index_count: usize::MAX, material_id: 0 }];
+        assert!(msg(&m).starts_with("submesh 2.."));
+    }
+}
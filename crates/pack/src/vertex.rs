@@ -0,0 +1,47 @@
+/// デフォルトPNUVレイアウトの1頂点 (14バイト、リトルエンディアン前提)
+/// `bytemuck` feature を有効にすると `bytemuck::cast_slice::<u8, VertexPNUV>(&mesh.interleaved)` で読める
+/// バッファが2バイト境界にないとcast_sliceはpanicするので、心配なら `try_cast_slice` を使う
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct VertexPNUV {
+    pub pos: [i16; 3],
+    /// oct法線
+    pub nor: [u16; 2],
+    pub uv: [u16; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PackConfig, VertexFormat};
+    use std::mem::{align_of, offset_of, size_of};
+
+    #[test]
+    fn matches_default_layout() {
+        let layout = PackConfig::new().layout();
+        assert_eq!((size_of::<VertexPNUV>(), align_of::<VertexPNUV>()), (layout.stride, 2));
+        let offsets = [offset_of!(VertexPNUV, pos), offset_of!(VertexPNUV, nor), offset_of!(VertexPNUV, uv)];
+        assert_eq!(offsets.to_vec(), layout.attributes.iter().map(|a| a.offset).collect::<Vec<_>>());
+        assert_eq!(layout.attributes[0].format, VertexFormat::Sint16x3);
+    }
+
+    #[cfg(all(feature = "bytemuck", target_endian = "little"))]
+    #[test]
+    fn cast_interleaved_buffer() {
+        use crate::pack_interleave_permute;
+        use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 3]), quantize_uvs(&[[0.25,0.5]; 3]));
+        let mesh = pack_interleave_permute(&qpos, &qnor, &quv, None, 0).unwrap();
+        let verts: &[VertexPNUV] = bytemuck::cast_slice(&mesh.interleaved);
+        assert_eq!(verts.len(), 3);
+        // 置換後の頂点から元の番号を座標で引く
+        for v in verts {
+            let old = (0..3).find(|&i| (0..3).all(|a| qpos.data.get(i * 3 + a) == v.pos[a])).unwrap();
+            assert_eq!(v.nor, [qnor.data[old * 2], qnor.data[old * 2 + 1]]);
+            assert_eq!(v.uv, [quv.data[old * 2], quv.data[old * 2 + 1]]);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{PackedMesh, VertexFormat};
+
+/// 使う分だけのVkFormat (値はVulkanの定義と同じ)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+pub enum VkFormat {
+    R8_UNORM = 9,
+    R8G8B8A8_UNORM = 37,
+    R8G8B8A8_UINT = 41,
+    A2B10G10R10_SNORM_PACK32 = 65,
+    R16G16_UNORM = 77,
+    R16G16B16_SINT = 89,
+    R16G16B16_SFLOAT = 90,
+    R16G16B16A16_SNORM = 92,
+    R16G16B16A16_UINT = 95,
+}
+
+impl VkFormat {
+    pub fn as_raw(self) -> i32 {
+        self as i32
+    }
+}
+
+impl From<VertexFormat> for VkFormat {
+    fn from(f: VertexFormat) -> Self {
+        match f {
+            VertexFormat::Sint16x3 => VkFormat::R16G16B16_SINT,
+            VertexFormat::Float16x3 => VkFormat::R16G16B16_SFLOAT,
+            VertexFormat::Unorm16x2 => VkFormat::R16G16_UNORM,
+            // Rがbit0-9なのでA2B10G10R10
+            VertexFormat::Snorm10_10_10_2 => VkFormat::A2B10G10R10_SNORM_PACK32,
+            VertexFormat::Snorm16x4 => VkFormat::R16G16B16A16_SNORM,
+            VertexFormat::Unorm8x4 => VkFormat::R8G8B8A8_UNORM,
+            VertexFormat::Uint8x4 => VkFormat::R8G8B8A8_UINT,
+            VertexFormat::Uint16x4 => VkFormat::R16G16B16A16_UINT,
+            VertexFormat::Unorm8 => VkFormat::R8_UNORM,
+        }
+    }
+}
+
+/// `VkVertexInputAttributeDescription` と同じ並びのフィールド
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VkVertexInputAttributeDescriptionLike {
+    pub location: u32,
+    pub binding: u32,
+    pub format: VkFormat,
+    pub offset: u32,
+}
+
+/// binding 0、locationはレイアウト順
+/// Vulkanの頂点入力はリトルエンディアン前提なのでビッグエンディアンでpackしたものは渡さないこと
+/// R16G16B16系は頂点バッファとしての対応が任意なので、使う前に vkGetPhysicalDeviceFormatProperties で確認する
+pub fn to_vulkan_vertex_input(mesh: &PackedMesh) -> Vec<VkVertexInputAttributeDescriptionLike> {
+    mesh.layout.attributes.iter().enumerate().map(|(location, a)| VkVertexInputAttributeDescriptionLike {
+        location: location as u32,
+        binding: 0,
+        format: a.format.into(),
+        offset: a.offset as u32,
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pack_with_config, MeshAttributes, PackConfig};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn vulkan_descriptions_follow_layout() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.25,0.5]; 2]));
+        let mesh = pack_with_config(&MeshAttributes::new(&qpos, &qnor, &quv), None, 1, &PackConfig::new()).unwrap();
+        let desc = to_vulkan_vertex_input(&mesh);
+        let got: Vec<_> = desc.iter().map(|d| (d.location, d.binding, d.format.as_raw(), d.offset)).collect();
+        assert_eq!(got, vec![(0, 0, 89, 0), (1, 0, 77, 6), (2, 0, 77, 10)]);
+    }
+}
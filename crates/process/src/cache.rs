@@ -0,0 +1,178 @@
+use meshguard_core::{validate_indices, MeshGuardError};
+use std::collections::VecDeque;
+
+/// Forsythのスコア計算で想定するLRUキャッシュサイズ
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRI_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+fn vertex_score(cache_pos: Option<usize>, remaining: u32) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache = match cache_pos {
+        None => 0.0,
+        // 直前の三角形の頂点は固定値 (同じ三角形を連続で選びすぎないように)
+        Some(p) if p < 3 => LAST_TRI_SCORE,
+        Some(p) => (1.0 - (p - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(CACHE_DECAY_POWER),
+    };
+    cache + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// Forsyth法で三角形の順序を並べ替える (頂点は動かさない)
+/// インデックスがvertex_countを超えたらIndexOutOfBounds
+pub fn optimize_vertex_cache(indices: &[u32], vertex_count: usize) -> Result<Vec<u32>, MeshGuardError> {
+    validate_indices(indices, vertex_count)?;
+    let tri_count = indices.len() / 3;
+    let mut remaining = vec![0u32; vertex_count];
+    for &i in &indices[..tri_count * 3] {
+        remaining[i as usize] += 1;
+    }
+    // 頂点 -> 隣接三角形 (CSR)
+    let mut start = vec![0usize; vertex_count + 1];
+    for v in 0..vertex_count {
+        start[v + 1] = start[v] + remaining[v] as usize;
+    }
+    let mut fill = start.clone();
+    let mut adjacency = vec![0u32; tri_count * 3];
+    for (t, tri) in indices.chunks_exact(3).enumerate() {
+        for &i in tri {
+            adjacency[fill[i as usize]] = t as u32;
+            fill[i as usize] += 1;
+        }
+    }
+    let mut live = remaining.clone();
+
+    let mut cache_pos: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut score: Vec<f32> = (0..vertex_count).map(|v| vertex_score(None, live[v])).collect();
+    let mut tri_score: Vec<f32> = indices.chunks_exact(3).map(|t| t.iter().map(|&i| score[i as usize]).sum()).collect();
+    let mut emitted = vec![false; tri_count];
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut out = Vec::with_capacity(tri_count * 3);
+    let mut best: Option<usize> = None;
+    let mut cursor = 0;
+
+    for _ in 0..tri_count {
+        let t = match best {
+            Some(t) => t,
+            None => {
+                // キャッシュ内に候補がなければ未出力の最高スコアを探す
+                while emitted[cursor] { cursor += 1; }
+                (cursor..tri_count).filter(|&t| !emitted[t]).max_by(|&a, &b| tri_score[a].total_cmp(&tri_score[b])).unwrap()
+            }
+        };
+        emitted[t] = true;
+        let tri = &indices[t * 3..t * 3 + 3];
+        out.extend_from_slice(tri);
+
+        for &i in tri {
+            let v = i as usize;
+            live[v] -= 1;
+            // 隣接リストから出力済み三角形を外す
+            let list = &mut adjacency[start[v]..start[v] + remaining[v] as usize];
+            if let Some(k) = list[..live[v] as usize + 1].iter().position(|&x| x as usize == t) {
+                list.swap(k, live[v] as usize);
+            }
+        }
+
+        // LRU更新: 今の三角形の頂点を先頭へ
+        let mut next: Vec<u32> = tri.to_vec();
+        next.extend(cache.iter().copied().filter(|v| !tri.contains(v)));
+        for &v in next.iter().skip(CACHE_SIZE) {
+            cache_pos[v as usize] = None;
+        }
+        for (p, &v) in next.iter().enumerate().take(CACHE_SIZE) {
+            cache_pos[v as usize] = Some(p);
+        }
+
+        for &v in &next {
+            let v = v as usize;
+            score[v] = vertex_score(cache_pos[v], live[v]);
+        }
+        best = None;
+        let mut best_score = f32::MIN;
+        for &v in &next {
+            let v = v as usize;
+            for &at in &adjacency[start[v]..start[v] + live[v] as usize] {
+                let at = at as usize;
+                let s: f32 = indices[at * 3..at * 3 + 3].iter().map(|&i| score[i as usize]).sum();
+                tri_score[at] = s;
+                if s > best_score {
+                    best_score = s;
+                    best = Some(at);
+                }
+            }
+        }
+        next.truncate(CACHE_SIZE);
+        cache = next;
+    }
+    Ok(out)
+}
+
+/// FIFOキャッシュを仮定した三角形あたりの平均キャッシュミス数 (ACMR)
+/// インデックスがvertex_countを超えたらIndexOutOfBounds
+pub fn acmr(indices: &[u32], vertex_count: usize, cache_size: usize) -> Result<f32, MeshGuardError> {
+    validate_indices(indices, vertex_count)?;
+    let tri_count = indices.len() / 3;
+    if tri_count == 0 {
+        return Ok(0.0);
+    }
+    let mut in_cache = vec![false; vertex_count];
+    let mut fifo = VecDeque::with_capacity(cache_size + 1);
+    let mut misses = 0usize;
+    for &i in &indices[..tri_count * 3] {
+        let v = i as usize;
+        if in_cache[v] {
+            continue;
+        }
+        misses += 1;
+        in_cache[v] = true;
+        fifo.push_back(v);
+        if fifo.len() > cache_size {
+            in_cache[fifo.pop_front().unwrap()] = false;
+        }
+    }
+    Ok(misses as f32 / tri_count as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forsyth_improves_shuffled_grid() {
+        // 32x32の格子を三角形単位でばらばらに並べる
+        let n = 33u32;
+        let mut tris = Vec::new();
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                tris.push([i, i + 1, i + n + 1]);
+                tris.push([i, i + n + 1, i + n]);
+            }
+        }
+        let mut s = 0x9E3779B97F4A7C15u64;
+        for k in (1..tris.len()).rev() {
+            s ^= s << 13; s ^= s >> 7; s ^= s << 17;
+            tris.swap(k, (s % (k as u64 + 1)) as usize);
+        }
+        let indices: Vec<u32> = tris.iter().flatten().copied().collect();
+        let vc = (n * n) as usize;
+
+        let opt = optimize_vertex_cache(&indices, vc).unwrap();
+        let before = acmr(&indices, vc, 16).unwrap();
+        let after = acmr(&opt, vc, 16).unwrap();
+        assert!(after < 1.0 && after < before * 0.5, "before {} after {}", before, after);
+
+        let mut a: Vec<[u32; 3]> = indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let mut b: Vec<[u32; 3]> = opt.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+
+        assert!(matches!(optimize_vertex_cache(&[0, 1, 2, 2, 1, 3], 3), Err(MeshGuardError::IndexOutOfBounds { index: 3, .. })));
+        assert!(acmr(&[0, 1, 5], 3, 16).is_err());
+    }
+}
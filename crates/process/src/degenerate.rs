@@ -0,0 +1,60 @@
+use crate::math::{cross, length, sub};
+use meshguard_core::{validate_indices, MeshGuardError};
+
+/// 座標, インデックス, 取り除いた三角形の数
+pub type CleanedMesh = (Vec<[f32; 3]>, Vec<u32>, usize);
+
+/// 面積が area_threshold 未満の三角形と、同じ頂点を2回使う三角形を取り除く
+/// 参照されなくなった頂点も詰める (残った頂点の順番は元のまま)
+/// インデックスが頂点数を超えたらIndexOutOfBounds
+pub fn remove_degenerate_triangles(positions: &[[f32; 3]], indices: &[u32], area_threshold: f32) -> Result<CleanedMesh, MeshGuardError> {
+    validate_indices(indices, positions.len())?;
+    let mut kept = Vec::with_capacity(indices.len());
+    let mut removed = 0;
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]];
+        let area = 0.5 * length(cross(sub(positions[b as usize], positions[a as usize]), sub(positions[c as usize], positions[a as usize])));
+        if a == b || b == c || a == c || area < area_threshold {
+            removed += 1;
+        } else {
+            kept.extend_from_slice(tri);
+        }
+    }
+
+    let mut used = vec![false; positions.len()];
+    for &i in &kept {
+        used[i as usize] = true;
+    }
+    let mut remap = vec![u32::MAX; positions.len()];
+    let mut out = Vec::new();
+    for (v, p) in positions.iter().enumerate() {
+        if used[v] {
+            remap[v] = out.len() as u32;
+            out.push(*p);
+        }
+    }
+    let kept = kept.into_iter().map(|i| remap[i as usize]).collect();
+    Ok((out, kept, removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_slivers_and_unused_vertices() {
+        let pos = [[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0],[2.0,0.0,0.0],[5.0,5.0,5.0],[1.0,1.0,0.0]];
+        // 通常, 一直線, 頂点の重複, 通常
+        let idx = [0, 1, 2, 0, 1, 3, 4, 4, 2, 1, 5, 2];
+        let (p, i, removed) = remove_degenerate_triangles(&pos, &idx, 1e-6).unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(p, vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0],[1.0,1.0,0.0]]);
+        assert_eq!(i, vec![0, 1, 2, 1, 3, 2]);
+
+        // 閾値を上げると面積0.5の三角形も消える
+        let (p, i, removed) = remove_degenerate_triangles(&pos, &idx, 1.0).unwrap();
+        assert_eq!((p.len(), i.len(), removed), (0, 0, 4));
+
+        assert_eq!(remove_degenerate_triangles(&pos, &[0, 1, 6], 1e-6), Err(MeshGuardError::IndexOutOfBounds { index: 6, position: Some(2), vertex_count: 6 }));
+    }
+}
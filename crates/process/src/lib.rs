@@ -0,0 +1,27 @@
+//! 量子化前のメッシュ加工 (溶接, 法線/接線生成, 最適化など)
+
+mod cache;
+mod degenerate;
+mod manifold;
+mod math;
+mod meshlet;
+mod normals;
+mod obb;
+mod seams;
+mod simplify;
+mod strip;
+mod tangents;
+mod weld;
+
+pub use meshguard_core::{MeshGuardError, STRIP_RESTART_INDEX};
+pub use cache::{acmr, optimize_vertex_cache};
+pub use degenerate::{remove_degenerate_triangles, CleanedMesh};
+pub use manifold::{find_non_manifold_edges, is_manifold};
+pub use meshlet::{build_meshlets, compute_meshlet_bounds, Meshlet, MeshletBounds, Meshlets};
+pub use normals::{compute_normals, compute_normals_with_hard_edges, HardEdgeMesh, DEGENERATE_EPSILON};
+pub use seams::{detect_uv_seams, detect_uv_seams_with_threshold, UV_SEAM_EPSILON};
+pub use obb::{compute_obb, Obb};
+pub use simplify::{simplify, SimplifiedMesh};
+pub use strip::{strip_to_triangles, triangles_to_strip, triangles_to_strip_restart};
+pub use tangents::compute_tangents;
+pub use weld::{weld_vertices, WeldedMesh};
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+/// 3枚以上の三角形が共有している辺 (小さい番号, 大きい番号) を昇順で返す
+pub fn find_non_manifold_edges(indices: &[u32]) -> Vec<(u32, u32)> {
+    let mut count: HashMap<(u32, u32), u32> = HashMap::new();
+    for tri in indices.chunks_exact(3) {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            *count.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    let mut edges: Vec<_> = count.into_iter().filter(|&(_, n)| n > 2).map(|(e, _)| e).collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// 非多様体辺がなければtrue (境界辺は許す)
+pub fn is_manifold(indices: &[u32]) -> bool {
+    find_non_manifold_edges(indices).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_fin_edge() {
+        let quad = [0, 1, 2, 2, 1, 3];
+        assert!(is_manifold(&quad));
+        // 辺1-2に3枚目の三角形を生やす
+        let fin = [0, 1, 2, 2, 1, 3, 1, 2, 4];
+        assert_eq!(find_non_manifold_edges(&fin), vec![(1, 2)]);
+        assert!(!is_manifold(&fin));
+    }
+}
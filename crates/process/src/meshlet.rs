@@ -0,0 +1,160 @@
+use crate::math::{add, cross, dot, length, normalize, scale, sub};
+use meshguard_core::{validate_indices, MeshGuardError};
+
+/// 頂点と三角形の範囲。実体は `Meshlets` の平坦配列を参照する
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Meshlet {
+    pub vertex_offset: u32,
+    pub vertex_count: u8,
+    /// `meshlet_triangles` 内の開始位置 (バイト単位, 1三角形3バイト)
+    pub triangle_offset: u32,
+    pub triangle_count: u8,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Meshlets {
+    pub meshlets: Vec<Meshlet>,
+    /// メッシュレットのローカル頂点 -> 元の頂点番号
+    pub meshlet_vertices: Vec<u32>,
+    /// ローカル頂点番号を3つずつ並べた三角形
+    pub meshlet_triangles: Vec<u8>,
+}
+
+impl Meshlets {
+    pub fn vertices(&self, m: &Meshlet) -> &[u32] {
+        &self.meshlet_vertices[m.vertex_offset as usize..m.vertex_offset as usize + m.vertex_count as usize]
+    }
+
+    pub fn triangles(&self, m: &Meshlet) -> &[u8] {
+        &self.meshlet_triangles[m.triangle_offset as usize..m.triangle_offset as usize + m.triangle_count as usize * 3]
+    }
+}
+
+/// 法線コーン付きの境界
+/// `dot(center - camera, axis) >= cutoff * |center - camera| + radius` なら裏向きとして捨ててよい
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshletBounds {
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub axis: [f32; 3],
+    /// 1.0ならコーンで判定できない (カリング不可)
+    pub cutoff: f32,
+}
+
+/// インデックス順に三角形を詰め、どちらかの上限を超えたら次のメッシュレットにする
+/// 上限はu8に収まるよう255で頭打ちにする。インデックスがvertex_countを超えたらIndexOutOfBounds
+pub fn build_meshlets(indices: &[u32], vertex_count: usize, max_vertices: usize, max_triangles: usize) -> Result<Meshlets, MeshGuardError> {
+    validate_indices(indices, vertex_count)?;
+    if max_vertices < 3 || max_triangles < 1 {
+        return Err(MeshGuardError::InvalidInput(format!("meshlet limits too small: {} vertices, {} triangles", max_vertices, max_triangles)));
+    }
+    let max_vertices = max_vertices.min(255);
+    let max_triangles = max_triangles.min(255);
+
+    const NONE: u8 = 0xFF;
+    let mut local = vec![NONE; vertex_count];
+    let mut out = Meshlets::default();
+    let mut cur = Meshlet { vertex_offset: 0, vertex_count: 0, triangle_offset: 0, triangle_count: 0 };
+
+    for tri in indices.chunks_exact(3) {
+        let new = tri.iter().enumerate()
+            .filter(|&(k, &v)| local[v as usize] == NONE && !tri[..k].contains(&v))
+            .count();
+        if cur.vertex_count as usize + new > max_vertices || cur.triangle_count as usize >= max_triangles {
+            flush(&mut out, &mut cur, &mut local);
+        }
+        for &v in tri {
+            if local[v as usize] == NONE {
+                local[v as usize] = cur.vertex_count;
+                out.meshlet_vertices.push(v);
+                cur.vertex_count += 1;
+            }
+            out.meshlet_triangles.push(local[v as usize]);
+        }
+        cur.triangle_count += 1;
+    }
+    if cur.triangle_count > 0 {
+        flush(&mut out, &mut cur, &mut local);
+    }
+    Ok(out)
+}
+
+fn flush(out: &mut Meshlets, cur: &mut Meshlet, local: &mut [u8]) {
+    for &v in &out.meshlet_vertices[cur.vertex_offset as usize..] {
+        local[v as usize] = 0xFF;
+    }
+    out.meshlets.push(*cur);
+    *cur = Meshlet {
+        vertex_offset: out.meshlet_vertices.len() as u32,
+        vertex_count: 0,
+        triangle_offset: out.meshlet_triangles.len() as u32,
+        triangle_count: 0,
+    };
+}
+
+/// メッシュレットごとの境界球と法線コーン
+/// メッシュレットの頂点が座標の数を超えたらIndexOutOfBounds
+pub fn compute_meshlet_bounds(meshlets: &Meshlets, positions: &[[f32; 3]]) -> Result<Vec<MeshletBounds>, MeshGuardError> {
+    validate_indices(&meshlets.meshlet_vertices, positions.len())?;
+    Ok(meshlets.meshlets.iter().map(|m| {
+        let verts = meshlets.vertices(m);
+        let mut center = [0.0f32; 3];
+        for &v in verts {
+            center = add(center, positions[v as usize]);
+        }
+        center = scale(center, 1.0 / verts.len().max(1) as f32);
+        let radius = verts.iter().map(|&v| length(sub(positions[v as usize], center))).fold(0.0, f32::max);
+
+        let normals: Vec<[f32; 3]> = meshlets.triangles(m).chunks_exact(3).filter_map(|t| {
+            let [a, b, c] = [t[0], t[1], t[2]].map(|l| positions[verts[l as usize] as usize]);
+            normalize(cross(sub(b, a), sub(c, a)))
+        }).collect();
+        let axis = normalize(normals.iter().fold([0.0; 3], |s, &n| add(s, n))).unwrap_or([0.0, 0.0, 1.0]);
+        let min_dot = normals.iter().map(|&n| dot(n, axis)).fold(1.0, f32::min);
+        // コーンが半球を超えたら判定不能
+        let cutoff = if normals.is_empty() || min_dot <= 0.0 { 1.0 } else { (1.0 - min_dot * min_dot).sqrt() };
+        MeshletBounds { center, radius, axis, cutoff }
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meshlets_respect_limits() {
+        let n = 17u32;
+        let mut indices = Vec::new();
+        let mut positions = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                positions.push([x as f32, y as f32, 0.0]);
+            }
+        }
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                indices.extend_from_slice(&[i, i + 1, i + n + 1, i, i + n + 1, i + n]);
+            }
+        }
+        let ms = build_meshlets(&indices, positions.len(), 64, 124).unwrap();
+        assert!(build_meshlets(&indices, positions.len(), 2, 124).is_err());
+        assert_eq!(ms.meshlets.iter().map(|m| m.triangle_count as usize).sum::<usize>(), indices.len() / 3);
+
+        let mut rebuilt = Vec::new();
+        for m in &ms.meshlets {
+            assert!(m.vertex_count <= 64 && m.triangle_count <= 124);
+            let verts = ms.vertices(m);
+            rebuilt.extend(ms.triangles(m).iter().map(|&l| verts[l as usize]));
+        }
+        assert_eq!(rebuilt, indices);
+
+        // 平面なのでコーンは+Zで幅0
+        for b in compute_meshlet_bounds(&ms, &positions).unwrap() {
+            assert!((b.axis[2] - 1.0).abs() < 1e-6 && b.cutoff < 1e-3);
+        }
+
+        assert!(matches!(build_meshlets(&[0, 1, 289], positions.len(), 64, 124), Err(MeshGuardError::IndexOutOfBounds { index: 289, .. })));
+        assert!(matches!(compute_meshlet_bounds(&ms, &positions[..10]), Err(MeshGuardError::IndexOutOfBounds { .. })));
+    }
+}
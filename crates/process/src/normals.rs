@@ -0,0 +1,150 @@
+use crate::math::{add, cross, dot, length, normalize, sub};
+use meshguard_core::{validate_indices, MeshGuardError};
+use std::collections::HashMap;
+
+/// 座標, 法線, インデックス
+pub type HardEdgeMesh = (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<u32>);
+
+/// これより小さい外積の長さ (面積の2倍) の三角形は縮退とみなす
+pub const DEGENERATE_EPSILON: f32 = 1e-12;
+
+/// 隣接面の法線を面積で重み付け平均した頂点法線
+/// どの面にも属さない頂点は +Z。インデックスが頂点数を超えたらIndexOutOfBounds
+pub fn compute_normals(positions: &[[f32; 3]], indices: &[u32]) -> Result<Vec<[f32; 3]>, MeshGuardError> {
+    validate_indices(indices, positions.len())?;
+    let mut sum = vec![[0.0f32; 3]; positions.len()];
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+        let n = cross(sub(positions[b], positions[a]), sub(positions[c], positions[a]));
+        // 外積の長さは面積の2倍なので、そのまま足せば面積重みになる
+        if length(n) < DEGENERATE_EPSILON {
+            continue;
+        }
+        for v in [a, b, c] {
+            sum[v] = add(sum[v], n);
+        }
+    }
+    Ok(sum.into_iter().map(|n| normalize(n).unwrap_or([0.0, 0.0, 1.0])).collect())
+}
+
+/// 二面角が crease_angle_deg を超える辺で頂点を分けてから法線を付ける (DCCツールのスムージング角)
+/// 頂点まわりの面を、なめらかな辺でつながっているものごとにまとめ、まとまりごとに面積重み付き平均をとる
+/// 最初のまとまりは元の頂点番号のまま、2つ目以降は末尾に追加した頂点になる
+/// 縮退三角形は向きがないので隣とつながっているものとして扱う。180度以上なら `compute_normals` と同じ結果
+pub fn compute_normals_with_hard_edges(positions: &[[f32; 3]], indices: &[u32], crease_angle_deg: f32) -> Result<HardEdgeMesh, MeshGuardError> {
+    validate_indices(indices, positions.len())?;
+    let faces: Vec<[usize; 3]> = indices.chunks_exact(3).map(|t| [t[0] as usize, t[1] as usize, t[2] as usize]).collect();
+    let face_n: Vec<[f32; 3]> = faces.iter().map(|&[a, b, c]| cross(sub(positions[b], positions[a]), sub(positions[c], positions[a]))).collect();
+    let cos_crease = crease_angle_deg.to_radians().cos();
+    let smooth = |f: usize, g: usize| {
+        let (lf, lg) = (length(face_n[f]), length(face_n[g]));
+        crease_angle_deg >= 180.0 || lf < DEGENERATE_EPSILON || lg < DEGENERATE_EPSILON || dot(face_n[f], face_n[g]) >= cos_crease * lf * lg
+    };
+
+    // 角 (面, 何番目の頂点) = f * 3 + k をunion-findでまとめる
+    let mut parent: Vec<usize> = (0..faces.len() * 3).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (f, tri) in faces.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            edges.entry((a.min(b), a.max(b))).or_default().push(f);
+        }
+    }
+    let corner = |f: usize, v: usize| f * 3 + faces[f].iter().position(|&x| x == v).unwrap();
+    for (&(a, b), fs) in &edges {
+        for (i, &f) in fs.iter().enumerate() {
+            for &g in &fs[i + 1..] {
+                if f == g || !smooth(f, g) {
+                    continue;
+                }
+                for v in [a, b] {
+                    let (x, y) = (find(&mut parent, corner(f, v)), find(&mut parent, corner(g, v)));
+                    parent[x] = y;
+                }
+            }
+        }
+    }
+
+    let mut out_pos = positions.to_vec();
+    let mut sum = vec![[0.0f32; 3]; positions.len()];
+    let mut slot: HashMap<usize, u32> = HashMap::new();
+    let mut used = vec![false; positions.len()];
+    let mut out_idx = Vec::with_capacity(indices.len());
+    for (f, tri) in faces.iter().enumerate() {
+        for (k, &v) in tri.iter().enumerate() {
+            let root = find(&mut parent, f * 3 + k);
+            let i = *slot.entry(root).or_insert_with(|| {
+                if !used[v] {
+                    used[v] = true;
+                    v as u32
+                } else {
+                    out_pos.push(positions[v]);
+                    sum.push([0.0; 3]);
+                    (out_pos.len() - 1) as u32
+                }
+            });
+            if length(face_n[f]) >= DEGENERATE_EPSILON {
+                sum[i as usize] = add(sum[i as usize], face_n[f]);
+            }
+            out_idx.push(i);
+        }
+    }
+    let normals = sum.into_iter().map(|n| normalize(n).unwrap_or([0.0, 0.0, 1.0])).collect();
+    Ok((out_pos, normals, out_idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_quad_and_degenerate() {
+        // XY平面上の四角形 (反時計回り) + 縮退三角形
+        let pos = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 3.0, 0.0], [0.0, 3.0, 0.0], [5.0, 5.0, 5.0]];
+        let idx = [0, 1, 2, 0, 2, 3, 4, 4, 4];
+        let n = compute_normals(&pos, &idx).unwrap();
+        for v in &n[..4] {
+            assert!((v[0].abs() + v[1].abs() + (v[2] - 1.0).abs()) < 1e-6);
+        }
+        assert_eq!(n[4], [0.0, 0.0, 1.0]);
+
+        // 面積の大きい面が勝つ
+        let pos = [[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [0.0, 0.0, 1.0]];
+        let n = compute_normals(&pos, &[0, 1, 2, 0, 3, 1]).unwrap();
+        assert!(n[0][2] > 0.9);
+
+        assert_eq!(compute_normals(&pos, &[0, 1, 4]), Err(MeshGuardError::IndexOutOfBounds { index: 4, position: Some(2), vertex_count: 4 }));
+        assert!(compute_normals_with_hard_edges(&pos, &[0, 1, 4], 30.0).is_err());
+    }
+
+    #[test]
+    fn hard_edges_split_cube_corners() {
+        let pos: Vec<[f32; 3]> = (0..8).map(|i| [(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32]).collect();
+        // 外向き (反時計回り) の12三角形
+        let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+        let idx: Vec<u32> = quads.iter().flat_map(|q| [q[0], q[1], q[2], q[0], q[2], q[3]]).collect();
+
+        let (p, n, i) = compute_normals_with_hard_edges(&pos, &idx, 30.0).unwrap();
+        assert_eq!((p.len(), i.len()), (24, 36));
+        for tri in i.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|v| v as usize);
+            let face = normalize(cross(sub(p[b], p[a]), sub(p[c], p[a]))).unwrap();
+            for v in [a, b, c] {
+                assert!(dot(n[v], face) > 0.9999, "{:?} vs {:?}", n[v], face);
+            }
+        }
+        // 最初のまとまりは元の番号を使う
+        assert!((0..8).all(|v| i.contains(&v)));
+
+        let (p, n, i) = compute_normals_with_hard_edges(&pos, &idx, 180.0).unwrap();
+        assert_eq!((p.len(), &i), (8, &idx));
+        assert_eq!(n, compute_normals(&pos, &idx).unwrap());
+    }
+}
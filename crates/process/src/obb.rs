@@ -0,0 +1,139 @@
+use crate::math::{add, cross, dot, scale, sub};
+
+/// 有向境界箱。`axes` は正規直交 (右手系) で、各軸方向に `center ± half_extents[i]` の範囲
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: [f32; 3],
+    pub axes: [[f32; 3]; 3],
+    pub half_extents: [f32; 3],
+}
+
+impl Obb {
+    pub fn volume(&self) -> f32 {
+        8.0 * self.half_extents[0] * self.half_extents[1] * self.half_extents[2]
+    }
+
+    /// 各軸への射影が `half_extents + epsilon` に収まるか
+    pub fn contains(&self, p: [f32; 3], epsilon: f32) -> bool {
+        let d = sub(p, self.center);
+        (0..3).all(|i| dot(d, self.axes[i]).abs() <= self.half_extents[i] + epsilon)
+    }
+}
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// 頂点の共分散行列の固有ベクトル (PCA) を軸にしたOBB
+/// 主軸が取れない形ではAABBより大きくなることがあるので、その場合はAABBをそのまま返す
+/// 頂点がなければ原点に大きさ0の箱
+pub fn compute_obb(positions: &[[f32; 3]]) -> Obb {
+    if positions.is_empty() {
+        return Obb { center: [0.0; 3], axes: IDENTITY, half_extents: [0.0; 3] };
+    }
+    let n = positions.len() as f64;
+    let mut mean = [0.0f64; 3];
+    for p in positions {
+        for a in 0..3 {
+            mean[a] += p[a] as f64 / n;
+        }
+    }
+    let mut cov = [[0.0f64; 3]; 3];
+    for p in positions {
+        let d = [p[0] as f64 - mean[0], p[1] as f64 - mean[1], p[2] as f64 - mean[2]];
+        for r in 0..3 {
+            for c in 0..3 {
+                cov[r][c] += d[r] * d[c] / n;
+            }
+        }
+    }
+    let v = jacobi_eigenvectors(cov);
+    let a0 = [v[0][0] as f32, v[1][0] as f32, v[2][0] as f32];
+    let a1 = [v[0][1] as f32, v[1][1] as f32, v[2][1] as f32];
+    let pca = fit(positions, [a0, a1, cross(a0, a1)]);
+    let aabb = fit(positions, IDENTITY);
+    if pca.volume() < aabb.volume() { pca } else { aabb }
+}
+
+/// 軸を固定して頂点をちょうど囲む箱
+fn fit(positions: &[[f32; 3]], axes: [[f32; 3]; 3]) -> Obb {
+    let mut lo = [f32::INFINITY; 3];
+    let mut hi = [f32::NEG_INFINITY; 3];
+    for &p in positions {
+        for i in 0..3 {
+            let t = dot(p, axes[i]);
+            lo[i] = lo[i].min(t);
+            hi[i] = hi[i].max(t);
+        }
+    }
+    let mut center = [0.0; 3];
+    for i in 0..3 {
+        center = add(center, scale(axes[i], (lo[i] + hi[i]) * 0.5));
+    }
+    Obb { center, axes, half_extents: std::array::from_fn(|i| (hi[i] - lo[i]) * 0.5) }
+}
+
+/// 3x3対称行列のヤコビ法。固有ベクトルを列に並べて返す
+fn jacobi_eigenvectors(mut a: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let norm = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+    for _ in 0..32 {
+        let (p, q) = [(0, 1), (0, 2), (1, 2)].into_iter()
+            .max_by(|&(i, j), &(k, l)| a[i][j].abs().total_cmp(&a[k][l].abs()))
+            .unwrap();
+        if a[p][q].abs() <= 1e-15 * norm {
+            break;
+        }
+        let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+        let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+        let c = 1.0 / (t * t + 1.0).sqrt();
+        let s = t * c;
+        for row in a.iter_mut().chain(v.iter_mut()) {
+            let (kp, kq) = (row[p], row[q]);
+            row[p] = c * kp - s * kq;
+            row[q] = s * kp + c * kq;
+        }
+        let (rp, rq) = (a[p], a[q]);
+        for k in 0..3 {
+            a[p][k] = c * rp[k] - s * rq[k];
+            a[q][k] = s * rp[k] + c * rq[k];
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::length;
+
+    #[test]
+    fn obb_is_tight_for_rotated_box() {
+        // 20x2x0.5の板をZ軸まわりに30度, X軸まわりに20度回す
+        let (s1, c1) = 30f32.to_radians().sin_cos();
+        let (s2, c2) = 20f32.to_radians().sin_cos();
+        let mut pos = Vec::new();
+        for i in 0..=20 {
+            for j in 0..=4 {
+                for k in 0..=1 {
+                    let p = [i as f32 - 10.0, j as f32 * 0.5 - 1.0, k as f32 * 0.5 - 0.25];
+                    let p = [p[0] * c1 - p[1] * s1, p[0] * s1 + p[1] * c1, p[2]];
+                    pos.push([p[0] + 3.0, p[1] * c2 - p[2] * s2 - 1.0, p[1] * s2 + p[2] * c2]);
+                }
+            }
+        }
+        let obb = compute_obb(&pos);
+        for i in 0..3 {
+            assert!((length(obb.axes[i]) - 1.0).abs() < 1e-5);
+            assert!(dot(obb.axes[i], obb.axes[(i + 1) % 3]).abs() < 1e-5);
+        }
+        assert!(pos.iter().all(|&p| obb.contains(p, 1e-4)));
+        assert!((obb.volume() - 20.0 * 2.0 * 0.5).abs() < 1e-2, "{}", obb.volume());
+        assert!(obb.volume() < fit(&pos, IDENTITY).volume() * 0.5);
+
+        // 軸に沿った箱ならAABBと同じ
+        let aligned = [[0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [0.0, 2.0, 0.0], [4.0, 2.0, 1.0], [4.0, 0.0, 1.0], [0.0, 2.0, 1.0]];
+        let obb = compute_obb(&aligned);
+        assert!((obb.volume() - 8.0).abs() < 1e-4);
+        assert!(aligned.iter().all(|&p| obb.contains(p, 1e-5)));
+        assert_eq!(compute_obb(&[]).half_extents, [0.0; 3]);
+    }
+}
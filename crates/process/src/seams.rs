@@ -0,0 +1,52 @@
+use meshguard_core::MeshGuardError;
+
+/// これより離れたUVは不連続とみなす
+pub const UV_SEAM_EPSILON: f32 = 1e-5;
+
+/// UVの継ぎ目にある頂点 (昇順)
+/// uvsは三角形の角ごと (uvs[k] が indices[k] の角のUV) で、OBJのvtのように頂点と別に持つ場合の形
+/// 同じ頂点を使う角の間でUVが食い違う頂点を返す。ここは溶接せず、分割して別頂点にする必要がある
+pub fn detect_uv_seams(uvs: &[[f32; 2]], indices: &[u32]) -> Result<Vec<u32>, MeshGuardError> {
+    detect_uv_seams_with_threshold(uvs, indices, UV_SEAM_EPSILON)
+}
+
+/// 閾値を指定する版 (UV空間での距離)
+pub fn detect_uv_seams_with_threshold(uvs: &[[f32; 2]], indices: &[u32], threshold: f32) -> Result<Vec<u32>, MeshGuardError> {
+    if uvs.len() != indices.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "per-corner uv", expected: indices.len(), got: uvs.len() });
+    }
+    let vertex_count = indices.iter().map(|&i| i as usize + 1).max().unwrap_or(0);
+    let mut first: Vec<Option<[f32; 2]>> = vec![None; vertex_count];
+    let mut seam = vec![false; vertex_count];
+    for (&i, uv) in indices.iter().zip(uvs) {
+        let v = i as usize;
+        match first[v] {
+            None => first[v] = Some(*uv),
+            Some(f) => {
+                let (du, dv) = (uv[0] - f[0], uv[1] - f[1]);
+                if du * du + dv * dv > threshold * threshold {
+                    seam[v] = true;
+                }
+            }
+        }
+    }
+    Ok((0..vertex_count as u32).filter(|&v| seam[v as usize]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_vertices_on_seam() {
+        // 四角形の対角線 (頂点1, 2) でUVが切れている
+        let idx = [0, 1, 2, 2, 1, 3];
+        let uvs = [[0.0,0.0],[1.0,0.0],[0.0,1.0], [0.5,1.0],[0.5,0.0],[1.0,1.0]];
+        assert_eq!(detect_uv_seams(&uvs, &idx).unwrap(), vec![1, 2]);
+        assert!(detect_uv_seams(&uvs[..5], &idx).is_err());
+
+        let continuous = [[0.0,0.0],[1.0,0.0],[0.0,1.0], [0.0,1.0],[1.0,0.0],[1.0,1.0]];
+        assert!(detect_uv_seams(&continuous, &idx).unwrap().is_empty());
+        assert!(detect_uv_seams_with_threshold(&uvs, &idx, 0.6).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::math::{add, cross, dot, normalize, sub};
+use meshguard_core::{validate_indices, MeshGuardError};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// 境界辺を保つための垂直平面の重み
+const BOUNDARY_WEIGHT: f64 = 1e3;
+
+#[derive(Clone, Debug, Default)]
+pub struct SimplifiedMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    /// 出力頂点ごとの元の頂点番号 (UVなど簡略化しない属性を引き継ぐ用)
+    pub source: Vec<u32>,
+    /// 縮約で動かした頂点から、その頂点に集まった元の面の平面までの距離の最大値 (ワールド単位)
+    pub error: f32,
+}
+
+/// 対称4x4行列の上三角 [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd]
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane(n: [f64; 3], d: f64, w: f64) -> Quadric {
+        let [a, b, c] = n;
+        Quadric([a*a*w, a*b*w, a*c*w, a*d*w, b*b*w, b*c*w, b*d*w, c*c*w, c*d*w, d*d*w])
+    }
+
+    fn add(&mut self, o: &Quadric) {
+        for (x, y) in self.0.iter_mut().zip(o.0) { *x += y; }
+    }
+
+    fn eval(&self, p: [f64; 3]) -> f64 {
+        let q = &self.0;
+        let [x, y, z] = p;
+        q[0]*x*x + 2.0*q[1]*x*y + 2.0*q[2]*x*z + 2.0*q[3]*x
+            + q[4]*y*y + 2.0*q[5]*y*z + 2.0*q[6]*y
+            + q[7]*z*z + 2.0*q[8]*z + q[9]
+    }
+
+    /// 誤差最小点 (行列が特異ならNone)
+    fn minimizer(&self) -> Option<[f64; 3]> {
+        let q = &self.0;
+        let m = [[q[0], q[1], q[2]], [q[1], q[4], q[5]], [q[2], q[5], q[7]]];
+        let b = [-q[3], -q[6], -q[8]];
+        let det = m[0][0]*(m[1][1]*m[2][2] - m[1][2]*m[2][1])
+            - m[0][1]*(m[1][0]*m[2][2] - m[1][2]*m[2][0])
+            + m[0][2]*(m[1][0]*m[2][1] - m[1][1]*m[2][0]);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        // クラメルの公式
+        let solve = |col: usize| {
+            let mut a = m;
+            for r in 0..3 { a[r][col] = b[r]; }
+            (a[0][0]*(a[1][1]*a[2][2] - a[1][2]*a[2][1])
+                - a[0][1]*(a[1][0]*a[2][2] - a[1][2]*a[2][0])
+                + a[0][2]*(a[1][0]*a[2][1] - a[1][1]*a[2][0])) / det
+        };
+        Some([solve(0), solve(1), solve(2)])
+    }
+}
+
+struct Collapse {
+    cost: f64,
+    u: u32,
+    v: u32,
+    stamp: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, o: &Self) -> bool { self.cmp(o) == Ordering::Equal }
+}
+impl Eq for Collapse {}
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, o: &Self) -> Option<Ordering> { Some(self.cmp(o)) }
+}
+impl Ord for Collapse {
+    // BinaryHeapは最大ヒープなので逆順。同じコストは (u, v) の小さい方から取り出して結果を決定的にする
+    fn cmp(&self, o: &Self) -> Ordering { o.cost.total_cmp(&self.cost).then_with(|| (o.u, o.v).cmp(&(self.u, self.v))) }
+}
+
+fn to64(p: [f32; 3]) -> [f64; 3] { p.map(|c| c as f64) }
+fn to32(p: [f64; 3]) -> [f32; 3] { p.map(|c| c as f32) }
+
+/// Garland-Heckbertの二次誤差による辺縮約
+/// target_ratio (0, 1] は残す三角形の割合。1.0なら入力をそのまま返す
+/// normals は空でもよい (空でなければ頂点数と同じ長さ)
+pub fn simplify(positions: &[[f32; 3]], normals: &[[f32; 3]], indices: &[u32], target_ratio: f32) -> Result<SimplifiedMesh, MeshGuardError> {
+    if !normals.is_empty() && normals.len() != positions.len() {
+        return Err(MeshGuardError::InvalidInput(format!("{} normals for {} positions", normals.len(), positions.len())));
+    }
+    validate_indices(indices, positions.len())?;
+    if !(target_ratio > 0.0 && target_ratio <= 1.0) {
+        return Err(MeshGuardError::InvalidInput(format!("target_ratio must be in (0, 1], got {}", target_ratio)));
+    }
+    if target_ratio >= 1.0 {
+        return Ok(SimplifiedMesh { positions: positions.to_vec(), normals: normals.to_vec(), indices: indices.to_vec(), source: (0..positions.len() as u32).collect(), error: 0.0 });
+    }
+    let n = positions.len();
+    let has_normals = !normals.is_empty();
+    let mut pos: Vec<[f32; 3]> = positions.to_vec();
+    let mut nor: Vec<[f32; 3]> = normals.to_vec();
+    let mut tris: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+    let mut alive = vec![true; tris.len()];
+    let mut live_tris = tris.len();
+    let target = ((tris.len() as f32 * target_ratio).ceil() as usize).max(1);
+
+    let mut quadrics = vec![Quadric::default(); n];
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut edge_faces: HashMap<(u32, u32), u32> = HashMap::new();
+    // 誤差の計測用に元の面の平面 (単位法線, d) を重みなしで持っておく
+    let mut planes: Vec<Option<([f64; 3], f64)>> = vec![None; tris.len()];
+    for (t, tri) in tris.iter().enumerate() {
+        let [a, b, c] = tri.map(|i| to64(pos[i as usize]));
+        let e1 = [b[0]-a[0], b[1]-a[1], b[2]-a[2]];
+        let e2 = [c[0]-a[0], c[1]-a[1], c[2]-a[2]];
+        let cr = [e1[1]*e2[2] - e1[2]*e2[1], e1[2]*e2[0] - e1[0]*e2[2], e1[0]*e2[1] - e1[1]*e2[0]];
+        let len = (cr[0]*cr[0] + cr[1]*cr[1] + cr[2]*cr[2]).sqrt();
+        if len > 0.0 {
+            let nn = cr.map(|x| x / len);
+            let d = -(nn[0]*a[0] + nn[1]*a[1] + nn[2]*a[2]);
+            let q = Quadric::plane(nn, d, len * 0.5);
+            for &i in tri { quadrics[i as usize].add(&q); }
+            planes[t] = Some((nn, d));
+        }
+        for k in 0..3 {
+            adjacency[tri[k] as usize].push(t);
+            let (x, y) = (tri[k], tri[(k + 1) % 3]);
+            *edge_faces.entry((x.min(y), x.max(y))).or_default() += 1;
+        }
+    }
+    // 境界辺には辺を含み面に垂直な平面を足して形を保つ
+    for tri in &tris {
+        let [a, b, c] = tri.map(|i| to64(pos[i as usize]));
+        let fnorm = {
+            let e1 = [b[0]-a[0], b[1]-a[1], b[2]-a[2]];
+            let e2 = [c[0]-a[0], c[1]-a[1], c[2]-a[2]];
+            [e1[1]*e2[2] - e1[2]*e2[1], e1[2]*e2[0] - e1[0]*e2[2], e1[0]*e2[1] - e1[1]*e2[0]]
+        };
+        for k in 0..3 {
+            let (x, y) = (tri[k], tri[(k + 1) % 3]);
+            if edge_faces[&(x.min(y), x.max(y))] != 1 { continue; }
+            let (p, q) = (to64(pos[x as usize]), to64(pos[y as usize]));
+            let e = [q[0]-p[0], q[1]-p[1], q[2]-p[2]];
+            let pn = [e[1]*fnorm[2] - e[2]*fnorm[1], e[2]*fnorm[0] - e[0]*fnorm[2], e[0]*fnorm[1] - e[1]*fnorm[0]];
+            let len = (pn[0]*pn[0] + pn[1]*pn[1] + pn[2]*pn[2]).sqrt();
+            if len == 0.0 { continue; }
+            let pn = pn.map(|v| v / len);
+            let quad = Quadric::plane(pn, -(pn[0]*p[0] + pn[1]*p[1] + pn[2]*p[2]), BOUNDARY_WEIGHT);
+            quadrics[x as usize].add(&quad);
+            quadrics[y as usize].add(&quad);
+        }
+    }
+
+    // 頂点ごとに、縮約で集まった元の面
+    let mut cluster: Vec<Vec<usize>> = adjacency.clone();
+    let mut stamp = vec![0u32; n];
+    let mut removed = vec![false; n];
+    let best_position = |q: &Quadric, a: [f32; 3], b: [f32; 3]| -> ([f32; 3], f64) {
+        let mid = [(a[0]+b[0]) * 0.5, (a[1]+b[1]) * 0.5, (a[2]+b[2]) * 0.5];
+        let mut cands = vec![a, b, mid];
+        if let Some(p) = q.minimizer() { cands.push(to32(p)); }
+        cands.into_iter().map(|p| (p, q.eval(to64(p)).max(0.0))).min_by(|x, y| x.1.total_cmp(&y.1)).unwrap()
+    };
+    let mut heap = BinaryHeap::new();
+    let push = |heap: &mut BinaryHeap<Collapse>, pos: &[[f32; 3]], quadrics: &[Quadric], stamp: &[u32], u: u32, v: u32| {
+        let mut q = quadrics[u as usize];
+        q.add(&quadrics[v as usize]);
+        let (_, cost) = best_position(&q, pos[u as usize], pos[v as usize]);
+        heap.push(Collapse { cost, u, v, stamp: (stamp[u as usize], stamp[v as usize]) });
+    };
+    let mut edges: Vec<(u32, u32)> = edge_faces.keys().copied().collect();
+    edges.sort_unstable();
+    for (x, y) in edges {
+        push(&mut heap, &pos, &quadrics, &stamp, x, y);
+    }
+
+    let mut max_dist = 0.0f64;
+    while live_tris > target {
+        let Some(c) = heap.pop() else { break };
+        let (u, v) = (c.u as usize, c.v as usize);
+        if removed[u] || removed[v] || c.stamp != (stamp[u], stamp[v]) {
+            continue;
+        }
+        let mut q = quadrics[u];
+        q.add(&quadrics[v]);
+        let (p, _) = best_position(&q, pos[u], pos[v]);
+
+        // 面が裏返る縮約は採用しない
+        let flips = adjacency[u].iter().chain(&adjacency[v]).any(|&t| {
+            if !alive[t] { return false; }
+            let tri = tris[t];
+            if tri.contains(&(u as u32)) && tri.contains(&(v as u32)) { return false; }
+            let before = tri.map(|i| pos[i as usize]);
+            let after = tri.map(|i| if i as usize == u || i as usize == v { p } else { pos[i as usize] });
+            let nb = cross(sub(before[1], before[0]), sub(before[2], before[0]));
+            let na = cross(sub(after[1], after[0]), sub(after[2], after[0]));
+            dot(nb, na) <= 0.0
+        });
+        if flips {
+            continue;
+        }
+
+        pos[u] = p;
+        quadrics[u] = q;
+        if has_normals {
+            nor[u] = normalize(add(nor[u], nor[v])).unwrap_or(nor[u]);
+        }
+        removed[v] = true;
+        let moved = std::mem::take(&mut adjacency[v]);
+        for t in moved {
+            if !alive[t] { continue; }
+            if tris[t].contains(&(u as u32)) {
+                alive[t] = false;
+                live_tris -= 1;
+            } else {
+                for i in tris[t].iter_mut() { if *i as usize == v { *i = u as u32; } }
+                adjacency[u].push(t);
+            }
+        }
+        adjacency[u].retain(|&t| alive[t]);
+        stamp[u] += 1;
+        let mut merged = std::mem::take(&mut cluster[v]);
+        cluster[u].append(&mut merged);
+        cluster[u].sort_unstable();
+        cluster[u].dedup();
+        let p64 = to64(p);
+        for (nn, d) in cluster[u].iter().filter_map(|&t| planes[t]) {
+            max_dist = max_dist.max((nn[0]*p64[0] + nn[1]*p64[1] + nn[2]*p64[2] + d).abs());
+        }
+
+        let mut neighbors: Vec<u32> = adjacency[u].iter().flat_map(|&t| tris[t]).filter(|&w| w as usize != u).collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        for w in neighbors {
+            push(&mut heap, &pos, &quadrics, &stamp, u as u32, w);
+        }
+    }
+
+    // 残った頂点を元の順序のまま詰める
+    let mut remap = vec![u32::MAX; n];
+    let mut out = SimplifiedMesh { error: max_dist as f32, ..Default::default() };
+    for (t, tri) in tris.iter().enumerate() {
+        if alive[t] {
+            for &i in tri { remap[i as usize] = 0; }
+        }
+    }
+    for i in 0..n {
+        if remap[i] == 0 {
+            remap[i] = out.positions.len() as u32;
+            out.positions.push(pos[i]);
+            out.source.push(i as u32);
+            if has_normals { out.normals.push(nor[i]); }
+        }
+    }
+    for (t, tri) in tris.iter().enumerate() {
+        if alive[t] {
+            out.indices.extend(tri.iter().map(|&i| remap[i as usize]));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(n: u32) -> (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<u32>) {
+        let mut pos = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                pos.push([x as f32, y as f32, 0.0]);
+            }
+        }
+        let mut idx = Vec::new();
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                idx.extend_from_slice(&[i, i + 1, i + n + 1, i, i + n + 1, i + n]);
+            }
+        }
+        let nor = vec![[0.0, 0.0, 1.0]; pos.len()];
+        (pos, nor, idx)
+    }
+
+    #[test]
+    fn ratio_one_is_identity() {
+        let (pos, nor, idx) = grid(4);
+        let s = simplify(&pos, &nor, &idx, 1.0).unwrap();
+        assert_eq!((s.positions, s.normals, s.indices, s.error), (pos, nor, idx, 0.0));
+    }
+
+    #[test]
+    fn same_input_gives_same_output() {
+        let (pos, nor, idx) = grid(9);
+        let a = simplify(&pos, &nor, &idx, 0.5).unwrap();
+        for _ in 0..5 {
+            let b = simplify(&pos, &nor, &idx, 0.5).unwrap();
+            assert_eq!((&a.positions, &a.normals, &a.indices, &a.source, a.error), (&b.positions, &b.normals, &b.indices, &b.source, b.error));
+        }
+    }
+
+    #[test]
+    fn flat_grid_collapses_without_error() {
+        let (pos, nor, idx) = grid(11);
+        let s = simplify(&pos, &nor, &idx, 0.1).unwrap();
+        assert!(simplify(&pos, &nor, &idx, 0.0).is_err());
+        assert!(matches!(simplify(&pos, &nor, &[0, 1, 121], 0.5), Err(MeshGuardError::IndexOutOfBounds { index: 121, .. })));
+        assert!(s.indices.len() / 3 <= 20, "{} triangles left", s.indices.len() / 3);
+        assert!(s.error < 1e-3);
+        // 平面のまま、外周も保たれる
+        assert!(s.positions.iter().all(|p| p[2] == 0.0));
+        for corner in [[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [0.0, 10.0, 0.0], [10.0, 10.0, 0.0]] {
+            assert!(s.positions.contains(&corner));
+        }
+        assert!(s.indices.iter().all(|&i| (i as usize) < s.positions.len()));
+        assert_eq!(s.source.len(), s.positions.len());
+        assert!(s.source.windows(2).all(|w| w[0] < w[1]) && s.source.iter().all(|&i| i < 121));
+        assert!(matches!(simplify(&pos, &nor[1..], &idx, 0.5), Err(MeshGuardError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn error_is_a_world_space_distance() {
+        // 半幅w・高さhの四角錐 (底面は開いている)。頂点が角へ縮約されると、
+        // 誤差は縮約先から元の4面までの距離の最大値 (ちょうど角なら 2wh / sqrt(w^2 + h^2))
+        for (w, h) in [(1.0f32, 1.0f32), (5.0, 2.0)] {
+            let pos = [[-w, -w, 0.0], [w, -w, 0.0], [w, w, 0.0], [-w, w, 0.0], [0.0, 0.0, h]];
+            let idx = [0u32, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4];
+            let s = simplify(&pos, &[], &idx, 0.5).unwrap();
+            assert_eq!(s.indices.len(), 6);
+            assert!(!s.source.contains(&4));
+            // 動いたのは1頂点だけ
+            let moved: Vec<[f32; 3]> = s.positions.iter().zip(&s.source).filter(|(p, &i)| **p != pos[i as usize]).map(|(p, _)| *p).collect();
+            assert_eq!(moved.len(), 1);
+            let p = moved[0];
+            let want = idx.chunks_exact(3).map(|t| {
+                let (a, b, c) = (pos[t[0] as usize], pos[t[1] as usize], pos[t[2] as usize]);
+                let n = normalize(cross(sub(b, a), sub(c, a))).unwrap();
+                dot(n, sub(p, a)).abs()
+            }).fold(0.0f32, f32::max);
+            assert!((s.error - want).abs() < want * 1e-4, "{} vs {}", s.error, want);
+            let corner = 2.0 * w * h / (w * w + h * h).sqrt();
+            assert!(s.error > corner * 0.5 && s.error <= corner * 1.01, "{} vs {}", s.error, corner);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use meshguard_core::{MeshGuardError, STRIP_RESTART_INDEX};
+use std::collections::HashMap;
+
+/// 三角形リストを貪欲につないだストリップ群 (向きは保つ)
+fn build_strips(indices: &[u32]) -> Result<Vec<Vec<u32>>, MeshGuardError> {
+    if !indices.len().is_multiple_of(3) {
+        return Err(MeshGuardError::InvalidInput(format!("index count {} is not a multiple of 3", indices.len())));
+    }
+    let tris: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+    // 有向辺 a->b を持つ三角形
+    let mut by_edge: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (t, tri) in tris.iter().enumerate() {
+        for k in 0..3 {
+            by_edge.entry((tri[k], tri[(k + 1) % 3])).or_default().push(t);
+        }
+    }
+    let mut used = vec![false; tris.len()];
+    let third = |tri: &[u32; 3], a: u32, b: u32| tri.iter().copied().find(|&v| v != a && v != b);
+    let next = |used: &[bool], a: u32, b: u32| by_edge.get(&(a, b)).and_then(|ts| ts.iter().copied().find(|&t| !used[t]));
+
+    let mut strips = Vec::new();
+    for start in 0..tris.len() {
+        if used[start] { continue; }
+        used[start] = true;
+        let [x, y, z] = tris[start];
+        // 2つ目の三角形が続く回転を選ぶ
+        let rot = [[x, y, z], [y, z, x], [z, x, y]];
+        let first = rot.iter().copied().find(|r| next(&used, r[2], r[1]).is_some()).unwrap_or(rot[0]);
+        let mut strip = first.to_vec();
+        loop {
+            let n = strip.len();
+            let (a, b) = (strip[n - 2], strip[n - 1]);
+            // 偶数番目の三角形は (a, b, c)、奇数番目は (b, a, c) が元の向き
+            let edge = if (n - 2).is_multiple_of(2) { (a, b) } else { (b, a) };
+            let Some(t) = next(&used, edge.0, edge.1) else { break };
+            let Some(c) = third(&tris[t], a, b) else { break };
+            used[t] = true;
+            strip.push(c);
+        }
+        strips.push(strip);
+    }
+    Ok(strips)
+}
+
+/// 三角形リスト → 1本のストリップ
+/// ストリップ同士は縮退三角形でつなぐ (向きが変わらないよう必要なら1頂点足す)
+pub fn triangles_to_strip(indices: &[u32]) -> Result<Vec<u32>, MeshGuardError> {
+    let mut out: Vec<u32> = Vec::with_capacity(indices.len());
+    for strip in build_strips(indices)? {
+        if let Some(&last) = out.last() {
+            out.push(last);
+            if out.len().is_multiple_of(2) {
+                out.push(strip[0]);
+            }
+            out.push(strip[0]);
+        }
+        out.extend_from_slice(&strip);
+    }
+    Ok(out)
+}
+
+/// 三角形リスト → STRIP_RESTART_INDEX で区切ったストリップ
+pub fn triangles_to_strip_restart(indices: &[u32]) -> Result<Vec<u32>, MeshGuardError> {
+    let mut out = Vec::with_capacity(indices.len());
+    for strip in build_strips(indices)? {
+        if !out.is_empty() {
+            out.push(STRIP_RESTART_INDEX);
+        }
+        out.extend_from_slice(&strip);
+    }
+    Ok(out)
+}
+
+/// ストリップ → 三角形リスト
+/// 縮退三角形 (同じ頂点を含むもの) は捨て、STRIP_RESTART_INDEX で向きの偶奇をリセットする
+pub fn strip_to_triangles(strip: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(strip.len() * 3);
+    for run in strip.split(|&i| i == STRIP_RESTART_INDEX) {
+        for (k, w) in run.windows(3).enumerate() {
+            if w[0] == w[1] || w[1] == w[2] || w[0] == w[2] { continue; }
+            if k.is_multiple_of(2) { out.extend_from_slice(w) } else { out.extend_from_slice(&[w[1], w[0], w[2]]) }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 向きを保ったまま三角形を比較できるように回転を揃える
+    fn canonical(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut tris: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| {
+            let m = (0..3).min_by_key(|&k| t[k]).unwrap();
+            [t[m], t[(m + 1) % 3], t[(m + 2) % 3]]
+        }).collect();
+        tris.sort();
+        tris
+    }
+
+    fn grid(n: u32) -> Vec<u32> {
+        let mut idx = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let v = y * (n + 1) + x;
+                idx.extend_from_slice(&[v, v + 1, v + n + 1, v + 1, v + n + 2, v + n + 1]);
+            }
+        }
+        idx
+    }
+
+    #[test]
+    fn strip_round_trip_keeps_triangles_and_winding() {
+        let idx = grid(8);
+        let strip = triangles_to_strip(&idx).unwrap();
+        assert_eq!(canonical(&strip_to_triangles(&strip)), canonical(&idx));
+        assert!(strip.len() < idx.len() * 3 / 4, "{} vs {}", strip.len(), idx.len());
+
+        let restart = triangles_to_strip_restart(&idx).unwrap();
+        assert_eq!(canonical(&strip_to_triangles(&restart)), canonical(&idx));
+
+        // つながらない三角形だけでも壊れない
+        let loose = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(canonical(&strip_to_triangles(&triangles_to_strip(&loose).unwrap())), canonical(&loose));
+        assert!(triangles_to_strip(&[]).unwrap().is_empty());
+        assert!(triangles_to_strip(&[0, 1]).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::math::{add, cross, dot, length, normalize, scale, sub};
+use meshguard_core::{validate_indices, MeshGuardError};
+
+/// UV三角形の符号付き面積がこれ未満なら接線を決められないとみなす
+const UV_EPSILON: f32 = 1e-12;
+
+/// Mikktspace風の頂点接線 (xyz + 従接線の向きw = ±1)
+/// 面ごとの接線を頂点法線の接平面へ射影し、角の角度で重み付けして平均する
+/// UVが縮退していて決まらない頂点は法線に垂直な適当な向きにする
+/// normals / uvs が頂点数と合わなければLengthMismatch、インデックスが頂点数を超えたらIndexOutOfBounds
+pub fn compute_tangents(positions: &[[f32; 3]], normals: &[[f32; 3]], uvs: &[[f32; 2]], indices: &[u32]) -> Result<Vec<[f32; 4]>, MeshGuardError> {
+    let n = positions.len();
+    if normals.len() != n {
+        return Err(MeshGuardError::LengthMismatch { attribute: "normal", expected: n, got: normals.len() });
+    }
+    if uvs.len() != n {
+        return Err(MeshGuardError::LengthMismatch { attribute: "uv", expected: n, got: uvs.len() });
+    }
+    validate_indices(indices, n)?;
+    let mut tan = vec![[0.0f32; 3]; n];
+    let mut bit = vec![[0.0f32; 3]; n];
+    for tri in indices.chunks_exact(3) {
+        let v = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+        let e1 = sub(positions[v[1]], positions[v[0]]);
+        let e2 = sub(positions[v[2]], positions[v[0]]);
+        let (du1, dv1) = (uvs[v[1]][0] - uvs[v[0]][0], uvs[v[1]][1] - uvs[v[0]][1]);
+        let (du2, dv2) = (uvs[v[2]][0] - uvs[v[0]][0], uvs[v[2]][1] - uvs[v[0]][1]);
+        let r = du1 * dv2 - du2 * dv1;
+        if r.abs() < UV_EPSILON {
+            continue;
+        }
+        let t = scale(sub(scale(e1, dv2), scale(e2, dv1)), 1.0 / r);
+        let b = scale(sub(scale(e2, du1), scale(e1, du2)), 1.0 / r);
+
+        for k in 0..3 {
+            let (i, prev, next) = (v[k], v[(k + 2) % 3], v[(k + 1) % 3]);
+            let angle = corner_angle(positions[i], positions[next], positions[prev]);
+            let nrm = normals[i];
+            // Mikktspaceと同様に接平面へ射影してから正規化して足す
+            if let Some(tp) = normalize(sub(t, scale(nrm, dot(nrm, t)))) {
+                tan[i] = add(tan[i], scale(tp, angle));
+            }
+            if let Some(bp) = normalize(sub(b, scale(nrm, dot(nrm, b)))) {
+                bit[i] = add(bit[i], scale(bp, angle));
+            }
+        }
+    }
+
+    Ok((0..n)
+        .map(|i| {
+            let nrm = normals[i];
+            let t = normalize(sub(tan[i], scale(nrm, dot(nrm, tan[i])))).unwrap_or_else(|| perpendicular(nrm));
+            let w = if dot(cross(nrm, t), bit[i]) < 0.0 { -1.0 } else { 1.0 };
+            [t[0], t[1], t[2], w]
+        })
+        .collect())
+}
+
+fn corner_angle(p: [f32; 3], a: [f32; 3], b: [f32; 3]) -> f32 {
+    let (ea, eb) = (sub(a, p), sub(b, p));
+    let denom = length(ea) * length(eb);
+    if denom <= 0.0 { 0.0 } else { (dot(ea, eb) / denom).clamp(-1.0, 1.0).acos() }
+}
+
+/// nに最も平行でない軸との外積
+fn perpendicular(n: [f32; 3]) -> [f32; 3] {
+    let axis = if n[0].abs() <= n[1].abs() && n[0].abs() <= n[2].abs() { [1.0, 0.0, 0.0] }
+        else if n[1].abs() <= n[2].abs() { [0.0, 1.0, 0.0] }
+        else { [0.0, 0.0, 1.0] };
+    normalize(cross(n, axis)).unwrap_or([1.0, 0.0, 0.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_tangents_follow_u() {
+        let pos = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        let nor = [[0.0, 0.0, 1.0]; 4];
+        let idx = [0, 1, 2, 0, 2, 3];
+        let t = compute_tangents(&pos, &nor, &[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]], &idx).unwrap();
+        for v in &t {
+            assert!((v[0] - 1.0).abs() < 1e-5 && v[1].abs() < 1e-5 && v[2].abs() < 1e-5);
+            assert_eq!(v[3], 1.0);
+        }
+
+        // Vを反転すると従接線が逆向き
+        let t = compute_tangents(&pos, &nor, &[[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]], &idx).unwrap();
+        assert!(t.iter().all(|v| v[3] == -1.0));
+
+        // UVが全部同じなら法線に垂直な単位ベクトル
+        let t = compute_tangents(&pos, &nor, &[[0.5, 0.5]; 4], &idx).unwrap();
+        for v in &t {
+            assert!(v[2].abs() < 1e-6 && ((v[0]*v[0] + v[1]*v[1]).sqrt() - 1.0).abs() < 1e-6);
+        }
+
+        let uv = [[0.0, 0.0]; 4];
+        assert_eq!(compute_tangents(&pos, &nor[..3], &uv, &idx), Err(MeshGuardError::LengthMismatch { attribute: "normal", expected: 4, got: 3 }));
+        assert_eq!(compute_tangents(&pos, &nor, &uv[..2], &idx), Err(MeshGuardError::LengthMismatch { attribute: "uv", expected: 4, got: 2 }));
+        assert!(matches!(compute_tangents(&pos, &nor, &uv, &[0, 1, 7]), Err(MeshGuardError::IndexOutOfBounds { index: 7, .. })));
+    }
+}
@@ -0,0 +1,96 @@
+use meshguard_core::MeshGuardError;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default)]
+pub struct WeldedMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    /// 元の頂点iの新しい番号 (三角形スープならそのままインデックスバッファになる)
+    pub remap: Vec<u32>,
+}
+
+impl WeldedMesh {
+    /// 元の頂点を指すインデックスを溶接後の頂点に付け替える
+    pub fn remap_indices(&self, indices: &[u32]) -> Vec<u32> {
+        indices.iter().map(|&i| self.remap[i as usize]).collect()
+    }
+}
+
+/// 位置・法線・UVがすべてtolerance以内の頂点をまとめる
+/// normals / uvs は空なら無視する。tolerance <= 0 はビット一致のみ
+pub fn weld_vertices(positions: &[[f32; 3]], normals: &[[f32; 3]], uvs: &[[f32; 2]], tolerance: f32) -> Result<WeldedMesh, MeshGuardError> {
+    let n = positions.len();
+    if !normals.is_empty() && normals.len() != n {
+        return Err(MeshGuardError::LengthMismatch { attribute: "normal", expected: n, got: normals.len() });
+    }
+    if !uvs.is_empty() && uvs.len() != n {
+        return Err(MeshGuardError::LengthMismatch { attribute: "uv", expected: n, got: uvs.len() });
+    }
+
+    let exact = tolerance <= 0.0;
+    let tol = tolerance.max(0.0);
+    let cell = |p: &[f32; 3]| if exact { p.map(|c| c.to_bits() as i64) } else { p.map(|c| (c / tol).floor() as i64) };
+    let reach = if exact { 0 } else { 1 };
+    let close = |a: &[f32], b: &[f32]| {
+        if exact { a.iter().zip(b).all(|(x, y)| x.to_bits() == y.to_bits()) }
+        else { a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>() <= tol * tol }
+    };
+
+    let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut out = WeldedMesh { remap: Vec::with_capacity(n), ..Default::default() };
+    for (v, p) in positions.iter().enumerate() {
+        let c = cell(p);
+        let mut found = None;
+        'search: for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    let key = [c[0].saturating_add(dx), c[1].saturating_add(dy), c[2].saturating_add(dz)];
+                    let Some(list) = grid.get(&key) else { continue };
+                    for &i in list {
+                        let w = i as usize;
+                        if close(p, &out.positions[w])
+                            && (normals.is_empty() || close(&normals[v], &out.normals[w]))
+                            && (uvs.is_empty() || close(&uvs[v], &out.uvs[w])) {
+                            found = Some(i);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        let i = found.unwrap_or_else(|| {
+            out.positions.push(*p);
+            if !normals.is_empty() { out.normals.push(normals[v]); }
+            if !uvs.is_empty() { out.uvs.push(uvs[v]); }
+            let i = (out.positions.len() - 1) as u32;
+            grid.entry(c).or_default().push(i);
+            i
+        });
+        out.remap.push(i);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welds_only_matching_attributes() {
+        // 0と1は同一、2は法線違い、3は誤差内
+        let pos = [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [1e-6, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        let nor = [[0.0, 0.0, 1.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, 1.0]];
+        let uv = [[0.5, 0.5]; 5];
+        let w = weld_vertices(&pos, &nor, &uv, 1e-5).unwrap();
+        assert_eq!(w.remap, vec![0, 0, 1, 0, 2]);
+        assert_eq!(w.positions.len(), 3);
+        assert_eq!(w.normals[1], [1.0, 0.0, 0.0]);
+        assert_eq!(w.remap_indices(&[4, 3, 2]), vec![2, 0, 1]);
+
+        let exact = weld_vertices(&pos, &[], &[], 0.0).unwrap();
+        assert!(weld_vertices(&pos, &nor[..1], &[], 0.0).is_err());
+        assert_eq!(exact.remap, vec![0, 0, 0, 1, 2]);
+        assert!(exact.normals.is_empty());
+    }
+}
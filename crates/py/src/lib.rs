@@ -0,0 +1,56 @@
+//! PyO3によるPythonバインディング
+//! `maturin develop -m crates/py/Cargo.toml` でインストールし、`pytest crates/py/tests` でテストする
+//! 入力は (n, 3) / (n, 2) のfloat32配列
+
+use meshguard_quantize as mq;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+fn rows<const N: usize>(a: &PyReadonlyArray2<'_, f32>, what: &str) -> PyResult<Vec<[f32; N]>> {
+    let a = a.as_array();
+    if a.ncols() != N {
+        return Err(PyValueError::new_err(format!("{} must have shape (n, {}), got {:?}", what, N, a.shape())));
+    }
+    Ok(a.rows().into_iter().map(|r| std::array::from_fn(|i| r[i])).collect())
+}
+
+fn to_2d<'py, T: numpy::Element>(py: Python<'py>, data: Vec<T>, cols: usize) -> Bound<'py, PyArray2<T>> {
+    let n = data.len() / cols;
+    Array2::from_shape_vec((n, cols), data).expect("length is a multiple of cols").into_pyarray(py)
+}
+
+type Quantized<'py> = (Bound<'py, PyArray2<i16>>, Bound<'py, PyArray1<f32>>, Bound<'py, PyArray1<f32>>);
+
+/// (data: int16 (n, 3), scale: float32 (3,), offset: float32 (3,)) を返す
+/// 復元は `(data + 32768) * scale + offset`
+#[pyfunction]
+fn quantize_positions<'py>(py: Python<'py>, positions: PyReadonlyArray2<'py, f32>) -> PyResult<Quantized<'py>> {
+    let pos = rows::<3>(&positions, "positions")?;
+    let q = mq::quantize_positions(&pos).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let data = (0..q.data.len()).map(|i| q.data.get(i)).collect();
+    Ok((to_2d(py, data, 3), q.scale.to_vec().into_pyarray(py), q.offset.to_vec().into_pyarray(py)))
+}
+
+/// uint16 (n, 2) のoct法線を返す
+#[pyfunction]
+fn encode_normals_oct<'py>(py: Python<'py>, normals: PyReadonlyArray2<'py, f32>) -> PyResult<Bound<'py, PyArray2<u16>>> {
+    let nor = rows::<3>(&normals, "normals")?;
+    Ok(to_2d(py, mq::encode_normals_oct(&nor).data, 2))
+}
+
+/// uint16 (n, 2) のUVを返す ([0, 1] を 0..65535 に対応させる)
+#[pyfunction]
+fn quantize_uvs<'py>(py: Python<'py>, uvs: PyReadonlyArray2<'py, f32>) -> PyResult<Bound<'py, PyArray2<u16>>> {
+    let uv = rows::<2>(&uvs, "uvs")?;
+    Ok(to_2d(py, mq::quantize_uvs(&uv).data, 2))
+}
+
+#[pymodule]
+fn meshguard_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(quantize_positions, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_normals_oct, m)?)?;
+    m.add_function(wrap_pyfunction!(quantize_uvs, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+import numpy as np
+
+import meshguard_py as mg
+
+
+def test_positions_roundtrip():
+    rng = np.random.default_rng(0)
+    pos = rng.uniform(-5.0, 5.0, size=(100, 3)).astype(np.float32)
+    data, scale, offset = mg.quantize_positions(pos)
+    assert data.shape == (100, 3) and data.dtype == np.int16
+    restored = (data.astype(np.float64) + 32768.0) * scale + offset
+    assert np.all(np.abs(restored - pos) <= 0.5 * scale + 1e-5)
+
+
+def test_normals_and_uvs():
+    nor = np.array([[0, 0, 1], [1, 0, 0], [0, -1, 0]], dtype=np.float32)
+    assert mg.encode_normals_oct(nor).shape == (3, 2)
+    uv = mg.quantize_uvs(np.array([[0.0, 1.0], [0.5, 0.25]], dtype=np.float32))
+    assert uv.dtype == np.uint16
+    assert uv[0].tolist() == [0, 65535]
+
+
+def test_wrong_shape_is_rejected():
+    try:
+        mg.quantize_positions(np.zeros((4, 2), dtype=np.float32))
+    except ValueError as e:
+        assert "shape (n, 3)" in str(e)
+    else:
+        raise AssertionError("expected ValueError")
@@ -0,0 +1,26 @@
+//! aabb_min_max のスループット計測
+//! `cargo bench -p meshguard-quantize` と `--features simd` を付けた場合で比較する
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meshguard_quantize::aabb_min_max;
+use std::hint::black_box;
+
+fn make_positions(n: usize) -> Vec<[f32; 3]> {
+    (0..n).map(|i| { let t = i as f32 * 0.001; [t.sin() * 10.0, t.cos() * 5.0, t] }).collect()
+}
+
+fn bench_aabb(c: &mut Criterion) {
+    let path = if cfg!(feature = "simd") { "simd" } else { "scalar" };
+    let mut group = c.benchmark_group("aabb_min_max");
+    for n in [10_000usize, 1_000_000] {
+        let positions = make_positions(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new(path, n), &positions, |b, p| {
+            b.iter(|| aabb_min_max(black_box(p)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_aabb);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+//! dequantize_positions のスループット計測 (頂点/秒)
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meshguard_quantize::{dequantize_positions, quantize_positions};
+use std::hint::black_box;
+
+fn bench_dequantize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dequantize_positions");
+    for n in [1_000usize, 100_000, 1_000_000] {
+        let positions: Vec<[f32; 3]> = (0..n).map(|i| { let t = i as f32 * 0.001; [t.sin() * 10.0, t.cos() * 5.0, t] }).collect();
+        let q = quantize_positions(&positions).unwrap();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &q, |b, q| b.iter(|| dequantize_positions(black_box(q))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dequantize);
+criterion_main!(benches);
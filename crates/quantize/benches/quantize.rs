@@ -0,0 +1,57 @@
+//! 量子化まわりのスループット計測 (頂点/秒)
+//! `cargo bench -p meshguard-quantize --bench quantize`。結果はリポジトリ直下の bench_baseline.json と比べる
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meshguard_quantize::{aabb_min_max, encode_normals_oct, quantize_positions, quantize_uvs};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+fn make_positions(n: usize) -> Vec<[f32; 3]> {
+    (0..n).map(|i| { let t = i as f32 * 0.001; [t.sin() * 10.0, t.cos() * 5.0, t] }).collect()
+}
+
+fn make_normals(n: usize) -> Vec<[f32; 3]> {
+    (0..n).map(|i| { let t = i as f32 * 0.01; let (s, c) = t.sin_cos(); [c * 0.6, s * 0.6, 0.8] }).collect()
+}
+
+fn make_uvs(n: usize) -> Vec<[f32; 2]> {
+    (0..n).map(|i| { let t = i as f32 * 0.001; [t.fract(), (t * 0.5).fract()] }).collect()
+}
+
+fn bench_quantize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quantize_positions");
+    for n in SIZES {
+        let positions = make_positions(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &positions, |b, p| b.iter(|| quantize_positions(black_box(p))));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("encode_normals_oct");
+    for n in SIZES {
+        let normals = make_normals(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &normals, |b, p| b.iter(|| encode_normals_oct(black_box(p))));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("quantize_uvs");
+    for n in SIZES {
+        let uvs = make_uvs(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &uvs, |b, p| b.iter(|| quantize_uvs(black_box(p))));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("aabb_min_max_sizes");
+    for n in SIZES {
+        let positions = make_positions(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &positions, |b, p| b.iter(|| aabb_min_max(black_box(p))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_quantize);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! quantize_positions_par のスレッド数ごとのスケーリング
+//! `cargo bench -p meshguard-quantize --features rayon --bench quantize_par`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meshguard_quantize::{quantize_positions, quantize_positions_par};
+use std::hint::black_box;
+
+fn make_positions(n: usize) -> Vec<[f32; 3]> {
+    (0..n).map(|i| { let t = i as f32 * 0.001; [t.sin() * 10.0, t.cos() * 5.0, t] }).collect()
+}
+
+fn bench_par(c: &mut Criterion) {
+    let n = 1_000_000;
+    let positions = make_positions(n);
+    let mut group = c.benchmark_group("quantize_positions_1M");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_function("sequential", |b| b.iter(|| quantize_positions(black_box(&positions))));
+    for threads in [1usize, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        group.bench_with_input(BenchmarkId::new("par", threads), &positions, |b, p| {
+            b.iter(|| pool.install(|| quantize_positions_par(black_box(p))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_par);
+criterion_main!(benches);
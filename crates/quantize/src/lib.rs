@@ -1,21 +1,62 @@
+pub use meshguard_core::MeshGuardError;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Vec2(pub f32, pub f32);
 #[derive(Clone, Copy,Debug)]
 pub struct Vec3(pub f32, pub f32, pub f32);
+#[derive(Clone, Copy, Debug)]
+pub struct Vec4(pub f32, pub f32, pub f32, pub f32);
 
+/// 量子化済み座標の格納先
+/// 全軸8bit以下ならi8、9〜16bitの軸があればi16に詰める
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PositionData {
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+}
+
+impl PositionData {
+    pub fn len(&self) -> usize {
+        match self {
+            PositionData::I8(v) => v.len(),
+            PositionData::I16(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// i番目の成分をi16に広げて返す
+    #[inline]
+    pub fn get(&self, i: usize) -> i16 {
+        match self {
+            PositionData::I8(v) => v[i] as i16,
+            PositionData::I16(v) => v[i],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuantizedPositions {
-    pub data: Vec<i16>,
+    pub data: PositionData,
+    /// 軸ごとの量子化ビット深度 (1〜16)
+    /// 復号時はこれを見て軸ごとの中心値を決める
+    pub bits: [u8; 3],
     pub scale: [f32; 3],
     pub offset: [f32; 3],
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuantizedNormalsOct {
     pub data: Vec<u16>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuantizedUVs {
     pub data: Vec<u16>,
 }
@@ -25,9 +66,55 @@ fn clamp<T: PartialOrd>(x: T, lo: T, hi: T) -> T {
     if x < lo { lo } else if x > hi { hi } else { x }
 }
 
+/// NaN/Infを含む座標を探し、最初に見つかった位置を返す
+/// 壊れたモデルファイルだとAABBがNaNになり量子化結果がゴミになるので先に弾く
+pub fn validate_positions(positions: &[[f32; 3]]) -> Result<(), MeshGuardError> {
+    for (n, p) in positions.iter().enumerate() {
+        if let Some(a) = p.iter().position(|v| !v.is_finite()) {
+            let kind = if p[a].is_nan() { "NaN" } else { "Inf" };
+            return Err(MeshGuardError::InvalidInput(format!("{} in position[{}][{}]", kind, n, a)));
+        }
+    }
+    Ok(())
+}
+
 /// AABB: Axis-Aligned Bounding Box
 /// モデル全体をちょうど内包する直方体の最小座標と最大座標を返す
-pub fn aabb_min_max(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+/// 頂点がなければNone
+pub fn aabb_min_max(positions: &[[f32; 3]]) -> Option<([f32; 3], [f32; 3])> {
+    if positions.is_empty() {
+        return None;
+    }
+    #[cfg(feature = "simd")]
+    let (min, max) = aabb_simd(positions);
+    #[cfg(not(feature = "simd"))]
+    let (min, max) = aabb_scalar(positions);
+    Some(guard_degenerate(min, max))
+}
+
+/// 2つのAABBを両方内包するAABB
+/// 複数メッシュを同じ座標空間で量子化するときに `shared_aabb` へ渡す
+pub fn aabb_union(a_min: [f32; 3], a_max: [f32; 3], b_min: [f32; 3], b_max: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+    (std::array::from_fn(|i| a_min[i].min(b_min[i])), std::array::from_fn(|i| a_max[i].max(b_max[i])))
+}
+
+/// 全AABBの和
+/// 空なら min=+Inf, max=-Inf (何と合わせても相手がそのまま残る) を返す
+pub fn aabb_union_all(aabbs: &[([f32; 3], [f32; 3])]) -> ([f32; 3], [f32; 3]) {
+    aabbs.iter().fold(([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]), |(min, max), &(b_min, b_max)| aabb_union(min, max, b_min, b_max))
+}
+
+/// 幅がほぼ0の軸を少しだけ広げる
+fn guard_degenerate(min: [f32; 3], mut max: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+    for a in 0..3 {
+        if (max[a] - min[a]).abs() < 1e-12 {
+            max[a] = min[a] + 1e-6;
+        }
+    }
+    (min, max)
+}
+
+fn aabb_scalar(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
     let mut min = [f32::INFINITY; 3];
     let mut max = [f32::NEG_INFINITY; 3];
     for p in positions {
@@ -36,48 +123,434 @@ pub fn aabb_min_max(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
             if p[a] > max[a] { max[a] = p[a]; }
         }
     }
+    (min, max)
+}
 
-    for a in 0..3 {
-        if (max[a] - min[a]).abs() < 1e-12 {
-            max[a] = min[a] + 1e-6;
+/// 4頂点(12要素)をf32x4の3レジスタにそのまま載せて比較する
+/// レーンごとの軸は [x y z x] [y z x y] [z x y z] の並びになる
+#[cfg(feature = "simd")]
+fn aabb_simd(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    use wide::f32x4;
+
+    let flat = positions.as_flattened();
+    let chunks = flat.chunks_exact(12);
+    let rest = aabb_scalar(&positions[positions.len() - chunks.remainder().len() / 3..]);
+
+    let mut lo = [f32x4::splat(f32::INFINITY); 3];
+    let mut hi = [f32x4::splat(f32::NEG_INFINITY); 3];
+    // fast_min/fast_maxはNaNのとき第2引数を返すので、スカラー版と同じくNaNは無視される
+    for c in chunks {
+        for r in 0..3 {
+            let v = f32x4::from([c[r*4], c[r*4 + 1], c[r*4 + 2], c[r*4 + 3]]);
+            lo[r] = v.fast_min(lo[r]);
+            hi[r] = v.fast_max(hi[r]);
+        }
+    }
+
+    let lo = lo.map(|v| v.to_array());
+    let hi = hi.map(|v| v.to_array());
+    let mut min = rest.0;
+    let mut max = rest.1;
+    for r in 0..3 {
+        for l in 0..4 {
+            let a = (r * 4 + l) % 3;
+            min[a] = min[a].min(lo[r][l]);
+            max[a] = max[a].max(hi[r][l]);
         }
     }
     (min, max)
 }
 
+/// 包含球 (Ritterの近似)
+/// 最小球より最大で数%大きくなるが1回の走査で済む
+pub fn bounding_sphere(positions: &[[f32; 3]]) -> ([f32; 3], f32) {
+    let Some(&first) = positions.first() else { return ([0.0; 3], 0.0) };
+    let dist2 = |a: &[f32; 3], b: &[f32; 3]| (0..3).map(|i| ((a[i] - b[i]) as f64).powi(2)).sum::<f64>();
+    let farthest = |from: &[f32; 3]| *positions.iter().max_by(|a, b| dist2(from, a).total_cmp(&dist2(from, b))).unwrap();
+
+    let y = farthest(&first);
+    let z = farthest(&y);
+    let mut center = [0, 1, 2].map(|i| (y[i] as f64 + z[i] as f64) * 0.5);
+    let mut radius = dist2(&y, &z).sqrt() * 0.5;
+    for p in positions {
+        let d = [0, 1, 2].map(|i| p[i] as f64 - center[i]);
+        let len = (d[0]*d[0] + d[1]*d[1] + d[2]*d[2]).sqrt();
+        if len > radius {
+            let new_radius = (radius + len) * 0.5;
+            let k = (new_radius - radius) / len;
+            for i in 0..3 { center[i] += d[i] * k; }
+            radius = new_radius;
+        }
+    }
+    // f32に丸めたときに端の点がはみ出さないよう少しだけ広げる
+    let center = center.map(|c| c as f32);
+    let radius = positions
+        .iter()
+        .map(|p| dist2(p, &center).sqrt())
+        .fold(radius, f64::max);
+    (center, radius as f32 * (1.0 + f32::EPSILON))
+}
+
+/// AABBを内包する球 (中心はAABBの中心、半径は対角線の半分)
+pub fn bounding_sphere_from_aabb(min: [f32; 3], max: [f32; 3]) -> ([f32; 3], f32) {
+    let center = [0, 1, 2].map(|i| (min[i] + max[i]) * 0.5);
+    let h = [0, 1, 2].map(|i| (max[i] - min[i]) * 0.5);
+    (center, (h[0]*h[0] + h[1]*h[1] + h[2]*h[2]).sqrt())
+}
+
+/// 4x4行列 (列優先、`matrix[列][行]`。glTFのノード行列と同じ) で各点を変換する
+/// 点はw=1として扱い、結果をwで割る。wが0になる点はInf/NaNになるので `validate_positions` で弾くこと
+pub fn transform_positions(positions: &[[f32; 3]], matrix: &[[f32; 4]; 4]) -> Vec<[f32; 3]> {
+    positions.iter().map(|p| {
+        let h: [f32; 4] = std::array::from_fn(|r| matrix[0][r] * p[0] + matrix[1][r] * p[1] + matrix[2][r] * p[2] + matrix[3][r]);
+        [h[0] / h[3], h[1] / h[3], h[2] / h[3]]
+    }).collect()
+}
+
+/// AABBのminを原点に移し、最も長い軸が [0, 1] になるよう全軸を同じ倍率で縮める
+/// 大きさ0 (全頂点が同じ点) なら移動だけ
+pub fn normalize_positions_to_unit_cube(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let Some((min, max)) = aabb_min_max(positions) else { return Vec::new() };
+    let longest = (0..3).map(|a| max[a] - min[a]).fold(0.0f32, f32::max);
+    let s = if longest > 0.0 { 1.0 / longest } else { 1.0 };
+    positions.iter().map(|p| [0, 1, 2].map(|a| (p[a] - min[a]) * s)).collect()
+}
+
+/// 頂点の重心が原点に来るよう平行移動する
+pub fn center_at_origin(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let n = positions.len() as f64;
+    let mut c = [0.0f64; 3];
+    for p in positions {
+        for a in 0..3 {
+            c[a] += p[a] as f64 / n;
+        }
+    }
+    positions.iter().map(|p| [0, 1, 2].map(|a| (p[a] as f64 - c[a]) as f32)).collect()
+}
+
+/// 範囲rangeをrequired_precision以下の刻みで表すのに必要なビット数 ([1, 32] に丸める)
+/// `precision_of_bits(range, bits) <= required_precision` となる最小のbits
+/// 精度が0以下や非有限なら32
+///
+/// ```
+/// use meshguard_quantize::{bits_needed_for_precision, precision_of_bits};
+/// // 100mを1cm刻み
+/// assert_eq!(bits_needed_for_precision(100.0, 0.01), 14);
+/// assert!(precision_of_bits(100.0, 14) <= 0.01);
+/// assert!(precision_of_bits(100.0, 13) > 0.01);
+/// ```
+pub fn bits_needed_for_precision(range: f32, required_precision: f32) -> u8 {
+    if required_precision <= 0.0 || !required_precision.is_finite() || !range.is_finite() {
+        return 32;
+    }
+    // 2^bits - 1 >= range / precision
+    let steps = range.abs() as f64 / required_precision as f64;
+    ((steps + 1.0).log2().ceil() as i64).clamp(1, 32) as u8
+}
+
+/// bitsビットで範囲rangeを表したときの刻み幅 `range / (2^bits - 1)`
+/// bitsは `bits_needed_for_precision` と同じく [1, 32] に丸める
+pub fn precision_of_bits(range: f32, bits: u8) -> f32 {
+    (range as f64 / ((1u64 << bits.clamp(1, 32)) as f64 - 1.0)) as f32
+}
+
 /// 各頂点の16bit量子化
-pub fn quantize_positions(positions: &[[f32; 3]]) -> QuantizedPositions {
-    let (min, max) = aabb_min_max(positions);
-    let min64 = [min[0] as f64, min[1] as f64, min[2] as f64];
-    let rng64 = [
-        (max[0] as f64 - min64[0]),
-        (max[1] as f64 - min64[1]),
-        (max[2] as f64 - min64[2]),
-    ];
-    let rng64 = [
-        if rng64[0].abs() < 1e-12 { 1e-6 } else { rng64[0] },
-        if rng64[1].abs() < 1e-12 { 1e-6 } else { rng64[1] },
-        if rng64[2].abs() < 1e-12 { 1e-6 } else { rng64[2] },
-    ];
-    let scale = [
-        (rng64[0] / 65535.0) as f32,
-        (rng64[1] / 65535.0) as f32,
-        (rng64[2] / 65535.0) as f32,
-    ];
+/// 頂点がなければEmptyMesh
+pub fn quantize_positions(positions: &[[f32; 3]]) -> Result<QuantizedPositions, MeshGuardError> {
+    quantize_positions_cfg(positions, &QuantizeConfig::new())
+}
+
+/// 各頂点をbitsビットで量子化する (1〜16、範囲外はInvalidInput)
+pub fn quantize_positions_with_bits(positions: &[[f32; 3]], bits: u8) -> Result<QuantizedPositions, MeshGuardError> {
+    quantize_positions_cfg(positions, &QuantizeConfig::new().bits(bits))
+}
+
+/// 呼び出し側で決めたAABBで16bit量子化する
+/// 複数メッシュで `aabb_min_max` の結果を合わせてから使うと継ぎ目が出ない
+/// 座標やAABBにNaN/InfがあればInvalidInput
+pub fn quantize_positions_with_aabb(positions: &[[f32; 3]], aabb_min: [f32; 3], aabb_max: [f32; 3]) -> Result<QuantizedPositions, MeshGuardError> {
+    quantize_positions_cfg(positions, &QuantizeConfig::new().shared_aabb([aabb_min, aabb_max]))
+}
+
+/// 呼び出し側のバッファ `out` に16bitで書き込む (確保しない)
+/// `out` は `positions.len() * 3` 以上必要。返り値は復号用のメタデータだけで `data` は空
+/// 座標やAABBにNaN/InfがあればInvalidInput
+pub fn quantize_positions_into(positions: &[[f32; 3]], min: [f32; 3], max: [f32; 3], out: &mut [i16]) -> Result<QuantizedPositions, MeshGuardError> {
+    validate_positions(positions)?;
+    if out.len() < positions.len() * 3 {
+        return Err(MeshGuardError::LengthMismatch { attribute: "output buffer", expected: positions.len() * 3, got: out.len() });
+    }
+    let config = QuantizeConfig::new();
+    let grid = PositionGrid::new(min, max, config.axis_bits, config.degenerate_epsilon)?;
+    for (o, p) in out.chunks_exact_mut(3).zip(positions) {
+        o.copy_from_slice(&grid.quantize(p));
+    }
+    Ok(grid.finish(Vec::new()))
+}
+
+/// 16bitの値をGPUにどう解釈させるか
+/// Unormは [0, 65535] -> [0, 1]、Snormは [-32767, 32767] -> [-1, 1]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormMode {
+    #[default]
+    Unorm,
+    Snorm,
+}
+
+/// [0, 1] をUNORM16にする
+pub fn unorm16(v: f32) -> u16 {
+    clamp(v * 65535.0, 0.0, 65535.0).round() as u16
+}
+
+pub fn unorm16_to_f32(q: u16) -> f32 {
+    q as f32 / 65535.0
+}
+
+/// [-1, 1] をSNORM16にする: `round(v * 32767).clamp(-32768, 32767)`
+/// -1.0は-32767になり、-32768は範囲の外から丸めたときしか出ない
+/// GPUは-32768も-32767も-1.0として読むので、0を中心に対称な範囲だけを使うことになる
+pub fn snorm16(v: f32) -> i16 {
+    (v * 32767.0).round().clamp(-32768.0, 32767.0) as i16
+}
+
+/// GPUと同じく `max(q / 32767, -1)`
+pub fn snorm16_to_f32(q: i16) -> f32 {
+    (q as f32 / 32767.0).max(-1.0)
+}
+
+/// 座標量子化の設定
+/// `QuantizeConfig::new().bits(12).shared_aabb([min, max])` のように組み立てる
+#[derive(Clone, Debug)]
+pub struct QuantizeConfig {
+    axis_bits: [u8; 3],
+    shared_aabb: Option<[[f32; 3]; 2]>,
+    degenerate_epsilon: f64,
+    position_norm: NormMode,
+    normal_norm: NormMode,
+}
 
+impl Default for QuantizeConfig {
+    fn default() -> Self {
+        QuantizeConfig { axis_bits: [16; 3], shared_aabb: None, degenerate_epsilon: 1e-12, position_norm: NormMode::Snorm, normal_norm: NormMode::Unorm }
+    }
+}
+
+impl QuantizeConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 全軸のビット深度
+    pub fn bits(mut self, bits: u8) -> Self {
+        self.axis_bits = [bits; 3];
+        self
+    }
+
+    /// 軸ごとのビット深度 (1〜16)
+    /// 地形のように高さだけ精度を落としたい場合に使う
+    pub fn axis_bits(mut self, bits: [u8; 3]) -> Self {
+        self.axis_bits = bits;
+        self
+    }
+
+    /// 複数メッシュで共通のAABBを使う ([min, max])
+    pub fn shared_aabb(mut self, aabb: [[f32; 3]; 2]) -> Self {
+        self.shared_aabb = Some(aabb);
+        self
+    }
+
+    /// 幅がこれ未満の軸は潰れているとみなす
+    pub fn degenerate_epsilon(mut self, eps: f64) -> Self {
+        self.degenerate_epsilon = eps;
+        self
+    }
+
+    /// `position_words` の出力形式 (デフォルトはSnorm)
+    /// Unormにすると中心値を引かない格子番号 [0, 2^bits - 1] になり、復号は `w * scale + offset` で済む
+    pub fn position_norm(mut self, mode: NormMode) -> Self {
+        self.position_norm = mode;
+        self
+    }
+
+    /// `encode_normals_oct_cfg` の出力形式 (デフォルトはUnorm)
+    pub fn normal_norm(mut self, mode: NormMode) -> Self {
+        self.normal_norm = mode;
+        self
+    }
+}
+
+/// 量子化座標を頂点バッファに書く16bitワードにする
+/// Snormは格納値 (i16) のビット列そのまま、Unormは格納値に 2^(bits-1) を足した格子番号
+pub fn position_words(q: &QuantizedPositions, config: &QuantizeConfig) -> Vec<u16> {
+    (0..q.data.len()).map(|i| {
+        let v = q.data.get(i);
+        match config.position_norm {
+            NormMode::Snorm => v as u16,
+            NormMode::Unorm => (v as i32 + (1i32 << (q.bits[i % 3] - 1))) as u16,
+        }
+    }).collect()
+}
+
+/// 設定に従って各頂点を量子化する
+/// shared_aabbがなく頂点もなければEmptyMesh、NaN/InfがあればInvalidInput
+pub fn quantize_positions_cfg(positions: &[[f32; 3]], config: &QuantizeConfig) -> Result<QuantizedPositions, MeshGuardError> {
+    validate_positions(positions)?;
+    let (min, max) = match config.shared_aabb {
+        Some([min, max]) => (min, max),
+        None => aabb_min_max(positions).ok_or(MeshGuardError::EmptyMesh)?,
+    };
+    let grid = PositionGrid::new(min, max, config.axis_bits, config.degenerate_epsilon)?;
     let mut data = Vec::with_capacity(positions.len() * 3);
     for p in positions {
+        data.extend_from_slice(&grid.quantize(p));
+    }
+    Ok(grid.finish(data))
+}
+
+/// 事前に決めたAABBで少しずつ量子化する
+/// 全頂点をメモリに載せられない場合に、チャンクごとに `push_chunk` して結果を呼び出し側で書き出す
+pub struct PositionQuantizer {
+    grid: PositionGrid,
+    buf: Vec<i16>,
+}
+
+impl PositionQuantizer {
+    /// bitsは1〜16
+    pub fn new(aabb_min: [f32; 3], aabb_max: [f32; 3], bits: u8) -> Result<Self, MeshGuardError> {
+        let config = QuantizeConfig::new().bits(bits);
+        Ok(PositionQuantizer { grid: PositionGrid::new(aabb_min, aabb_max, config.axis_bits, config.degenerate_epsilon)?, buf: Vec::new() })
+    }
+
+    /// チャンクを量子化してxyz並びで返す
+    /// 返したスライスは次の呼び出しで上書きされる。チャンクにNaN/InfがあればInvalidInput (位置はチャンク内の番号)
+    pub fn push_chunk(&mut self, positions: &[[f32; 3]]) -> Result<&[i16], MeshGuardError> {
+        validate_positions(positions)?;
+        self.buf.clear();
+        for p in positions {
+            self.buf.extend_from_slice(&self.grid.quantize(p));
+        }
+        Ok(&self.buf)
+    }
+
+    /// 復号用の scale (`quantize_positions_with_aabb` と同じ値)
+    pub fn scale(&self) -> [f32; 3] {
+        [0, 1, 2].map(|a| (self.grid.rng64[a] / self.grid.levels[a]) as f32)
+    }
+
+    pub fn offset(&self) -> [f32; 3] {
+        self.grid.min
+    }
+
+    pub fn bits(&self) -> [u8; 3] {
+        self.grid.bits
+    }
+
+    /// 集めた出力からQuantizedPositionsを組み立てる
+    pub fn finish(&self, data: Vec<i16>) -> QuantizedPositions {
+        self.grid.finish(data)
+    }
+}
+
+/// AABBとビット深度から決まる量子化グリッド
+struct PositionGrid {
+    bits: [u8; 3],
+    min: [f32; 3],
+    min64: [f64; 3],
+    rng64: [f64; 3],
+    levels: [f64; 3],
+    half: [i64; 3],
+}
+
+impl PositionGrid {
+    fn new(min: [f32; 3], max: [f32; 3], bits: [u8; 3], eps: f64) -> Result<Self, MeshGuardError> {
+        if let Some(b) = bits.iter().find(|b| !(1..=16).contains(*b)) {
+            return Err(MeshGuardError::InvalidInput(format!("unsupported bit depth: {}", b)));
+        }
+        if min.iter().chain(&max).any(|v| !v.is_finite()) {
+            return Err(MeshGuardError::InvalidInput(format!("non-finite AABB: {:?} - {:?}", min, max)));
+        }
+        let min64 = [min[0] as f64, min[1] as f64, min[2] as f64];
+        let rng64 = [
+            (max[0] as f64 - min64[0]),
+            (max[1] as f64 - min64[1]),
+            (max[2] as f64 - min64[2]),
+        ];
+        let rng64 = rng64.map(|r| if r.abs() < eps { eps.max(1e-6) } else { r });
+        Ok(PositionGrid {
+            bits,
+            min,
+            min64,
+            rng64,
+            levels: bits.map(|b| ((1u32 << b) - 1) as f64),
+            half: bits.map(|b| 1i64 << (b - 1)),
+        })
+    }
+
+    #[inline]
+    fn quantize(&self, p: &[f32; 3]) -> [i16; 3] {
+        let mut q = [0i16; 3];
         for a in 0..3 {
             let pa = p[a] as f64;
-            let t = ((pa - min64[a]) / rng64[a]) * 65535.0;
-            let t = t.clamp(0.0, 65535.0);
-            let q_u16 = t.round() as i64;
-            let q_i32 = (q_u16 - 32768) as i32;
-            let q_i32 = q_i32.clamp(-32768, 32767);
-            data.push(q_i32 as i16);
+            let t = ((pa - self.min64[a]) / self.rng64[a]) * self.levels[a];
+            let t = t.clamp(0.0, self.levels[a]);
+            let q_unsigned = t.round() as i64;
+            let q_signed = (q_unsigned - self.half[a]).clamp(-self.half[a], self.half[a] - 1);
+            q[a] = q_signed as i16;
         }
+        q
+    }
+
+    fn finish(&self, data: Vec<i16>) -> QuantizedPositions {
+        let scale = [
+            (self.rng64[0] / self.levels[0]) as f32,
+            (self.rng64[1] / self.levels[1]) as f32,
+            (self.rng64[2] / self.levels[2]) as f32,
+        ];
+        let data = if self.bits.iter().all(|&b| b <= 8) {
+            PositionData::I8(data.into_iter().map(|v| v as i8).collect())
+        } else {
+            PositionData::I16(data)
+        };
+        QuantizedPositions { data, bits: self.bits, scale, offset: self.min }
     }
-    QuantizedPositions { data, scale, offset: min }
+}
+
+/// rayonで並列に16bit量子化する
+/// 結果は `quantize_positions` と同じ
+#[cfg(feature = "rayon")]
+pub fn quantize_positions_par(positions: &[[f32; 3]]) -> Result<QuantizedPositions, MeshGuardError> {
+    use rayon::prelude::*;
+
+    if positions.is_empty() {
+        return Err(MeshGuardError::EmptyMesh);
+    }
+    validate_positions(positions)?;
+    let (min, max) = positions
+        .par_iter()
+        .fold(
+            || ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+            |(mut min, mut max), p| {
+                for a in 0..3 {
+                    if p[a] < min[a] { min[a] = p[a]; }
+                    if p[a] > max[a] { max[a] = p[a]; }
+                }
+                (min, max)
+            },
+        )
+        .reduce(
+            || ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+            |a, b| ([0, 1, 2].map(|i| a.0[i].min(b.0[i])), [0, 1, 2].map(|i| a.1[i].max(b.1[i]))),
+        );
+    let (min, max) = guard_degenerate(min, max);
+
+    let config = QuantizeConfig::new();
+    let grid = PositionGrid::new(min, max, config.axis_bits, config.degenerate_epsilon).expect("16bit is always supported");
+    let mut data = vec![0i16; positions.len() * 3];
+    data.par_chunks_mut(3)
+        .zip(positions.par_iter())
+        .for_each(|(out, p)| out.copy_from_slice(&grid.quantize(p)));
+    Ok(grid.finish(data))
 }
 
 
@@ -87,32 +560,169 @@ pub fn quantize_positions(positions: &[[f32; 3]]) -> QuantizedPositions {
 pub fn encode_normals_oct(normals: &[[f32; 3]]) -> QuantizedNormalsOct {
     let mut out = Vec::with_capacity(normals.len() * 2);
     for n in normals {
-        let mut x = n[0];
-        let mut y = n[1];
-        let mut z = n[2];
-        let len = (x*x + y*y + z*z).sqrt();
-        if len > 0.0 { x /= len; y /= len; z /= len; } else { x = 0.0; y = 0.0; z = 0.0; } // 正規化
-
-        let denom = x.abs() + y.abs() + z.abs();
-        let mut px = if denom > 0.0 { x / denom } else { 0.0 };
-        let mut py = if denom > 0.0 { y / denom } else { 0.0 };
-        if z < 0.0 {
-            let sx = px.signum();
-            let sy = py.signum();
-            let ax = px.abs();
-            let ay = py.abs();
-            px = (1.0 - ax) * sx;
-            py = (1.0 - ay) * sy;
-        }
-
-        let u = clamp((px * 0.5 + 0.5) * 65535.0, 0.0, 65535.0).round() as u32;
-        let v = clamp((py * 0.5 + 0.5) * 65535.0, 0.0, 65535.0).round() as u32;
-        out.push(u as u16);
-        out.push(v as u16);
+        let [px, py] = oct_fold(*n);
+        out.push(unorm16(px * 0.5 + 0.5));
+        out.push(unorm16(py * 0.5 + 0.5));
     }
     QuantizedNormalsOct { data: out }
 }
 
+/// 設定の `normal_norm` に従ってoct法線を符号化する
+/// Snormでは `data` にi16のビット列が入るので、復号は `decode_normals_oct_cfg` に同じ設定を渡すこと
+pub fn encode_normals_oct_cfg(normals: &[[f32; 3]], config: &QuantizeConfig) -> QuantizedNormalsOct {
+    match config.normal_norm {
+        NormMode::Unorm => encode_normals_oct(normals),
+        NormMode::Snorm => QuantizedNormalsOct { data: normals.iter().flat_map(|&n| oct_fold(n).map(|c| snorm16(c) as u16)).collect() },
+    }
+}
+
+pub fn decode_normals_oct_cfg(q: &QuantizedNormalsOct, config: &QuantizeConfig) -> Vec<[f32; 3]> {
+    match config.normal_norm {
+        NormMode::Unorm => dequantize_normals_oct(q),
+        NormMode::Snorm => q.data.chunks_exact(2).map(|c| oct_unfold(snorm16_to_f32(c[0] as i16), snorm16_to_f32(c[1] as i16))).collect(),
+    }
+}
+
+/// 単位ベクトルを[-1, 1]の正方形へ展開する
+fn oct_fold(n: [f32; 3]) -> [f32; 2] {
+    let [mut x, mut y, mut z] = n;
+    let len = (x*x + y*y + z*z).sqrt();
+    if len > 0.0 { x /= len; y /= len; z /= len; } else { x = 0.0; y = 0.0; z = 0.0; } // 正規化
+
+    let denom = x.abs() + y.abs() + z.abs();
+    let mut px = if denom > 0.0 { x / denom } else { 0.0 };
+    let mut py = if denom > 0.0 { y / denom } else { 0.0 };
+    if z < 0.0 {
+        let sx = px.signum();
+        let sy = py.signum();
+        let ax = px.abs();
+        let ay = py.abs();
+        px = (1.0 - ay) * sx;
+        py = (1.0 - ax) * sy;
+    }
+    [px, py]
+}
+
+/// oct_foldの逆  z < 0 側の折り返しを元に戻して正規化する
+fn oct_unfold(mut x: f32, mut y: f32) -> [f32; 3] {
+    let z = 1.0 - x.abs() - y.abs();
+    if z < 0.0 {
+        let ax = x.abs();
+        let ay = y.abs();
+        x = (1.0 - ay) * x.signum();
+        y = (1.0 - ax) * y.signum();
+    }
+    let len = (x*x + y*y + z*z).sqrt();
+    if len > 0.0 { [x / len, y / len, z / len] } else { [0.0, 0.0, 0.0] }
+}
+
+/// Spheremap Transformで2成分にした法線 (u16 x2)
+#[derive(Clone, Debug)]
+pub struct QuantizedNormalsSpheremap {
+    pub data: Vec<u16>,
+}
+
+/// Spheremap Transform (Sloan 2008)
+/// enc = n.xy / sqrt(8 * n.z + 8) + 0.5
+/// -z方向ほど精度が落ち、n = (0, 0, -1) は円周上の1点に割り当てる
+pub fn encode_normals_spheremap(normals: &[[f32; 3]]) -> QuantizedNormalsSpheremap {
+    let mut data = Vec::with_capacity(normals.len() * 2);
+    for n in normals {
+        let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+        let [x, y, z] = if len > 0.0 { [n[0] / len, n[1] / len, n[2] / len] } else { [0.0, 0.0, 1.0] };
+        let denom = (8.0 * z + 8.0).sqrt();
+        let (u, v) = if denom > 1e-6 { (x / denom + 0.5, y / denom + 0.5) } else { (1.0, 0.5) };
+        data.push((clamp(u, 0.0, 1.0) * 65535.0).round() as u16);
+        data.push((clamp(v, 0.0, 1.0) * 65535.0).round() as u16);
+    }
+    QuantizedNormalsSpheremap { data }
+}
+
+pub fn decode_normals_spheremap(q: &QuantizedNormalsSpheremap) -> Vec<[f32; 3]> {
+    let mut out = Vec::with_capacity(q.data.len() / 2);
+    for i in 0..(q.data.len() / 2) {
+        let fx = q.data[i*2] as f32 / 65535.0 * 4.0 - 2.0;
+        let fy = q.data[i*2 + 1] as f32 / 65535.0 * 4.0 - 2.0;
+        let f = (fx*fx + fy*fy).min(4.0);
+        let g = (1.0 - f / 4.0).sqrt();
+        let [x, y, z] = [fx * g, fy * g, 1.0 - f / 2.0];
+        let len = (x*x + y*y + z*z).sqrt();
+        out.push([x / len, y / len, z / len]);
+    }
+    out
+}
+
+/// A2B10G10R10_SNORM_PACK32形式の法線
+/// 下位からR(x), G(y), B(z)の各10bit、上位2bitのwは0
+#[derive(Clone, Debug)]
+pub struct Packed10_10_10_2Normals {
+    pub data: Vec<u32>,
+}
+
+pub fn encode_normals_10_10_10_2(normals: &[[f32; 3]]) -> Packed10_10_10_2Normals {
+    let mut data = Vec::with_capacity(normals.len());
+    for n in normals {
+        let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+        let mut word = 0u32;
+        for (a, &c) in n.iter().enumerate() {
+            let v = if len > 0.0 { c / len } else { 0.0 };
+            let q = (clamp(v, -1.0, 1.0) * 511.0).round() as i32;
+            word |= ((q as u32) & 0x3FF) << (a * 10);
+        }
+        data.push(word);
+    }
+    Packed10_10_10_2Normals { data }
+}
+
+pub fn decode_normals_10_10_10_2(q: &Packed10_10_10_2Normals) -> Vec<[f32; 3]> {
+    q.data.iter().map(|&word| decode_normal_10_10_10_2(word)).collect()
+}
+
+/// 1頂点分の10-10-10-2法線を戻す
+pub fn decode_normal_10_10_10_2(word: u32) -> [f32; 3] {
+    [0, 1, 2].map(|a| {
+        // 10bitの符号拡張
+        let v = (((word >> (a * 10)) & 0x3FF) << 22) as i32 >> 22;
+        (v as f32 / 511.0).max(-1.0)
+    })
+}
+
+/// 法線マップ用の接線 (xyz + w: 従法線の向き±1)
+/// xyzはoctでSNORM16の2成分、wは符号だけ持つ
+#[derive(Clone, Debug)]
+pub struct QuantizedTangents {
+    pub data: Vec<i16>,
+    pub handedness: Vec<i8>,
+}
+
+pub fn quantize_tangents(tangents: &[[f32; 4]]) -> QuantizedTangents {
+    let mut data = Vec::with_capacity(tangents.len() * 2);
+    let mut handedness = Vec::with_capacity(tangents.len());
+    for t in tangents {
+        let [px, py] = oct_fold([t[0], t[1], t[2]]);
+        data.push((clamp(px, -1.0, 1.0) * 32767.0).round() as i16);
+        data.push((clamp(py, -1.0, 1.0) * 32767.0).round() as i16);
+        handedness.push(if t[3] < 0.0 { -1 } else { 1 });
+    }
+    QuantizedTangents { data, handedness }
+}
+
+pub fn dequantize_tangents(q: &QuantizedTangents) -> Vec<[f32; 4]> {
+    let mut out = Vec::with_capacity(q.handedness.len());
+    for (i, &w) in q.handedness.iter().enumerate() {
+        let x = (q.data[i*2] as f32 / 32767.0).max(-1.0);
+        let y = (q.data[i*2 + 1] as f32 / 32767.0).max(-1.0);
+        let [tx, ty, tz] = oct_unfold(x, y);
+        out.push([tx, ty, tz, w as f32]);
+    }
+    out
+}
+
+/// ライトマップ用の2つ目のUV。量子化は `quantize_uvs` と同じ
+pub fn quantize_uvs2(uvs: &[[f32; 2]]) -> QuantizedUVs {
+    quantize_uvs(uvs)
+}
+
 pub fn quantize_uvs(uvs: &[[f32; 2]]) -> QuantizedUVs {
     let mut out = Vec::with_capacity(uvs.len() * 2);
     for uv in uvs {
@@ -126,23 +736,398 @@ pub fn quantize_uvs(uvs: &[[f32; 2]]) -> QuantizedUVs {
     QuantizedUVs { data: out }
 }
 
+/// `quantize_attribute_f32` の結果。SNORMはi16、UNORMは16bitで65535まで使うのでu16
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeData {
+    Snorm(Vec<i16>),
+    Unorm(Vec<u16>),
+}
+
+impl AttributeData {
+    pub fn len(&self) -> usize {
+        match self {
+            AttributeData::Snorm(v) => v.len(),
+            AttributeData::Unorm(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// N成分の属性を成分ごとにbitsビットの整数にする (成分順に並べる)
+/// signedなら [-1, 1] を ±(2^(bits-1) - 1) のSnorm、そうでなければ [0, 1] を 0〜2^bits - 1 のUnormにし、範囲外はクランプする
+/// bitsは1 (signedは2) 〜16に丸める
+pub fn quantize_attribute_f32<const N: usize>(data: &[[f32; N]], bits: u8, signed: bool) -> AttributeData {
+    let bits = bits.clamp(if signed { 2 } else { 1 }, 16);
+    let comps = data.iter().flatten();
+    if signed {
+        let max = ((1i32 << (bits - 1)) - 1) as f32;
+        AttributeData::Snorm(comps.map(|&c| (clamp(c, -1.0, 1.0) * max).round() as i16).collect())
+    } else {
+        let max = ((1u32 << bits) - 1) as f32;
+        AttributeData::Unorm(comps.map(|&c| (clamp(c, 0.0, 1.0) * max).round() as u16).collect())
+    }
+}
+
+/// 頂点カラー (RGBA8, 1頂点4バイト)
+#[derive(Clone, Debug)]
+pub struct QuantizedColors {
+    pub data: Vec<u8>,
+}
+
+/// [0, 1]のRGBAをUNORM8にする
+pub fn quantize_vertex_colors(colors: &[[f32; 4]]) -> QuantizedColors {
+    let mut data = Vec::with_capacity(colors.len() * 4);
+    for c in colors {
+        for ch in c {
+            data.push((clamp(*ch, 0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    QuantizedColors { data }
+}
+
+pub fn dequantize_vertex_colors(q: &QuantizedColors) -> Vec<[f32; 4]> {
+    q.data
+        .chunks_exact(4)
+        .map(|c| [c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0, c[3] as f32 / 255.0])
+        .collect()
+}
+
+/// RGB9E5で表せる最大値 (511/512 * 2^16)
+pub const RGB9E5_MAX: f32 = 65408.0;
+
+/// HDRカラーを9bit仮数x3 + 5bit共有指数に詰める (GL_RGB9_E5と同じビット配置)
+/// 負の値とNaNは0、RGB9E5_MAXを超える値はRGB9E5_MAXにする
+pub fn encode_rgb9e5(colors: &[[f32; 3]]) -> Vec<u32> {
+    const BIAS: i32 = 15;
+    const MANTISSA: i32 = 9;
+    colors.iter().map(|c| {
+        let c = c.map(|v| if v > 0.0 { v.min(RGB9E5_MAX) as f64 } else { 0.0 });
+        let max = c[0].max(c[1]).max(c[2]);
+        let mut exp = (max.log2().floor() as i32).max(-BIAS - 1) + 1 + BIAS;
+        // 丸めで仮数が512に繰り上がったら指数を1つ上げる
+        if (max / 2f64.powi(exp - BIAS - MANTISSA) + 0.5).floor() as u32 == 1 << MANTISSA {
+            exp += 1;
+        }
+        let unit = 2f64.powi(exp - BIAS - MANTISSA);
+        let [r, g, b] = c.map(|v| ((v / unit + 0.5).floor() as u32).min(511));
+        r | g << 9 | b << 18 | (exp as u32) << 27
+    }).collect()
+}
+
+pub fn decode_rgb9e5(encoded: &[u32]) -> Vec<[f32; 3]> {
+    encoded.iter().map(|&w| {
+        let unit = 2f32.powi((w >> 27) as i32 - 15 - 9);
+        [0, 9, 18].map(|s| ((w >> s) & 0x1FF) as f32 * unit)
+    }).collect()
+}
+
+/// 頂点ごとのベイク済みAO (UNORM8, 1頂点1バイト)
+#[derive(Clone, Debug)]
+pub struct QuantizedAO {
+    pub data: Vec<u8>,
+}
+
+/// [0, 1]のAOをUNORM8にする
+pub fn quantize_ao(ao: &[f32]) -> QuantizedAO {
+    QuantizedAO { data: ao.iter().map(|&v| (clamp(v, 0.0, 1.0) * 255.0).round() as u8).collect() }
+}
+
+pub fn dequantize_ao(q: &QuantizedAO) -> Vec<f32> {
+    q.data.iter().map(|&b| b as f32 / 255.0).collect()
+}
+
+/// スキニング用ウェイト (UNORM8 x4)
+/// 非ゼロの頂点はバイトの合計が必ず255になる
+#[derive(Clone, Debug)]
+pub struct QuantizedJointWeights {
+    pub data: Vec<u8>,
+}
+
+/// ジョイント番号 (最大値が255以下ならu8、それ以外はu16)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuantizedJointIndices {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+}
+
+impl QuantizedJointIndices {
+    pub fn len(&self) -> usize {
+        match self {
+            QuantizedJointIndices::U8(v) => v.len(),
+            QuantizedJointIndices::U16(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> u16 {
+        match self {
+            QuantizedJointIndices::U8(v) => v[i] as u16,
+            QuantizedJointIndices::U16(v) => v[i],
+        }
+    }
+}
+
+/// これ未満の合計はウェイトなしとみなす
+pub const JOINT_WEIGHT_EPSILON: f32 = 1e-6;
+
+/// 頂点ごとにウェイトの合計を1.0にする
+/// 負のウェイトは0扱い、合計が `JOINT_WEIGHT_EPSILON` 未満なら全成分0
+pub fn normalize_joint_weights(weights: &mut [[f32; 4]]) {
+    for w in weights {
+        let c = w.map(|v| v.max(0.0));
+        let sum: f32 = c.iter().sum();
+        *w = if sum < JOINT_WEIGHT_EPSILON { [0.0; 4] } else { c.map(|v| v / sum) };
+    }
+}
+
+/// glTFのWEIGHTS_0は合計1.0が必須なので、正規化してからUNORM8に丸める
+/// 丸めで合計が255からずれた分は最大成分で吸収する
+/// 正規化は `normalize_joint_weights` と同じ
+pub fn quantize_joint_weights(weights: &[[f32; 4]]) -> QuantizedJointWeights {
+    let mut data = Vec::with_capacity(weights.len() * 4);
+    for &w in weights {
+        let mut w = [w];
+        normalize_joint_weights(&mut w);
+        if w[0] == [0.0; 4] {
+            data.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        let mut q = w[0].map(|v| (v * 255.0).round() as i32);
+        let diff = 255 - q.iter().sum::<i32>();
+        let largest = (0..4).fold(0, |m, i| if q[i] > q[m] { i } else { m });
+        q[largest] += diff;
+        data.extend(q.map(|v| v.clamp(0, 255) as u8));
+    }
+    QuantizedJointWeights { data }
+}
+
+/// 各頂点のバイト合計が255 (ウェイトなしの頂点は0) になっているか
+pub fn validate_joint_weights(q: &QuantizedJointWeights) -> Result<(), MeshGuardError> {
+    if !q.data.len().is_multiple_of(4) {
+        return Err(MeshGuardError::LengthMismatch { attribute: "joint weights", expected: q.data.len().next_multiple_of(4), got: q.data.len() });
+    }
+    for (i, c) in q.data.chunks_exact(4).enumerate() {
+        let sum: u32 = c.iter().map(|&b| b as u32).sum();
+        if sum != 255 && sum != 0 {
+            return Err(MeshGuardError::InvalidInput(format!("joint weights of vertex {} sum to {}, not 255", i, sum)));
+        }
+    }
+    Ok(())
+}
+
+pub fn dequantize_joint_weights(q: &QuantizedJointWeights) -> Vec<[f32; 4]> {
+    q.data
+        .chunks_exact(4)
+        .map(|c| [c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0, c[3] as f32 / 255.0])
+        .collect()
+}
+
+pub fn quantize_joint_indices(indices: &[[u16; 4]]) -> QuantizedJointIndices {
+    let flat = indices.as_flattened();
+    if flat.iter().all(|&j| j <= u8::MAX as u16) {
+        QuantizedJointIndices::U8(flat.iter().map(|&j| j as u8).collect())
+    } else {
+        QuantizedJointIndices::U16(flat.to_vec())
+    }
+}
+
 /// テスト復号用
 pub fn dequantize_positions(q: &QuantizedPositions) -> Vec<[f32; 3]> {
-    let scale64 = [q.scale[0] as f64, q.scale[1] as f64, q.scale[2] as f64];
-    let off64   = [q.offset[0] as f64, q.offset[1] as f64, q.offset[2] as f64];
     let mut out = Vec::with_capacity(q.data.len() / 3);
     for i in 0..(q.data.len() / 3) {
-        let xq = q.data[i*3 + 0] as i32 as f64;
-        let yq = q.data[i*3 + 1] as i32 as f64;
-        let zq = q.data[i*3 + 2] as i32 as f64;
-        let x = ((xq + 32768.0) * scale64[0]) + off64[0];
-        let y = ((yq + 32768.0) * scale64[1]) + off64[1];
-        let z = ((zq + 32768.0) * scale64[2]) + off64[2];
-        out.push([x as f32, y as f32, z as f32]);
+        let v = [q.data.get(i*3), q.data.get(i*3 + 1), q.data.get(i*3 + 2)];
+        out.push(dequantize_position(v, q.bits, q.scale, q.offset));
     }
     out
 }
 
+/// 1頂点分の量子化座標を戻す (PackedMeshのpos_bits/pos_scale/pos_offsetをそのまま渡せる)
+pub fn dequantize_position(v: [i16; 3], bits: [u8; 3], scale: [f32; 3], offset: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|a| {
+        let half = (1i64 << (bits[a] - 1)) as f64;
+        ((v[a] as f64 + half) * scale[a] as f64 + offset[a] as f64) as f32
+    })
+}
+
+/// モーフターゲット (ブレンドシェイプ) の頂点ごとの差分
+/// 差分のAABBで16bit量子化する。復号は `dequantize_position` と同じ式 (bitsは16)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizedMorphDeltas {
+    pub data: Vec<i16>,
+    pub delta_scale: [f32; 3],
+    pub delta_offset: [f32; 3],
+}
+
+/// target - base を量子化する (頂点順はbaseと同じ)
+pub fn quantize_morph_deltas(base: &[[f32; 3]], target: &[[f32; 3]]) -> Result<QuantizedMorphDeltas, MeshGuardError> {
+    if target.len() != base.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "morph target", expected: base.len(), got: target.len() });
+    }
+    let deltas: Vec<[f32; 3]> = base.iter().zip(target).map(|(b, t)| [t[0] - b[0], t[1] - b[1], t[2] - b[2]]).collect();
+    let q = quantize_positions(&deltas)?;
+    Ok(QuantizedMorphDeltas { data: (0..q.data.len()).map(|i| q.data.get(i)).collect(), delta_scale: q.scale, delta_offset: q.offset })
+}
+
+pub fn dequantize_morph_deltas(q: &QuantizedMorphDeltas) -> Vec<[f32; 3]> {
+    q.data.chunks_exact(3).map(|d| dequantize_position([d[0], d[1], d[2]], [16; 3], q.delta_scale, q.delta_offset)).collect()
+}
+
+pub fn dequantize_uvs(q: &QuantizedUVs) -> Vec<[f32; 2]> {
+    let mut out = Vec::with_capacity(q.data.len() / 2);
+    for i in 0..(q.data.len() / 2) {
+        let u = q.data[i*2] as f32 / 65535.0;
+        let v = q.data[i*2 + 1] as f32 / 65535.0;
+        out.push([u, v]);
+    }
+    out
+}
+
+/// 量子化誤差 (軸ごとの最大絶対誤差とRMS誤差)
+#[derive(Clone, Copy, Debug)]
+pub struct QuantizationError {
+    pub max: [f32; 3],
+    pub rms: [f32; 3],
+}
+
+/// 復号して元の座標との誤差を測る
+pub fn quantization_error(original: &[[f32; 3]], q: &QuantizedPositions) -> Result<QuantizationError, MeshGuardError> {
+    let restored = dequantize_positions(q);
+    if restored.len() != original.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "position", expected: restored.len(), got: original.len() });
+    }
+    let mut max = [0.0f64; 3];
+    let mut sum_sq = [0.0f64; 3];
+    for (p, r) in original.iter().zip(&restored) {
+        for a in 0..3 {
+            let d = (p[a] as f64 - r[a] as f64).abs();
+            if d > max[a] { max[a] = d; }
+            sum_sq[a] += d * d;
+        }
+    }
+    let n = original.len().max(1) as f64;
+    Ok(QuantizationError {
+        max: max.map(|m| m as f32),
+        rms: sum_sq.map(|s| (s / n).sqrt() as f32),
+    })
+}
+
+/// 座標のPSNR (dB)。peakはAABBの対角線長、誤差は頂点ごとの距離のRMS
+/// 誤差が0なら無限大。1ビット増えるごとにおよそ6dB上がる
+pub fn psnr_positions(original: &[[f32; 3]], q: &QuantizedPositions) -> Result<f32, MeshGuardError> {
+    let restored = dequantize_positions(q);
+    if restored.len() != original.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "position", expected: restored.len(), got: original.len() });
+    }
+    let (min, max) = aabb_min_max(original).ok_or(MeshGuardError::EmptyMesh)?;
+    let peak = (0..3).map(|a| (max[a] as f64 - min[a] as f64).powi(2)).sum::<f64>().sqrt();
+    let sum_sq: f64 = original.iter().zip(&restored)
+        .map(|(p, r)| (0..3).map(|a| (p[a] as f64 - r[a] as f64).powi(2)).sum::<f64>())
+        .sum();
+    let rms = (sum_sq / original.len() as f64).sqrt();
+    if rms == 0.0 {
+        return Ok(f32::INFINITY);
+    }
+    Ok((20.0 * (peak / rms).log10()) as f32)
+}
+
+/// 理論上の最大誤差 (ワールド単位)
+/// 各軸半ステップずれた場合の距離
+pub fn max_world_error(q: &QuantizedPositions) -> f32 {
+    let h = q.scale.map(|s| 0.5 * s as f64);
+    (h[0]*h[0] + h[1]*h[1] + h[2]*h[2]).sqrt() as f32
+}
+
+/// oct法線の復号結果と元の法線がなす角の最大値 (度)。長さ0の法線は数えない
+pub fn max_normal_angle_error(original: &[[f32; 3]], q: &QuantizedNormalsOct) -> Result<f32, MeshGuardError> {
+    let restored = dequantize_normals_oct(q);
+    if restored.len() != original.len() {
+        return Err(MeshGuardError::LengthMismatch { attribute: "normal", expected: restored.len(), got: original.len() });
+    }
+    let mut worst = 0.0f64;
+    for (n, r) in original.iter().zip(&restored) {
+        let len = n.iter().map(|&c| c as f64 * c as f64).sum::<f64>().sqrt();
+        if len == 0.0 { continue; }
+        let dot = (0..3).map(|a| n[a] as f64 * r[a] as f64).sum::<f64>() / len;
+        worst = worst.max(dot.clamp(-1.0, 1.0).acos());
+    }
+    Ok(worst.to_degrees() as f32)
+}
+
+/// Octahedral Encodingの復号
+/// [-1, 1]の平面に戻してから z < 0 側の折り返しを元に戻す
+pub fn dequantize_normals_oct(q: &QuantizedNormalsOct) -> Vec<[f32; 3]> {
+    let mut out = Vec::with_capacity(q.data.len() / 2);
+    for i in 0..(q.data.len() / 2) {
+        out.push(decode_normal_oct(q.data[i*2], q.data[i*2 + 1]));
+    }
+    out
+}
+
+/// 1頂点分のoct法線を戻す
+pub fn decode_normal_oct(u: u16, v: u16) -> [f32; 3] {
+    let x = u as f32 / 65535.0 * 2.0 - 1.0;
+    let y = v as f32 / 65535.0 * 2.0 - 1.0;
+    oct_unfold(x, y)
+}
+
+/// 透かしを入れる成分の順序 (keyで決まる擬似乱数順)
+fn watermark_slots(len: usize, bits: usize, key: u64) -> Vec<usize> {
+    let mut slots: Vec<usize> = (0..len).collect();
+    let mut s = if key == 0 { 0x9E3779B97F4A7C15 } else { key };
+    // 先頭bits個だけ決まればよいので部分Fisher-Yates
+    for i in 0..bits.min(len) {
+        s ^= s >> 12;
+        s ^= s << 25;
+        s ^= s >> 27;
+        let r = i + (s.wrapping_mul(0x2545F4914F6CDD1D) % (len - i) as u64) as usize;
+        slots.swap(i, r);
+    }
+    slots.truncate(bits);
+    slots
+}
+
+/// payloadのビットを量子化座標の最下位ビットに埋め込む
+/// どの成分を使うかはkeyで決まるので、取り出しにも同じkeyが必要
+/// LSBを書き換えても各軸のビット深度の範囲からは出ない
+pub fn watermark_positions(q: &mut QuantizedPositions, payload: &[u8], key: u64) -> Result<(), MeshGuardError> {
+    let bits = payload.len() * 8;
+    check_watermark_fits(bits, q)?;
+    for (b, slot) in watermark_slots(q.data.len(), bits, key).into_iter().enumerate() {
+        let bit = (payload[b / 8] >> (b % 8)) & 1;
+        match &mut q.data {
+            PositionData::I8(v) => v[slot] = (v[slot] & !1) | bit as i8,
+            PositionData::I16(v) => v[slot] = (v[slot] & !1) | bit as i16,
+        }
+    }
+    Ok(())
+}
+
+/// `watermark_positions` で埋めたpayloadを取り出す
+pub fn extract_watermark(q: &QuantizedPositions, payload_len: usize, key: u64) -> Result<Vec<u8>, MeshGuardError> {
+    let bits = payload_len * 8;
+    check_watermark_fits(bits, q)?;
+    let mut out = vec![0u8; payload_len];
+    for (b, slot) in watermark_slots(q.data.len(), bits, key).into_iter().enumerate() {
+        out[b / 8] |= ((q.data.get(slot) & 1) as u8) << (b % 8);
+    }
+    Ok(out)
+}
+
+fn check_watermark_fits(bits: usize, q: &QuantizedPositions) -> Result<(), MeshGuardError> {
+    if bits > q.data.len() {
+        return Err(MeshGuardError::InvalidInput(format!("payload of {} bits does not fit in {} components", bits, q.data.len())));
+    }
+    Ok(())
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -151,7 +1136,7 @@ mod tests {
     #[test]
     fn roundtrip_positions() {
         let src = vec![[0.0, 1.0, 2.0], [10.0, 20.0, 30.0], [-1.0, 0.5, 100.0]];
-        let q = quantize_positions(&src);
+        let q = quantize_positions(&src).unwrap();
         let restored = dequantize_positions(&q);
         assert_eq!(restored.len(), src.len());
         for (i, p) in src.iter().enumerate() {
@@ -162,4 +1147,545 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn aabb_matches_scalar_for_all_remainders() {
+        for n in 1..20 {
+            let src: Vec<[f32; 3]> = (0..n).map(|i| { let t = i as f32; [t.sin() * 3.0, -t, (t * 0.7).cos() + t * 0.1] }).collect();
+            let (min, max) = aabb_min_max(&src).unwrap();
+            let (smin, smax) = aabb_scalar(&src);
+            assert_eq!(min, smin);
+            for a in 0..3 {
+                assert!(max[a] == smax[a] || (smax[a] - smin[a]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_positions_are_rejected() {
+        assert_eq!(aabb_min_max(&[]), None);
+        assert_eq!(quantize_positions(&[]).unwrap_err(), MeshGuardError::EmptyMesh);
+        assert_eq!(quantize_positions_with_bits(&[], 12).unwrap_err(), MeshGuardError::EmptyMesh);
+    }
+
+    #[test]
+    fn aabb_union_covers_all_inputs() {
+        let a = aabb_min_max(&[[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]]).unwrap();
+        let b = aabb_min_max(&[[-1.0, 1.0, 0.5], [0.5, 5.0, 1.0]]).unwrap();
+        let c = ([2.0, -3.0, 0.0], [2.5, 0.0, 0.0]);
+        assert_eq!(aabb_union(a.0, a.1, b.0, b.1), ([-1.0, 0.0, 0.0], [1.0, 5.0, 3.0]));
+        assert_eq!(aabb_union_all(&[a, b, c]), ([-1.0, -3.0, 0.0], [2.5, 5.0, 3.0]));
+        assert_eq!(aabb_union_all(&[c]), c);
+        let (emin, emax) = aabb_union_all(&[]);
+        assert_eq!(aabb_union(emin, emax, c.0, c.1), c);
+    }
+
+    #[test]
+    fn morph_deltas_roundtrip() {
+        let base: Vec<[f32; 3]> = (0..30).map(|i| { let t = i as f32; [t, t.sin(), 0.0] }).collect();
+        let target: Vec<[f32; 3]> = base.iter().enumerate().map(|(i, p)| [p[0] + 0.01 * i as f32, p[1] - 0.5, p[2]]).collect();
+        let q = quantize_morph_deltas(&base, &target).unwrap();
+        assert_eq!(q.data.len(), 90);
+        for (i, d) in dequantize_morph_deltas(&q).iter().enumerate() {
+            let want = [target[i][0] - base[i][0], target[i][1] - base[i][1], 0.0];
+            assert!((0..3).all(|a| (d[a] - want[a]).abs() < 1e-5), "{}: {:?} vs {:?}", i, d, want);
+        }
+        assert!(matches!(quantize_morph_deltas(&base, &target[1..]), Err(MeshGuardError::LengthMismatch { .. })));
+    }
+
+    #[test]
+    fn non_finite_positions_are_rejected() {
+        let mut src = vec![[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]];
+        assert!(validate_positions(&src).is_ok());
+        src[1][2] = f32::NAN;
+        assert_eq!(validate_positions(&src).unwrap_err(), MeshGuardError::InvalidInput("NaN in position[1][2]".into()));
+        assert_eq!(quantize_positions(&src).unwrap_err(), MeshGuardError::InvalidInput("NaN in position[1][2]".into()));
+        src[1][2] = 5.0;
+        src[0][0] = f32::NEG_INFINITY;
+        assert_eq!(quantize_positions(&src).unwrap_err(), MeshGuardError::InvalidInput("Inf in position[0][0]".into()));
+    }
+
+    #[test]
+    fn bits_for_precision() {
+        assert_eq!(bits_needed_for_precision(100.0, 0.01), 14);
+        // ちょうど割り切れる境界: 255段階なら8ビットで足りる
+        assert_eq!(bits_needed_for_precision(255.0, 1.0), 8);
+        assert_eq!(bits_needed_for_precision(256.0, 1.0), 9);
+        assert_eq!(bits_needed_for_precision(0.0, 1.0), 1);
+        assert_eq!(bits_needed_for_precision(1e9, 1e-9), 32);
+        assert_eq!(bits_needed_for_precision(1.0, 0.0), 32);
+        assert_eq!(precision_of_bits(255.0, 8), 1.0);
+        // 0ビットは1ビットとして扱い、0除算でInfにしない
+        assert_eq!(precision_of_bits(10.0, 0), 10.0);
+        assert_eq!(precision_of_bits(10.0, 64), precision_of_bits(10.0, 32));
+        for bits in 1..=16 {
+            let p = precision_of_bits(10.0, bits);
+            assert_eq!(bits_needed_for_precision(10.0, p * 1.0001), bits);
+        }
+    }
+
+    #[test]
+    fn roundtrip_positions_with_bits() {
+        let src: Vec<[f32; 3]> = (0..50).map(|i| { let t = i as f32; [t * 0.37, (t * 1.3).sin() * 5.0, -t * t * 0.01] }).collect();
+        assert!(quantize_positions_with_bits(&src, 0).is_err());
+        assert!(quantize_positions_with_bits(&src, 17).is_err());
+        for bits in [8u8, 10, 12, 16] {
+            let q = quantize_positions_with_bits(&src, bits).unwrap();
+            assert_eq!(q.bits, [bits; 3]);
+            assert_eq!(matches!(q.data, PositionData::I8(_)), bits == 8);
+            let restored = dequantize_positions(&q);
+            for (p, r) in src.iter().zip(&restored) {
+                for a in 0..3 {
+                    let diff = (p[a] - r[a]).abs();
+                    let tol = 0.5 * q.scale[a] + 1e-5;
+                    assert!(diff <= tol, "{}bit: diff {} exceeds tol {}", bits, diff, tol);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn config_defaults_match_quantize_positions() {
+        let src = vec![[0.0, 1.0, 2.0], [10.0, 20.0, 30.0], [-1.0, 0.5, 100.0]];
+        let a = quantize_positions(&src).unwrap();
+        let b = quantize_positions_cfg(&src, &QuantizeConfig::new()).unwrap();
+        assert_eq!(a.bits, b.bits);
+        assert_eq!(a.scale, b.scale);
+        assert_eq!(a.offset, b.offset);
+        assert_eq!(a.data.len(), b.data.len());
+        assert!((0..a.data.len()).all(|i| a.data.get(i) == b.data.get(i)));
+    }
+
+    #[test]
+    fn config_shared_aabb_and_axis_bits() {
+        let body = vec![[0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+        let hat  = vec![[0.2, 1.8, 0.2], [0.8, 2.5, 0.8]];
+        let (min_a, max_a) = aabb_min_max(&body).unwrap();
+        let (min_b, max_b) = aabb_min_max(&hat).unwrap();
+        let min = [0, 1, 2].map(|a| min_a[a].min(min_b[a]));
+        let max = [0, 1, 2].map(|a| max_a[a].max(max_b[a]));
+        let cfg = QuantizeConfig::new().shared_aabb([min, max]).axis_bits([16, 8, 16]);
+        let qa = quantize_positions_cfg(&body, &cfg).unwrap();
+        let qb = quantize_positions_cfg(&hat, &cfg).unwrap();
+        assert_eq!(qa.scale, qb.scale);
+        assert_eq!(qa.offset, qb.offset);
+        assert_eq!(qa.bits, [16, 8, 16]);
+        assert!((qa.scale[1] - 2.5 / 255.0).abs() < 1e-6);
+        for (p, r) in hat.iter().zip(dequantize_positions(&qb)) {
+            for a in 0..3 {
+                assert!((p[a] - r[a]).abs() <= 0.5 * qb.scale[a] + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn error_metrics_within_bounds() {
+        // 一様に散らばった点ならRMSはステップ幅/sqrt(12)に近づく
+        let src: Vec<[f32; 3]> = (0..20000).map(|i| {
+            let t = i as f32;
+            [(t * 0.618_034).fract(), (t * 0.414_213_57).fract() * 10.0, (t * 0.732_050_8).fract() * 3.0]
+        }).collect();
+        let q = quantize_positions_with_bits(&src, 8).unwrap();
+        let err = quantization_error(&src, &q).unwrap();
+        for a in 0..3 {
+            assert!(err.max[a] <= 0.5 * q.scale[a] + 1e-5);
+            let expected_rms = q.scale[a] / 12f32.sqrt();
+            assert!((err.rms[a] - expected_rms).abs() < 0.1 * expected_rms, "rms {} vs {}", err.rms[a], expected_rms);
+        }
+        let bound = max_world_error(&q);
+        let worst = (err.max[0].powi(2) + err.max[1].powi(2) + err.max[2].powi(2)).sqrt();
+        assert!(worst <= bound + 1e-5);
+        assert!((bound - 0.5 * (q.scale[0].powi(2) + q.scale[1].powi(2) + q.scale[2].powi(2)).sqrt()).abs() < 1e-7);
+    }
+
+    #[test]
+    fn psnr_drops_with_fewer_bits() {
+        let src: Vec<[f32; 3]> = (0..5000).map(|i| {
+            let t = i as f32;
+            [(t * 0.618_034).fract() * 4.0, (t * 0.414_213_57).fract() * 10.0, (t * 0.732_050_8).fract() * 3.0]
+        }).collect();
+        let psnr: Vec<f32> = [8, 10, 12, 14, 16].iter()
+            .map(|&b| psnr_positions(&src, &quantize_positions_with_bits(&src, b).unwrap()).unwrap())
+            .collect();
+        assert!(psnr[4] > 60.0, "{:?}", psnr);
+        // 2ビットごとに約12dB
+        for w in psnr.windows(2) {
+            assert!((w[1] - w[0] - 12.04).abs() < 1.0, "{:?}", psnr);
+        }
+        let q = quantize_positions(&src).unwrap();
+        assert!(psnr_positions(&src[1..], &q).is_err());
+        let flat = [[1.0, 2.0, 3.0]; 4];
+        assert_eq!(psnr_positions(&flat, &quantize_positions(&flat).unwrap()).unwrap(), f32::INFINITY);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_matches_sequential() {
+        let src: Vec<[f32; 3]> = (0..10_000).map(|i| { let t = i as f32; [t.sin(), t * 0.01, (t * 0.3).cos() * 4.0] }).collect();
+        let a = quantize_positions(&src).unwrap();
+        let b = quantize_positions_par(&src).unwrap();
+        assert_eq!(a.scale, b.scale);
+        assert_eq!(a.offset, b.offset);
+        assert!((0..a.data.len()).all(|i| a.data.get(i) == b.data.get(i)));
+    }
+
+    #[test]
+    fn roundtrip_normals_oct() {
+        let src = vec![
+            [0.0, 0.0, 1.0], [0.0, 0.0, -1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0],
+            [0.577, 0.577, 0.577], [-0.3, 0.4, -0.866], [0.8, -0.6, -0.01], [-0.5, -0.5, 0.7],
+        ];
+        let q = encode_normals_oct(&src);
+        let restored = dequantize_normals_oct(&q);
+        assert_eq!(restored.len(), src.len());
+        for (n, r) in src.iter().zip(&restored) {
+            let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+            let dot = (n[0]*r[0] + n[1]*r[1] + n[2]*r[2]) / len;
+            let angle = dot.clamp(-1.0, 1.0).acos();
+            assert!(angle < 1e-3, "angle {} too large for {:?} -> {:?}", angle, n, r);
+        }
+        let worst = max_normal_angle_error(&src, &q).unwrap();
+        assert!(worst > 0.0 && worst < 1e-3f32.to_degrees(), "{}", worst);
+        assert!(max_normal_angle_error(&src[1..], &q).is_err());
+    }
+
+    #[test]
+    fn norm_modes() {
+        assert_eq!((snorm16(1.0), snorm16(-1.0), snorm16(0.0), snorm16(-1.1)), (32767, -32767, 0, -32768));
+        assert_eq!((snorm16_to_f32(-32768), snorm16_to_f32(-32767)), (-1.0, -1.0));
+        assert_eq!((unorm16(0.0), unorm16(1.0), unorm16(2.0)), (0, 65535, 65535));
+        assert_eq!(unorm16_to_f32(65535), 1.0);
+
+        let src = [[0.0, 0.0, 1.0], [0.0, 0.0, -1.0], [0.577, -0.577, 0.577], [-0.3, 0.4, -0.866]];
+        let config = QuantizeConfig::new().normal_norm(NormMode::Snorm);
+        let q = encode_normals_oct_cfg(&src, &config);
+        // +Zは(0, 0)
+        assert_eq!(&q.data[..2], &[0, 0]);
+        for (n, r) in src.iter().zip(decode_normals_oct_cfg(&q, &config)) {
+            let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+            assert!(((n[0]*r[0] + n[1]*r[1] + n[2]*r[2]) / len).clamp(-1.0, 1.0).acos() < 2e-3, "{:?} -> {:?}", n, r);
+        }
+        assert_eq!(encode_normals_oct_cfg(&src, &QuantizeConfig::new()).data, encode_normals_oct(&src).data);
+
+        let pos = vec![[0.0, 1.0, 2.0], [10.0, 20.0, 30.0], [-1.0, 0.5, 100.0]];
+        let q16 = quantize_positions(&pos).unwrap();
+        let snorm = position_words(&q16, &QuantizeConfig::new());
+        let unorm = position_words(&q16, &QuantizeConfig::new().position_norm(NormMode::Unorm));
+        assert!(snorm.iter().zip(&unorm).all(|(&s, &u)| s ^ 0x8000 == u));
+        let q10 = quantize_positions_with_bits(&pos, 10).unwrap();
+        let unorm = position_words(&q10, &QuantizeConfig::new().position_norm(NormMode::Unorm));
+        assert!(unorm.iter().all(|&w| w <= 1023) && unorm.contains(&0) && unorm.contains(&1023));
+        for (i, p) in dequantize_positions(&q10).iter().enumerate() {
+            for a in 0..3 {
+                assert!((unorm[i * 3 + a] as f32 * q10.scale[a] + q10.offset[a] - p[a]).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_uvs() {
+        let src: Vec<[f32; 2]> = (0..=100).map(|i| { let t = i as f32 / 100.0; [t, 1.0 - t * t] }).collect();
+        let q = quantize_uvs(&src);
+        let restored = dequantize_uvs(&q);
+        assert_eq!(restored.len(), src.len());
+        let tol = 1.0 / 65535.0 * 0.5 + 1e-7;
+        for (uv, r) in src.iter().zip(&restored) {
+            for a in 0..2 {
+                let diff = (uv[a] - r[a]).abs();
+                assert!(diff <= tol, "diff {} exceeds tol {}", diff, tol);
+            }
+        }
+    }
+
+    #[test]
+    fn unit_cube_and_centering() {
+        let src = [[1.0, -2.0, 5.0], [5.0, 0.0, 6.0], [3.0, -1.0, 5.5]];
+        assert_eq!(normalize_positions_to_unit_cube(&src), vec![[0.0, 0.0, 0.0], [1.0, 0.5, 0.25], [0.5, 0.25, 0.125]]);
+        assert_eq!(normalize_positions_to_unit_cube(&[[2.0, 2.0, 2.0]; 2]), vec![[0.0; 3]; 2]);
+        assert!(normalize_positions_to_unit_cube(&[]).is_empty());
+
+        let c = center_at_origin(&src);
+        for a in 0..3 {
+            assert!(c.iter().map(|p| p[a]).sum::<f32>().abs() < 1e-5);
+        }
+        assert_eq!(c[0], [-2.0, -1.0, -0.5]);
+        assert!(center_at_origin(&[]).is_empty());
+    }
+
+    #[test]
+    fn transform_positions_column_major() {
+        let src = [[1.0, 2.0, 3.0], [0.0, 0.0, 0.0]];
+        // Z軸まわり90度 + (10, 0, 0) の平行移動
+        let m = [[0.0, 1.0, 0.0, 0.0], [-1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [10.0, 0.0, 0.0, 1.0]];
+        assert_eq!(transform_positions(&src, &m), vec![[8.0, 1.0, 3.0], [10.0, 0.0, 0.0]]);
+        // w列があれば射影として割る
+        let mut proj = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 1.0], [0.0, 0.0, 0.0, 0.0]];
+        assert_eq!(transform_positions(&[[2.0, 4.0, 2.0]], &proj), vec![[1.0, 2.0, 1.0]]);
+        proj[2][3] = 0.0;
+        assert!(validate_positions(&transform_positions(&[[2.0, 4.0, 2.0]], &proj)).is_err());
+    }
+
+    #[test]
+    fn generic_attribute_quantization() {
+        let w = [Vec4(0.5, 0.25, 0.25, 0.0), Vec4(1.0, 0.0, 0.0, 0.0)].map(|v| [v.0, v.1, v.2, v.3]);
+        assert_eq!(quantize_attribute_f32(&w, 8, false), AttributeData::Unorm(vec![128, 64, 64, 0, 255, 0, 0, 0]));
+        assert_eq!(quantize_attribute_f32(&[[-1.0, 0.5, 2.0]], 8, true), AttributeData::Snorm(vec![-127, 64, 127]));
+        // 符号なし16bitは32767を超えても折り返さない
+        assert_eq!(quantize_attribute_f32(&[[1.5f32], [0.75], [-0.5]], 16, false), AttributeData::Unorm(vec![65535, 49151, 0]));
+        assert!(quantize_attribute_f32::<4>(&[], 8, true).is_empty());
+        // 既存の16bit UV・接線と同じ値になる
+        let uv = [[0.1, 0.9], [0.333, 0.0]];
+        assert_eq!(quantize_attribute_f32(&uv, 16, false), AttributeData::Unorm(quantize_uvs(&uv).data));
+        let t = [[0.6, -0.8, 0.0, 1.0]];
+        let folded: Vec<[f32; 2]> = t.iter().map(|t| oct_fold([t[0], t[1], t[2]])).collect();
+        assert_eq!(quantize_attribute_f32(&folded, 16, true), AttributeData::Snorm(quantize_tangents(&t).data));
+    }
+
+    #[test]
+    fn roundtrip_tangents() {
+        let src = vec![
+            [1.0, 0.0, 0.0, 1.0], [0.0, 0.0, -1.0, -1.0], [0.6, -0.8, 0.0, 1.0], [-0.36, 0.48, -0.8, -1.0],
+        ];
+        let q = quantize_tangents(&src);
+        assert_eq!(q.data.len(), 8);
+        assert_eq!(q.handedness, vec![1, -1, 1, -1]);
+        for (t, r) in src.iter().zip(dequantize_tangents(&q)) {
+            let dot = t[0]*r[0] + t[1]*r[1] + t[2]*r[2];
+            assert!(dot.clamp(-1.0, 1.0).acos() < 1e-3, "{:?} -> {:?}", t, r);
+            assert_eq!(t[3], r[3]);
+        }
+    }
+
+    #[test]
+    fn roundtrip_vertex_colors() {
+        let src = vec![[0.0, 0.5, 1.0, 1.0], [0.1, 0.2, 0.3, 0.4], [-1.0, 2.0, 0.999, 0.001]];
+        let q = quantize_vertex_colors(&src);
+        assert_eq!(q.data.len(), 12);
+        assert_eq!(&q.data[8..], &[0, 255, 255, 0]);
+        for (c, r) in src.iter().zip(dequantize_vertex_colors(&q)) {
+            for ch in 0..4 {
+                assert!((c[ch].clamp(0.0, 1.0) - r[ch]).abs() <= 0.5 / 255.0 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_rgb9e5() {
+        let src = [[0.0, 0.0, 0.0], [1.0, 0.5, 0.25], [100.0, 3.0, 0.01], [RGB9E5_MAX, 1e9, -5.0], [0.001, 0.002, 0.0005]];
+        let enc = encode_rgb9e5(&src);
+        let dec = decode_rgb9e5(&enc);
+        assert_eq!(dec[0], [0.0; 3]);
+        assert_eq!(dec[1], [1.0, 0.5, 0.25]);
+        assert_eq!(dec[3], [RGB9E5_MAX, RGB9E5_MAX, 0.0]);
+        assert_eq!(enc[3], 0x1FF | 0x1FF << 9 | 31 << 27);
+        for (c, d) in src.iter().zip(&dec).skip(2).filter(|(c, _)| c[0] < RGB9E5_MAX) {
+            // 誤差は最大成分の仮数1/2ステップ (2^-9) 以内
+            let max = c[0].max(c[1]).max(c[2]);
+            assert!((0..3).all(|a| (c[a] - d[a]).abs() <= max / 512.0), "{:?} vs {:?}", c, d);
+        }
+        // 511.5/512 は512に繰り上がるので指数が1つ上がる
+        assert_eq!(decode_rgb9e5(&encode_rgb9e5(&[[1023.0 / 1024.0, 0.0, 0.0]])), vec![[1.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn roundtrip_ao() {
+        let q = quantize_ao(&[0.0, 0.5, 1.0, -0.2, 1.5, 0.3]);
+        assert_eq!(q.data, vec![0, 128, 255, 0, 255, 77]);
+        let r = dequantize_ao(&q);
+        assert_eq!((r[0], r[2]), (0.0, 1.0));
+        assert!((r[5] - 0.3).abs() <= 0.5 / 255.0);
+    }
+
+    #[test]
+    fn joint_weights_sum_to_255() {
+        let src = vec![
+            [1.0, 0.0, 0.0, 0.0], [0.25, 0.25, 0.25, 0.25], [1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 0.0],
+            [0.5, 0.3, 0.2, 0.01], [2.0, 1.0, 1.0, 0.0], [0.2, -0.1, 0.8, 0.0], [0.0; 4],
+        ];
+        let q = quantize_joint_weights(&src);
+        for (i, c) in q.data.chunks_exact(4).enumerate() {
+            let sum: u32 = c.iter().map(|&b| b as u32).sum();
+            if i == src.len() - 1 { assert_eq!(sum, 0); } else { assert_eq!(sum, 255, "vertex {}: {:?}", i, c); }
+        }
+        assert_eq!(&q.data[4..8], &[63, 64, 64, 64]);
+        let restored = dequantize_joint_weights(&q);
+        assert!((restored[4][0] - 0.5).abs() < 1.0 / 255.0);
+        assert_eq!(restored[5][1], 0.0);
+    }
+
+    #[test]
+    fn joint_weights_normalize_and_validate() {
+        let mut w = [[0.5, 0.3, 0.2, 0.0001], [1e-7, 0.0, 0.0, 0.0], [0.6, -0.2, 0.6, 0.0], [0.999, 0.0, 0.0, 0.0]];
+        normalize_joint_weights(&mut w);
+        for v in [w[0], w[2], w[3]] {
+            assert!((v.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(w[1], [0.0; 4]);
+        assert_eq!(w[2], [0.5, 0.0, 0.5, 0.0]);
+
+        // 0.5 * 255 = 127.5 は両方切り上がって256になるので最大成分 (先頭) から1引く
+        let q = quantize_joint_weights(&[[0.5, 0.5, 0.0, 0.0], [0.6, 0.2, 0.1, 0.1], [1e-7, 0.0, 0.0, 0.0]]);
+        assert_eq!(&q.data[..4], &[127, 128, 0, 0]);
+        assert_eq!(&q.data[8..], &[0; 4]);
+        assert!(validate_joint_weights(&q).is_ok());
+        let bad = QuantizedJointWeights { data: vec![100, 100, 0, 0] };
+        assert!(validate_joint_weights(&bad).is_err());
+    }
+
+    #[test]
+    fn joint_indices_pick_smallest_type() {
+        let small = quantize_joint_indices(&[[0, 1, 2, 255]]);
+        assert_eq!(small, QuantizedJointIndices::U8(vec![0, 1, 2, 255]));
+        let large = quantize_joint_indices(&[[0, 1, 2, 256]]);
+        assert_eq!(large, QuantizedJointIndices::U16(vec![0, 1, 2, 256]));
+        assert_eq!(large.get(3), 256);
+    }
+
+    #[test]
+    fn with_aabb_uses_given_bounds() {
+        let src = vec![[0.5, 0.5, 2.0], [1.5, 0.25, 2.0]];
+        let q = quantize_positions_with_aabb(&src, [0.0, 0.0, 2.0], [2.0, 1.0, 2.0]).unwrap();
+        assert!(quantize_positions_with_aabb(&src, [0.0, f32::NAN, 2.0], [2.0, 1.0, 2.0]).is_err());
+        assert!(quantize_positions_with_aabb(&src, [0.0; 3], [f32::INFINITY, 1.0, 2.0]).is_err());
+        assert!(quantize_positions_with_aabb(&[[f32::NAN, 0.0, 0.0]], [0.0; 3], [1.0; 3]).is_err());
+        assert_eq!(q.offset, [0.0, 0.0, 2.0]);
+        assert!((q.scale[0] - 2.0 / 65535.0).abs() < 1e-9);
+        // 幅0の軸もガードが効く
+        assert!(q.scale[2] > 0.0 && q.scale[2].is_finite());
+        for (p, r) in src.iter().zip(dequantize_positions(&q)) {
+            for a in 0..3 {
+                assert!((p[a] - r[a]).abs() <= 0.5 * q.scale[a] + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn per_axis_bits_roundtrip() {
+        let src: Vec<[f32; 3]> = (0..400).map(|i| { let t = i as f32; [(t * 0.37).fract() * 100.0, (t * 0.11).sin() * 3.0, (t * 0.73).fract() * 100.0] }).collect();
+        for bits in [[16, 12, 16], [7, 5, 3], [1, 9, 16]] {
+            let q = quantize_positions_cfg(&src, &QuantizeConfig::new().axis_bits(bits)).unwrap();
+            assert_eq!(q.bits, bits);
+            assert_eq!(matches!(q.data, PositionData::I8(_)), bits.iter().all(|&b| b <= 8));
+            for (a, &b) in bits.iter().enumerate() {
+                let half = 1i32 << (b - 1);
+                assert!((0..src.len()).all(|i| (-half..half).contains(&(q.data.get(i * 3 + a) as i32))));
+            }
+            let err = quantization_error(&src, &q).unwrap();
+            for a in 0..3 {
+                assert!(err.max[a] <= 0.5 * q.scale[a] + 1e-4, "{:?} axis {}: {} vs {}", bits, a, err.max[a], q.scale[a]);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_normals_10_10_10_2() {
+        let src = vec![[0.0, 0.0, 1.0], [0.0, -1.0, 0.0], [0.577, -0.577, 0.577], [-0.3, 0.4, -0.866]];
+        let q = encode_normals_10_10_10_2(&src);
+        assert_eq!(q.data[0], 511 << 20);
+        assert_eq!(q.data[0] >> 30, 0);
+        for (n, r) in src.iter().zip(decode_normals_10_10_10_2(&q)) {
+            let len = (n[0]*n[0] + n[1]*n[1] + n[2]*n[2]).sqrt();
+            for a in 0..3 {
+                assert!((n[a] / len - r[a]).abs() <= 0.5 / 511.0 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_normals_spheremap() {
+        // フィボナッチ球で全方向をなめる (真下付近は精度が落ちるので別扱い)
+        let n = 2000;
+        let src: Vec<[f32; 3]> = (0..n).map(|i| {
+            let z = 1.0 - 2.0 * (i as f32 + 0.5) / n as f32;
+            let r = (1.0 - z * z).sqrt();
+            let phi = i as f32 * 2.399_963;
+            [r * phi.cos(), r * phi.sin(), z]
+        }).collect();
+        let q = encode_normals_spheremap(&src);
+        for (n, r) in src.iter().zip(decode_normals_spheremap(&q)) {
+            if n[2] < -0.95 { continue; }
+            let angle = (n[0]*r[0] + n[1]*r[1] + n[2]*r[2]).clamp(-1.0, 1.0).acos();
+            assert!(angle < 1e-3, "angle {} for {:?}", angle, n);
+        }
+        let down = decode_normals_spheremap(&encode_normals_spheremap(&[[0.0, 0.0, -1.0]]));
+        assert!((down[0][2] + 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounding_sphere_contains_all_points() {
+        let src: Vec<[f32; 3]> = (0..500).map(|i| { let t = i as f32; [(t * 0.37).sin() * 4.0 + 10.0, (t * 1.1).cos() * 0.5, (t * 0.73).fract() * 7.0 - 3.0] }).collect();
+        let (center, radius) = bounding_sphere(&src);
+        let (amin, amax) = aabb_min_max(&src).unwrap();
+        let (acenter, aradius) = bounding_sphere_from_aabb(amin, amax);
+        for s in [(center, radius), (acenter, aradius)] {
+            for p in &src {
+                let d = ((p[0] - s.0[0]).powi(2) + (p[1] - s.0[1]).powi(2) + (p[2] - s.0[2]).powi(2)).sqrt();
+                assert!(d <= s.1, "{:?} outside sphere {:?}", p, s);
+            }
+        }
+        assert!(radius <= aradius * 1.05);
+        assert_eq!(bounding_sphere(&[]), ([0.0; 3], 0.0));
+        assert_eq!(bounding_sphere(&[[1.0, 2.0, 3.0]]).0, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn watermark_survives_lossless_round_trip() {
+        let src: Vec<[f32; 3]> = (0..100).map(|i| { let t = i as f32; [t.sin() * 3.0, t * 0.1, (t * 0.7).cos()] }).collect();
+        let mut q = quantize_positions(&src).unwrap();
+        let payload = b"meshguard-id";
+        watermark_positions(&mut q, payload, 1234).unwrap();
+        assert_eq!(extract_watermark(&q, payload.len(), 1234).unwrap(), payload);
+        assert_ne!(extract_watermark(&q, payload.len(), 4321).unwrap(), payload);
+
+        // 同じ範囲で量子化し直しても1LSB未満の誤差なら残る
+        let restored = dequantize_positions(&q);
+        let (min, max) = aabb_min_max(&src).unwrap();
+        let again = quantize_positions_with_aabb(&restored, min, max).unwrap();
+        assert_eq!(extract_watermark(&again, payload.len(), 1234).unwrap(), payload);
+
+        let mut small = quantize_positions_with_bits(&src, 6).unwrap();
+        watermark_positions(&mut small, &[0xA5], 7).unwrap();
+        assert_eq!(extract_watermark(&small, 1, 7).unwrap(), vec![0xA5]);
+        assert!(watermark_positions(&mut small, &[0; 1000], 7).is_err());
+    }
+
+    #[test]
+    fn streaming_quantizer_matches_whole_mesh() {
+        let src: Vec<[f32; 3]> = (0..50).map(|i| { let t = i as f32; [t.sin() * 3.0, t * 0.1, (t * 0.7).cos()] }).collect();
+        let (min, max) = aabb_min_max(&src).unwrap();
+        let whole = quantize_positions_with_aabb(&src, min, max).unwrap();
+
+        let mut quantizer = PositionQuantizer::new(min, max, 16).unwrap();
+        let mut collected = Vec::new();
+        for chunk in src.chunks(7) {
+            let out = quantizer.push_chunk(chunk).unwrap();
+            assert_eq!(out.len(), chunk.len() * 3);
+            collected.extend_from_slice(out);
+        }
+        assert_eq!((quantizer.scale(), quantizer.offset(), quantizer.bits()), (whole.scale, whole.offset, whole.bits));
+        assert!(matches!(quantizer.push_chunk(&[[0.0; 3], [f32::NAN, f32::INFINITY, 0.5]]), Err(MeshGuardError::InvalidInput(_))));
+        assert!(quantizer.push_chunk(&[[f32::NEG_INFINITY, 0.0, 0.0]]).is_err());
+        let streamed = quantizer.finish(collected);
+        assert_eq!((0..whole.data.len()).map(|i| whole.data.get(i)).collect::<Vec<_>>(), (0..streamed.data.len()).map(|i| streamed.data.get(i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn quantize_into_caller_buffer() {
+        let src = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let (min, max) = aabb_min_max(&src).unwrap();
+        let whole = quantize_positions(&src).unwrap();
+        let mut out = [0i16; 12];
+        let meta = quantize_positions_into(&src, min, max, &mut out).unwrap();
+        assert!(meta.data.is_empty());
+        assert_eq!((meta.scale, meta.offset, meta.bits), (whole.scale, whole.offset, whole.bits));
+        assert_eq!(out[..9].to_vec(), (0..9).map(|i| whole.data.get(i)).collect::<Vec<_>>());
+        assert_eq!(out[9..], [0; 3]);
+
+        let err = quantize_positions_into(&src, min, max, &mut out[..8]).unwrap_err();
+        assert_eq!(err, MeshGuardError::LengthMismatch { attribute: "output buffer", expected: 9, got: 8 });
+        let nan = [[f32::NAN, 0.0, 0.0], [1.0, 1.0, 1.0]];
+        assert!(matches!(quantize_positions_into(&nan, [0.0; 3], [1.0; 3], &mut out), Err(MeshGuardError::InvalidInput(_))));
+        let inf = [[0.5, f32::INFINITY, 0.0]];
+        assert!(matches!(quantize_positions_into(&inf, [0.0; 3], [1.0; 3], &mut out), Err(MeshGuardError::InvalidInput(_))));
+    }
+}
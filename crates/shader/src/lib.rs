@@ -0,0 +1,138 @@
+//! PackedMeshの頂点バッファを読むシェーダ断片 (GLSL 450 / WGSL) を作る
+//! 頂点入力は `meshguard-wgpu` と同じく属性をレイアウト順に location 0, 1, 2... に置き、3要素の型は4要素で読む
+//! 復号用のuniformは `u_pos_scale` / `u_pos_offset` の2つ (std140で32バイト、`uniform_data` で中身を作れる)
+//! 生成する断片に `main` はないので、利用側のシェーダに埋め込んで使う
+
+use meshguard_pack::{AttributeKind, PackedMesh, VertexFormat};
+use std::fmt::Write;
+
+/// uniformブロックの中身 `[scale.xyz, 0, offset.xyz, 0]`
+pub fn uniform_data(mesh: &PackedMesh) -> [f32; 8] {
+    let (s, o) = (mesh.pos_scale, mesh.pos_offset);
+    [s[0], s[1], s[2], 0.0, o[0], o[1], o[2], 0.0]
+}
+
+fn attribute_name(kind: AttributeKind) -> &'static str {
+    match kind {
+        AttributeKind::Position => "position",
+        AttributeKind::Normal => "normal",
+        AttributeKind::Uv => "uv",
+        AttributeKind::Uv2 => "uv2",
+        AttributeKind::Tangent => "tangent",
+        AttributeKind::Color => "color",
+        AttributeKind::JointWeights => "joint_weights",
+        AttributeKind::JointIndices => "joint_indices",
+        AttributeKind::AmbientOcclusion => "ao",
+    }
+}
+
+/// (GLSLの型, WGSLの型)。GPUが読む形式は `meshguard-wgpu` の対応表と同じ
+fn attribute_types(format: VertexFormat) -> (&'static str, &'static str) {
+    match format {
+        VertexFormat::Sint16x3 => ("ivec4", "vec4<i32>"),
+        VertexFormat::Float16x3 | VertexFormat::Snorm16x4 | VertexFormat::Unorm8x4 => ("vec4", "vec4<f32>"),
+        VertexFormat::Unorm16x2 => ("vec2", "vec2<f32>"),
+        VertexFormat::Snorm10_10_10_2 => ("uint", "u32"),
+        VertexFormat::Uint8x4 | VertexFormat::Uint16x4 => ("uvec4", "vec4<u32>"),
+        VertexFormat::Unorm8 => ("float", "f32"),
+    }
+}
+
+/// 軸ごとの中心値 2^(bits-1)
+fn pos_half(mesh: &PackedMesh) -> [String; 3] {
+    mesh.pos_bits.map(|b| format!("{:.1}", (1u32 << (b - 1)) as f32))
+}
+
+fn header(mesh: &PackedMesh, comment: &str) -> String {
+    let bits = mesh.pos_bits;
+    let kinds: Vec<&str> = mesh.layout.attributes.iter().map(|a| attribute_name(a.kind)).collect();
+    format!("{} meshguard: stride {}, position bits {}/{}/{}, attributes [{}]\n", comment, mesh.layout.stride, bits[0], bits[1], bits[2], kinds.join(", "))
+}
+
+/// GLSL 450の断片 (頂点入力、uniformブロック、`decode_position` と法線の復号関数)
+pub fn generate_glsl_decode(mesh: &PackedMesh) -> String {
+    let mut s = header(mesh, "//");
+    s += "layout(set = 0, binding = 0) uniform MeshguardDequant {\n    vec3 u_pos_scale;\n    vec3 u_pos_offset;\n};\n\n";
+    for (location, attr) in mesh.layout.attributes.iter().enumerate() {
+        let _ = writeln!(s, "layout(location = {}) in {} a_{};", location, attribute_types(attr.format).0, attribute_name(attr.kind));
+    }
+    s += "\n";
+    match mesh.layout.attribute(AttributeKind::Position).map(|a| a.format) {
+        Some(VertexFormat::Float16x3) => s += "// f16座標は復号済み\nvec3 decode_position(vec3 raw) {\n    return raw;\n}\n",
+        _ => {
+            let [hx, hy, hz] = pos_half(mesh);
+            let _ = write!(s, "vec3 decode_position(ivec3 raw) {{\n    return (vec3(raw) + vec3({}, {}, {})) * u_pos_scale + u_pos_offset;\n}}\n", hx, hy, hz);
+        }
+    }
+    match mesh.layout.attribute(AttributeKind::Normal).map(|a| a.format) {
+        Some(VertexFormat::Unorm16x2) => s += "\nvec3 decode_normal_oct(vec2 e) {\n    vec2 f = e * 2.0 - 1.0;\n    vec3 n = vec3(f, 1.0 - abs(f.x) - abs(f.y));\n    if (n.z < 0.0) {\n        n.xy = (1.0 - abs(n.yx)) * vec2(n.x >= 0.0 ? 1.0 : -1.0, n.y >= 0.0 ? 1.0 : -1.0);\n    }\n    return normalize(n);\n}\n",
+        Some(VertexFormat::Snorm10_10_10_2) => s += "\nvec3 decode_normal_10_10_10_2(uint w) {\n    ivec3 v = (ivec3(uvec3(w, w >> 10, w >> 20) & 0x3FFu) << 22) >> 22;\n    return max(vec3(v) / 511.0, vec3(-1.0));\n}\n",
+        _ => {}
+    }
+    s
+}
+
+/// WGSLの断片 (頂点入力の構造体、uniform、`decode_position` と法線の復号関数)
+pub fn generate_wgsl_decode(mesh: &PackedMesh) -> String {
+    let mut s = header(mesh, "//");
+    s += "struct MeshguardDequant {\n    u_pos_scale: vec3<f32>,\n    u_pos_offset: vec3<f32>,\n}\n\n@group(0) @binding(0) var<uniform> meshguard: MeshguardDequant;\n\n";
+    s += "struct MeshguardVertex {\n";
+    for (location, attr) in mesh.layout.attributes.iter().enumerate() {
+        let _ = writeln!(s, "    @location({}) {}: {},", location, attribute_name(attr.kind), attribute_types(attr.format).1);
+    }
+    s += "}\n\n";
+    match mesh.layout.attribute(AttributeKind::Position).map(|a| a.format) {
+        Some(VertexFormat::Float16x3) => s += "// f16座標は復号済み\nfn decode_position(raw: vec3<f32>) -> vec3<f32> {\n    return raw;\n}\n",
+        _ => {
+            let [hx, hy, hz] = pos_half(mesh);
+            let _ = write!(s, "fn decode_position(raw: vec3<i32>) -> vec3<f32> {{\n    return (vec3<f32>(raw) + vec3<f32>({}, {}, {})) * meshguard.u_pos_scale + meshguard.u_pos_offset;\n}}\n", hx, hy, hz);
+        }
+    }
+    match mesh.layout.attribute(AttributeKind::Normal).map(|a| a.format) {
+        Some(VertexFormat::Unorm16x2) => s += "\nfn decode_normal_oct(e: vec2<f32>) -> vec3<f32> {\n    let f = e * 2.0 - 1.0;\n    var n = vec3<f32>(f, 1.0 - abs(f.x) - abs(f.y));\n    if n.z < 0.0 {\n        let s = select(vec2<f32>(-1.0), vec2<f32>(1.0), n.xy >= vec2<f32>(0.0));\n        n = vec3<f32>((1.0 - abs(n.yx)) * s, n.z);\n    }\n    return normalize(n);\n}\n",
+        Some(VertexFormat::Snorm10_10_10_2) => s += "\nfn decode_normal_10_10_10_2(w: u32) -> vec3<f32> {\n    let v = bitcast<vec3<i32>>((vec3<u32>(w, w >> 10u, w >> 20u) & vec3<u32>(0x3FFu)) << vec3<u32>(22u)) >> vec3<u32>(22u);\n    return max(vec3<f32>(v) / 511.0, vec3<f32>(-1.0));\n}\n",
+        _ => {}
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshguard_pack::{pack_with_config, MeshAttributes, PackConfig};
+    use meshguard_quantize::{encode_normals_10_10_10_2, encode_normals_oct, quantize_positions_with_bits, quantize_uvs};
+
+    fn validate(module: &naga::Module) {
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+            .validate(module)
+            .unwrap_or_else(|e| panic!("{:?}", e));
+    }
+
+    #[test]
+    fn generated_shaders_validate_with_naga() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
+        let nor = [[0.0,0.0,1.0],[1.0,0.0,0.0],[0.0,-1.0,0.0]];
+        let (qpos, qnor, quv) = (quantize_positions_with_bits(&pos, 12).unwrap(), encode_normals_oct(&nor), quantize_uvs(&[[0.0,0.0]; 3]));
+        let n10 = encode_normals_10_10_10_2(&nor);
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv).with_normals_10_10_10_2(&n10);
+        let meshes = [
+            pack_with_config(&attrs, None, 1, &PackConfig::new().align_attributes(true)).unwrap(),
+            pack_with_config(&attrs, None, 1, &PackConfig::new().normals_10_10_10_2(true)).unwrap(),
+            pack_with_config(&attrs, None, 1, &PackConfig::new().f16_positions(true)).unwrap(),
+        ];
+        for mesh in &meshes {
+            let wgsl = generate_wgsl_decode(mesh);
+            assert!(wgsl.contains("u_pos_scale") && wgsl.contains("@location(2) uv: vec2<f32>"));
+            let wgsl_main = format!("{}\n@vertex\nfn vs(v: MeshguardVertex) -> @builtin(position) vec4<f32> {{\n    return vec4<f32>(decode_position(v.position.xyz), 1.0);\n}}\n", wgsl);
+            validate(&naga::front::wgsl::parse_str(&wgsl_main).unwrap_or_else(|e| panic!("{}\n{}", e.emit_to_string(&wgsl_main), wgsl_main)));
+
+            let glsl = format!("#version 450\n{}\nvoid main() {{\n    gl_Position = vec4(decode_position(a_position.xyz), 1.0);\n}}\n", generate_glsl_decode(mesh));
+            let mut frontend = naga::front::glsl::Frontend::default();
+            let module = frontend.parse(&naga::front::glsl::Options::from(naga::ShaderStage::Vertex), &glsl).unwrap_or_else(|e| panic!("{:?}\n{}", e, glsl));
+            validate(&module);
+        }
+        assert!(generate_wgsl_decode(&meshes[0]).contains("vec3<f32>(2048.0, 2048.0, 2048.0)"));
+        assert!(generate_glsl_decode(&meshes[1]).contains("decode_normal_10_10_10_2"));
+        assert_eq!(uniform_data(&meshes[0])[..3], meshes[0].pos_scale);
+    }
+}
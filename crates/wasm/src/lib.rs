@@ -0,0 +1,149 @@
+//! ブラウザ向けの wasm-bindgen ラッパ
+//! `wasm-pack build crates/wasm --target web` でnpmパッケージとして出力できる
+//! 入力はFloat32Array (xyz / uv を平坦に並べたもの)
+
+use meshguard_pack as mp;
+use meshguard_quantize as mq;
+use wasm_bindgen::prelude::*;
+
+fn chunks<const N: usize>(flat: &[f32], what: &str) -> Result<Vec<[f32; N]>, JsError> {
+    if !flat.len().is_multiple_of(N) {
+        return Err(JsError::new(&format!("{} length {} is not a multiple of {}", what, flat.len(), N)));
+    }
+    Ok(flat.chunks_exact(N).map(|c| c.try_into().unwrap()).collect())
+}
+
+#[wasm_bindgen]
+pub struct QuantizedPositions {
+    inner: mq::QuantizedPositions,
+}
+
+#[wasm_bindgen]
+impl QuantizedPositions {
+    pub fn scale(&self) -> Vec<f32> {
+        self.inner.scale.to_vec()
+    }
+
+    pub fn offset(&self) -> Vec<f32> {
+        self.inner.offset.to_vec()
+    }
+
+    pub fn bits(&self) -> Vec<u8> {
+        self.inner.bits.to_vec()
+    }
+
+    /// 格納形式に関わらずInt16Arrayで返す
+    pub fn data(&self) -> Vec<i16> {
+        (0..self.inner.data.len()).map(|i| self.inner.data.get(i)).collect()
+    }
+}
+
+#[wasm_bindgen]
+pub struct QuantizedNormals {
+    inner: mq::QuantizedNormalsOct,
+}
+
+#[wasm_bindgen]
+impl QuantizedNormals {
+    pub fn data(&self) -> Vec<u16> {
+        self.inner.data.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct QuantizedUVs {
+    inner: mq::QuantizedUVs,
+}
+
+#[wasm_bindgen]
+impl QuantizedUVs {
+    pub fn data(&self) -> Vec<u16> {
+        self.inner.data.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct PackedMesh {
+    inner: mp::PackedMesh,
+}
+
+#[wasm_bindgen]
+impl PackedMesh {
+    pub fn interleaved(&self) -> Vec<u8> {
+        self.inner.interleaved.clone()
+    }
+
+    /// 内部表現に関わらずUint32Arrayで返す
+    pub fn indices(&self) -> Vec<u32> {
+        self.inner.indices_u32()
+    }
+
+    #[wasm_bindgen(js_name = vertexCount)]
+    pub fn vertex_count(&self) -> usize {
+        self.inner.vertex_count
+    }
+
+    pub fn stride(&self) -> usize {
+        self.inner.layout.stride
+    }
+
+    #[wasm_bindgen(js_name = posScale)]
+    pub fn pos_scale(&self) -> Vec<f32> {
+        self.inner.pos_scale.to_vec()
+    }
+
+    #[wasm_bindgen(js_name = posOffset)]
+    pub fn pos_offset(&self) -> Vec<f32> {
+        self.inner.pos_offset.to_vec()
+    }
+}
+
+#[wasm_bindgen(js_name = quantizePositions)]
+pub fn quantize_positions(positions: &[f32]) -> Result<QuantizedPositions, JsError> {
+    Ok(QuantizedPositions { inner: mq::quantize_positions(&chunks::<3>(positions, "positions")?)? })
+}
+
+#[wasm_bindgen(js_name = encodeNormalsOct)]
+pub fn encode_normals_oct(normals: &[f32]) -> Result<QuantizedNormals, JsError> {
+    Ok(QuantizedNormals { inner: mq::encode_normals_oct(&chunks::<3>(normals, "normals")?) })
+}
+
+#[wasm_bindgen(js_name = quantizeUvs)]
+pub fn quantize_uvs(uvs: &[f32]) -> Result<QuantizedUVs, JsError> {
+    Ok(QuantizedUVs { inner: mq::quantize_uvs(&chunks::<2>(uvs, "uvs")?) })
+}
+
+/// `perm_seed` はJS側ではBigInt
+#[wasm_bindgen(js_name = packInterleavePermute)]
+pub fn pack_interleave_permute(
+    positions: &QuantizedPositions,
+    normals: &QuantizedNormals,
+    uvs: &QuantizedUVs,
+    indices: Option<Vec<u32>>,
+    perm_seed: u64,
+) -> Result<PackedMesh, JsError> {
+    let inner = mp::pack_interleave_permute(&positions.inner, &normals.inner, &uvs.inner, indices.as_deref(), perm_seed)?;
+    Ok(PackedMesh { inner })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrappers_match_native() {
+        let pos = [0.0,1.0,2.0, 10.0,20.0,30.0, -1.0,0.5,100.0];
+        let nor = [0.0,0.0,1.0, 1.0,0.0,0.0, 0.577,0.577,0.577];
+        let uv = [0.0,0.0, 0.5,0.75, 1.0,1.0];
+        let qp = quantize_positions(&pos).unwrap();
+        assert_eq!(qp.offset(), vec![-1.0, 0.5, 2.0]);
+        assert_eq!(qp.data().len(), 9);
+
+        let mesh = pack_interleave_permute(&qp, &encode_normals_oct(&nor).unwrap(), &quantize_uvs(&uv).unwrap(), Some(vec![0, 1, 2]), 42).unwrap();
+        assert_eq!((mesh.vertex_count(), mesh.stride()), (3, 14));
+        assert_eq!(mesh.interleaved().len(), 42);
+        let mut idx = mesh.indices();
+        idx.sort();
+        assert_eq!(idx, vec![0, 1, 2]);
+    }
+}
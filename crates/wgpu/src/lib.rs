@@ -0,0 +1,79 @@
+//! PackedMeshのレイアウトからwgpuの頂点バッファ記述を作る (`wgpu` feature)
+//! WebGPUはstrideと各オフセットが4バイト境界であることを要求するので、
+//! `PackConfig::align_attributes(true)` でpackしたメッシュを渡すこと
+
+#[cfg(feature = "wgpu")]
+use meshguard_pack::{Endianness, MeshGuardError, PackedMesh, VertexFormat};
+
+/// 属性をレイアウト順に shader_location 0, 1, 2... として並べる
+/// 3要素の型はWebGPUにないので4要素で読む (wは後ろのパディングを読むので無視する)
+/// 10-10-10-2法線は符号付きの型がないのでUint32で読み、シェーダ側で展開する
+#[cfg(feature = "wgpu")]
+pub fn wgpu_vertex_attributes(mesh: &PackedMesh) -> Result<Vec<wgpu::VertexAttribute>, MeshGuardError> {
+    let layout = &mesh.layout;
+    if layout.endianness != Endianness::Little {
+        return Err(MeshGuardError::InvalidInput("GPU vertex buffers must be little-endian".into()));
+    }
+    if !layout.stride.is_multiple_of(wgpu::VERTEX_ALIGNMENT as usize) {
+        return Err(MeshGuardError::InvalidInput(format!("stride {} is not a multiple of 4; pack with align_attributes(true)", layout.stride)));
+    }
+    let mut out = Vec::with_capacity(layout.attributes.len());
+    for (location, attr) in layout.attributes.iter().enumerate() {
+        let (format, read) = match attr.format {
+            VertexFormat::Sint16x3 => (wgpu::VertexFormat::Sint16x4, 8),
+            VertexFormat::Float16x3 => (wgpu::VertexFormat::Float16x4, 8),
+            VertexFormat::Unorm16x2 => (wgpu::VertexFormat::Unorm16x2, 4),
+            VertexFormat::Snorm10_10_10_2 => (wgpu::VertexFormat::Uint32, 4),
+            VertexFormat::Snorm16x4 => (wgpu::VertexFormat::Snorm16x4, 8),
+            VertexFormat::Unorm8x4 => (wgpu::VertexFormat::Unorm8x4, 4),
+            VertexFormat::Uint8x4 => (wgpu::VertexFormat::Uint8x4, 4),
+            VertexFormat::Uint16x4 => (wgpu::VertexFormat::Uint16x4, 8),
+            VertexFormat::Unorm8 => (wgpu::VertexFormat::Unorm8, 1),
+        };
+        if !attr.offset.is_multiple_of(4) || attr.offset + read > layout.stride {
+            return Err(MeshGuardError::InvalidInput(format!("{:?} at offset {} cannot be read as {:?}", attr.kind, attr.offset, format)));
+        }
+        out.push(wgpu::VertexAttribute { format, offset: attr.offset as u64, shader_location: location as u32 });
+    }
+    Ok(out)
+}
+
+/// 属性配列をリークして 'static な記述を返す
+/// パイプライン作成時に一度だけ呼ぶ想定。繰り返し呼ぶなら `wgpu_vertex_attributes` を自分で持つこと
+#[cfg(feature = "wgpu")]
+pub fn to_wgpu_vertex_buffer_layout(mesh: &PackedMesh) -> Result<wgpu::VertexBufferLayout<'static>, MeshGuardError> {
+    let attributes: &'static [wgpu::VertexAttribute] = Box::leak(wgpu_vertex_attributes(mesh)?.into_boxed_slice());
+    Ok(wgpu::VertexBufferLayout {
+        array_stride: mesh.layout.stride as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes,
+    })
+}
+
+#[cfg(all(test, feature = "wgpu"))]
+mod tests {
+    use super::*;
+    use meshguard_pack::{pack_with_config, MeshAttributes, PackConfig};
+    use meshguard_quantize::{encode_normals_oct, quantize_positions, quantize_uvs};
+
+    #[test]
+    fn aligned_mesh_maps_to_wgpu() {
+        let pos = vec![[0.0,1.0,2.0],[10.0,20.0,30.0]];
+        let (qpos, qnor, quv) = (quantize_positions(&pos).unwrap(), encode_normals_oct(&[[0.0,0.0,1.0]; 2]), quantize_uvs(&[[0.25,0.5]; 2]));
+        let attrs = MeshAttributes::new(&qpos, &qnor, &quv);
+
+        let tight = pack_with_config(&attrs, None, 1, &PackConfig::new()).unwrap();
+        assert!(to_wgpu_vertex_buffer_layout(&tight).is_err());
+
+        let mesh = pack_with_config(&attrs, None, 1, &PackConfig::new().align_attributes(true)).unwrap();
+        let layout = to_wgpu_vertex_buffer_layout(&mesh).unwrap();
+        assert_eq!(layout.array_stride, 16);
+        assert_eq!(layout.step_mode, wgpu::VertexStepMode::Vertex);
+        let got: Vec<_> = layout.attributes.iter().map(|a| (a.format, a.offset, a.shader_location)).collect();
+        assert_eq!(got, vec![
+            (wgpu::VertexFormat::Sint16x4, 0, 0),
+            (wgpu::VertexFormat::Unorm16x2, 8, 1),
+            (wgpu::VertexFormat::Unorm16x2, 12, 2),
+        ]);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{load, pipeline};
+use anyhow::{bail, Context, Result};
+use meshguard_quantize::psnr_positions;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 1ファイル分の結果
+pub struct BatchRow {
+    pub input: PathBuf,
+    pub vertex_count: usize,
+    /// 誤差0なら無限大
+    pub psnr_db: f32,
+    pub elapsed_ms: f64,
+}
+
+/// globを展開する。ディレクトリは除き、パス順に並べる
+pub fn expand_inputs(pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in glob::glob(pattern).with_context(|| format!("invalid glob pattern {:?}", pattern))? {
+        let path = entry?;
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        bail!("no files matched {:?}", pattern);
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn process_file(input: &Path, output: &Path, bits: u8, seed: u64) -> Result<BatchRow> {
+    let start = Instant::now();
+    let mesh = load::load_mesh(input)?;
+    let packed = pipeline::pack_mesh(&mesh, bits, seed)?;
+    let psnr_db = psnr_positions(&mesh.positions, &packed.positions)?;
+    let mut file = BufWriter::new(File::create(output).with_context(|| format!("failed to create {}", output.display()))?);
+    meshguard_io::write_mgb(&packed.mesh, &mut file)?;
+    // drop時の書き込み失敗は捨てられるので、ここで失敗として返す
+    file.flush().with_context(|| format!("failed to write {}", output.display()))?;
+    Ok(BatchRow { input: input.to_path_buf(), vertex_count: packed.mesh.vertex_count, psnr_db, elapsed_ms: start.elapsed().as_secs_f64() * 1e3 })
+}
+
+/// 各ファイルを `output_dir/<拡張子を除いたファイル名>.mgb` に書き出す (`mesh.v2.obj` は `mesh.v2.mgb`)。jobsスレッドで並列に処理する
+/// 失敗しても残りのファイルは続け、結果は入力と同じ順に返す
+/// 出力名が重なるファイルは後のものをエラーにする (上書きしない)
+pub fn run_batch(inputs: &[PathBuf], output_dir: &Path, bits: u8, seed: u64, jobs: usize) -> Result<Vec<(PathBuf, Result<BatchRow>)>> {
+    std::fs::create_dir_all(output_dir).with_context(|| format!("failed to create {}", output_dir.display()))?;
+    let mut seen = HashSet::new();
+    let outputs: Vec<Option<PathBuf>> = inputs.iter().map(|p| {
+        let stem = p.file_stem()?;
+        let out = output_dir.join(format!("{}.mgb", stem.to_string_lossy()));
+        seen.insert(out.clone()).then_some(out)
+    }).collect();
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    Ok(pool.install(|| inputs.par_iter().zip(&outputs).map(|(input, output)| {
+        let r = match output {
+            Some(out) => process_file(input, out, bits, seed),
+            None => Err(anyhow::anyhow!("output name collides with an earlier input")),
+        };
+        (input.clone(), r)
+    }).collect()))
+}
+
+pub fn summary_table(rows: &[&BatchRow]) -> String {
+    let mut s = String::from("| file | vertices | PSNR (dB) | ms |\n|---|---:|---:|---:|\n");
+    for r in rows {
+        s += &format!("| {} | {} | {:.1} | {:.1} |\n", r.input.display(), r.vertex_count, r.psnr_db, r.elapsed_ms);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_keeps_going_after_errors() {
+        let dir = std::env::temp_dir().join(format!("meshguard-batch-{}", std::process::id()));
+        let src = dir.join("src");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        let tri = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        std::fs::write(src.join("a.obj"), tri).unwrap();
+        std::fs::write(src.join("broken.obj"), "v 0 0 0\nf 1 2 9\n").unwrap();
+        std::fs::write(src.join("sub/c.obj"), tri).unwrap();
+        std::fs::write(src.join("sub/a.obj"), tri).unwrap();
+        std::fs::write(src.join("mesh.obj"), tri).unwrap();
+        std::fs::write(src.join("mesh.v2.obj"), tri).unwrap();
+
+        let inputs = expand_inputs(&format!("{}/**/*.obj", src.display())).unwrap();
+        assert_eq!(inputs.len(), 6);
+        let out = dir.join("packed");
+        let results = run_batch(&inputs, &out, 16, 1, 2).unwrap();
+        let ok: Vec<&BatchRow> = results.iter().filter_map(|(_, r)| r.as_ref().ok()).collect();
+        assert_eq!(ok.len(), 4);
+        assert!(ok.iter().all(|r| r.vertex_count == 3));
+        assert!(out.join("a.mgb").is_file() && out.join("c.mgb").is_file());
+        // 点を含む名前は最後の拡張子だけを置き換える
+        assert!(out.join("mesh.mgb").is_file() && out.join("mesh.v2.mgb").is_file());
+        assert_eq!(summary_table(&ok).lines().count(), 6);
+        // 書き込みに失敗したら成功扱いにしない
+        if Path::new("/dev/full").exists() {
+            assert!(process_file(&src.join("a.obj"), Path::new("/dev/full"), 16, 1).is_err());
+        }
+        assert!(expand_inputs(&format!("{}/*.ply", src.display())).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
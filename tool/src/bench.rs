@@ -0,0 +1,74 @@
+use crate::load::InputMesh;
+use anyhow::Result;
+use meshguard_pack::pack_interleave_permute;
+use meshguard_quantize::{aabb_min_max, encode_normals_oct, quantize_positions, quantize_uvs};
+use std::hint::black_box;
+use std::time::Instant;
+
+/// 段ごとの所要時間 (マイクロ秒、iterations回分)
+pub struct StageTimes {
+    pub name: &'static str,
+    pub micros: Vec<f64>,
+}
+
+impl StageTimes {
+    /// (平均, 標本標準偏差)。1回だけなら標準偏差は0
+    pub fn mean_std(&self) -> (f64, f64) {
+        let n = self.micros.len() as f64;
+        let mean = self.micros.iter().sum::<f64>() / n;
+        let var = if self.micros.len() > 1 { self.micros.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0) } else { 0.0 };
+        (mean, var.sqrt())
+    }
+}
+
+fn time<T>(out: &mut Vec<f64>, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let r = black_box(f());
+    out.push(start.elapsed().as_secs_f64() * 1e6);
+    r
+}
+
+/// パイプライン全体をiterations回まわして段ごとに計る
+pub fn run_bench(mesh: &InputMesh, iterations: usize) -> Result<Vec<StageTimes>> {
+    let n = mesh.positions.len();
+    let normals = if mesh.normals.is_empty() { vec![[0.0, 0.0, 1.0]; n] } else { mesh.normals.clone() };
+    let uvs = if mesh.uvs.is_empty() { vec![[0.0, 0.0]; n] } else { mesh.uvs.clone() };
+    let mut t: [Vec<f64>; 5] = Default::default();
+    for i in 0..iterations {
+        time(&mut t[0], || aabb_min_max(black_box(&mesh.positions)));
+        let qpos = time(&mut t[1], || quantize_positions(black_box(&mesh.positions)))?;
+        let qnor = time(&mut t[2], || encode_normals_oct(black_box(&normals)));
+        let quv = time(&mut t[3], || quantize_uvs(black_box(&uvs)));
+        time(&mut t[4], || pack_interleave_permute(&qpos, &qnor, &quv, Some(&mesh.indices), i as u64))?;
+    }
+    let names = ["aabb_min_max", "quantize_positions", "encode_normals_oct", "quantize_uvs", "pack_interleave_permute"];
+    Ok(names.into_iter().zip(t).map(|(name, micros)| StageTimes { name, micros }).collect())
+}
+
+pub fn markdown_table(stages: &[StageTimes]) -> String {
+    let mut s = String::from("| stage | mean (µs) | std dev (µs) |\n|---|---:|---:|\n");
+    for st in stages {
+        let (mean, std) = st.mean_std();
+        s += &format!("| {} | {:.2} | {:.2} |\n", st.name, mean, std);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_row_per_stage() {
+        let mesh = InputMesh { positions: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], normals: Vec::new(), uvs: Vec::new(), indices: vec![0, 1, 2] };
+        let stages = run_bench(&mesh, 3).unwrap();
+        assert!(stages.iter().all(|s| s.micros.len() == 3));
+        let table = markdown_table(&stages);
+        assert_eq!(table.lines().count(), 2 + 5);
+        assert!(table.lines().nth(6).unwrap().starts_with("| pack_interleave_permute | "));
+
+        let st = StageTimes { name: "x", micros: vec![1.0, 2.0, 3.0, 4.0] };
+        let (mean, std) = st.mean_std();
+        assert!((mean - 2.5).abs() < 1e-12 && (std - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+    }
+}
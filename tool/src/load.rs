@@ -0,0 +1,41 @@
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// 読み込んだメッシュ。ファイルにない法線/UVは空のまま
+pub struct InputMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+/// 拡張子 (.obj / .ply / .stl) から形式を決めて読む
+/// STLはファイルサイズが 84 + 50 * 三角形数 ならバイナリ、そうでなければASCIIとみなす
+pub fn load_mesh(path: &Path) -> Result<InputMesh> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    let open = || File::open(path).with_context(|| format!("failed to open {}", path.display()));
+    let mesh = match ext.as_deref() {
+        Some("obj") => {
+            let (positions, normals, uvs, indices) = meshguard_io::parse_obj(path)?;
+            InputMesh { positions, normals, uvs, indices }
+        }
+        Some("ply") => {
+            let m = meshguard_io::parse_ply(open()?).with_context(|| format!("failed to parse {}", path.display()))?;
+            InputMesh { positions: m.positions, normals: m.normals, uvs: m.uvs, indices: m.indices }
+        }
+        Some("stl") => {
+            let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+            let binary = data.len() >= 84 && data.len() as u64 == 84 + 50 * u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as u64;
+            let (positions, normals, indices) = if binary {
+                meshguard_io::parse_stl_binary(&data[..])
+            } else {
+                meshguard_io::parse_stl_ascii(BufReader::new(&data[..]), meshguard_io::DEFAULT_WELD_EPSILON)
+            }.with_context(|| format!("failed to parse {}", path.display()))?;
+            InputMesh { positions, normals, uvs: Vec::new(), indices }
+        }
+        _ => bail!("unsupported input format: {} (expected .obj, .ply or .stl)", path.display()),
+    };
+    Ok(mesh)
+}
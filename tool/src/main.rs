@@ -1,22 +1,180 @@
-use anyhow::Result;
-use meshguard_quantize::{quantize_positions, encode_normals_oct, quantize_uvs};
-use meshguard_pack::pack_interleave_permute;
+mod batch;
+mod bench;
+mod load;
+mod pipeline;
+mod stats;
+mod validate;
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use meshguard_pack::{compression_stats, PackedMesh};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(name = "meshguard-tool", about = "Quantize and pack a mesh into .mgb")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    pack: PackArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 座標のNaN/Inf、非多様体辺、縮退三角形、法線の長さを調べる。問題があれば終了コード1
+    Validate {
+        /// 入力メッシュ (.obj / .ply / .stl)
+        input: PathBuf,
+    },
+    /// 実メッシュでパイプラインの各段を計り、Markdownの表で出す
+    Bench {
+        /// 入力メッシュ (.obj / .ply / .stl)
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+    },
+    /// globに一致するファイルをまとめてpackし、`--output-dir` に.mgbを書き出す
+    Batch {
+        /// 入力のglob (例: "assets/**/*.obj")
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        output_dir: PathBuf,
+        #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u8).range(1..=16))]
+        bits: u8,
+        #[arg(long, default_value = "0xDEADBEEFCAFEBABE", value_parser = parse_seed)]
+        seed: u64,
+        /// 並列に処理するスレッド数
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: u32,
+    },
+}
+
+#[derive(Args)]
+struct PackArgs {
+    /// 入力メッシュ (.obj / .ply / .stl)
+    #[arg(long, required = true)]
+    input: Option<PathBuf>,
+    /// 書き出す.mgb。省略すると統計だけを表示する
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// 頂点置換のシード (10進数か0x付きの16進数)
+    #[arg(long, default_value = "0xDEADBEEFCAFEBABE", value_parser = parse_seed)]
+    seed: u64,
+    /// 座標の量子化ビット数 (1〜16)
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u8).range(1..=16))]
+    bits: u8,
+    /// 統計をJSONで標準出力に出す
+    #[arg(long)]
+    stats: bool,
+    /// 出力形式。threejsで `--output` がなければJSONを標準出力に出す
+    #[arg(long, value_enum, default_value_t = OutputFormat::Mgb)]
+    format: OutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Mgb,
+    /// Three.jsのBufferGeometry JSON
+    Threejs,
+}
+
+fn parse_seed(s: &str) -> Result<u64, String> {
+    let r = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(&hex.replace('_', ""), 16),
+        None => s.replace('_', "").parse(),
+    };
+    r.map_err(|e| format!("invalid seed {:?}: {}", s, e))
+}
+
+fn print_stats(mesh: &PackedMesh) {
+    let rows = [
+        ("vertex_count", mesh.vertex_count.to_string()),
+        ("triangle_count", (mesh.indices.len() / 3).to_string()),
+        ("interleaved_bytes", mesh.interleaved.len().to_string()),
+        ("index_bytes", stats::index_bytes(mesh).to_string()),
+        ("pos_scale", format!("{:?}", mesh.pos_scale)),
+        ("pos_offset", format!("{:?}", mesh.pos_offset)),
+        ("compression", compression_stats(mesh.vertex_count, mesh).to_string()),
+    ];
+    for (k, v) in rows {
+        println!("{:<18} {}", k, v);
+    }
+}
 
 fn main() -> Result<()> {
-    let pos = vec![[0.0, 1.0, 2.0],[10.0,20.0,30.0],[-1.0,0.5,100.0]];
-    let nor = vec![[0.0,0.0,1.0],[1.0,0.0,0.0],[0.577,0.577,0.577]];
-    let uv  = vec![[0.0,0.0],[0.5,0.75],[1.0,1.0]];
-    let idx = vec![0u32, 1, 2];
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Validate { input }) => run_validate(&input),
+        Some(Command::Bench { input, iterations }) => {
+            let mesh = load::load_mesh(&input)?;
+            print!("{}", bench::markdown_table(&bench::run_bench(&mesh, iterations as usize)?));
+            Ok(())
+        }
+        Some(Command::Batch { input, output_dir, bits, seed, jobs }) => run_batch(&input, &output_dir, bits, seed, jobs as usize),
+        None => run_pack(&cli.pack),
+    }
+}
 
-    let qpos = quantize_positions(&pos);
-    let qnor = encode_normals_oct(&nor);
-    let quv = quantize_uvs(&uv);
+fn run_validate(input: &Path) -> Result<()> {
+    let mesh = load::load_mesh(input)?;
+    let problems = validate::check_mesh(&mesh);
+    if problems.is_empty() {
+        println!("{}: ok ({} vertices, {} triangles)", input.display(), mesh.positions.len(), mesh.indices.len() / 3);
+        return Ok(());
+    }
+    eprintln!("{}: {} check(s) failed", input.display(), problems.len());
+    for p in &problems {
+        eprintln!("  - {}", p);
+    }
+    std::process::exit(1);
+}
 
-    let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&idx), 0xDEAD_BEEF_CAFE_BABE);
+fn run_batch(pattern: &str, output_dir: &Path, bits: u8, seed: u64, jobs: usize) -> Result<()> {
+    let inputs = batch::expand_inputs(pattern)?;
+    let results = batch::run_batch(&inputs, output_dir, bits, seed, jobs)?;
+    let ok: Vec<_> = results.iter().filter_map(|(_, r)| r.as_ref().ok()).collect();
+    print!("{}", batch::summary_table(&ok));
+    let failed: Vec<_> = results.iter().filter_map(|(p, r)| r.as_ref().err().map(|e| (p, e))).collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+    eprintln!("{} of {} files failed:", failed.len(), results.len());
+    for (p, e) in failed {
+        eprintln!("  - {}: {:#}", p.display(), e);
+    }
+    std::process::exit(1);
+}
 
-    println!("vertex_count: {}", packed.vertex_count);
-    println!("interleaved bytes: {}", packed.interleaved.len());
-    println!("indices: {:?}", packed.indices);
-    println!("pos scale={:?} offset={:?}", packed.pos_scale, packed.pos_offset);
+fn run_pack(cli: &PackArgs) -> Result<()> {
+    let input = cli.input.as_deref().context("--input is required")?;
+    let mesh = load::load_mesh(input)?;
+    let packed = pipeline::pack_mesh(&mesh, cli.bits, cli.seed)?;
+    let threejs = match cli.format {
+        OutputFormat::Threejs => Some(meshguard_io::export_threejs_json(&packed.mesh)?),
+        OutputFormat::Mgb => None,
+    };
+    if let Some(path) = &cli.output {
+        let mut file = BufWriter::new(File::create(path).with_context(|| format!("failed to create {}", path.display()))?);
+        match &threejs {
+            Some(json) => serde_json::to_writer(&mut file, json)?,
+            None => meshguard_io::write_mgb(&packed.mesh, &mut file)?,
+        }
+        // BufWriterはdrop時の書き込み失敗を捨てるので明示的にflushする
+        file.flush().with_context(|| format!("failed to write {}", path.display()))?;
+        eprintln!("wrote {} ({} vertices)", path.display(), packed.mesh.vertex_count);
+    }
+    if cli.stats {
+        println!("{}", stats::stats_json(&mesh, &packed)?);
+    } else if cli.output.is_none() {
+        match &threejs {
+            Some(json) => println!("{}", json),
+            None => print_stats(&packed.mesh),
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
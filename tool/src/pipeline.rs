@@ -0,0 +1,24 @@
+use crate::load::InputMesh;
+use anyhow::Result;
+use meshguard_pack::{pack_interleave_permute, PackedMesh};
+use meshguard_quantize::{encode_normals_oct, quantize_positions_with_bits, quantize_uvs, QuantizedNormalsOct, QuantizedPositions};
+
+/// pack結果と、統計に使う量子化済みの位置/法線
+pub struct Packed {
+    pub positions: QuantizedPositions,
+    pub normals: QuantizedNormalsOct,
+    pub mesh: PackedMesh,
+}
+
+/// 量子化してpackする。法線やUVがないメッシュはそれぞれ +Z と (0, 0) で埋める
+pub fn pack_mesh(mesh: &InputMesh, bits: u8, seed: u64) -> Result<Packed> {
+    let n = mesh.positions.len();
+    let normals = if mesh.normals.is_empty() { vec![[0.0, 0.0, 1.0]; n] } else { mesh.normals.clone() };
+    let uvs = if mesh.uvs.is_empty() { vec![[0.0, 0.0]; n] } else { mesh.uvs.clone() };
+
+    let qpos = quantize_positions_with_bits(&mesh.positions, bits)?;
+    let qnor = encode_normals_oct(&normals);
+    let quv = quantize_uvs(&uvs);
+    let packed = pack_interleave_permute(&qpos, &qnor, &quv, Some(&mesh.indices), seed)?;
+    Ok(Packed { positions: qpos, normals: qnor, mesh: packed })
+}
@@ -0,0 +1,31 @@
+use crate::load::InputMesh;
+use crate::pipeline::Packed;
+use anyhow::Result;
+use meshguard_pack::{compression_stats, IndexBuffer, PackedMesh};
+use meshguard_quantize::{max_normal_angle_error, psnr_positions, quantization_error};
+use serde_json::{json, Value};
+
+pub fn index_bytes(mesh: &PackedMesh) -> usize {
+    match &mesh.indices {
+        IndexBuffer::U16(v) => v.len() * 2,
+        IndexBuffer::U32(v) => v.len() * 4,
+    }
+}
+
+/// CI向けのJSON統計。誤差が0でPSNRが無限大のとき、入力に法線がないときは null
+pub fn stats_json(input: &InputMesh, packed: &Packed) -> Result<Value> {
+    let mesh = &packed.mesh;
+    let err = quantization_error(&input.positions, &packed.positions)?;
+    let psnr = psnr_positions(&input.positions, &packed.positions)?;
+    let normal_angle = if input.normals.is_empty() { None } else { Some(max_normal_angle_error(&input.normals, &packed.normals)?) };
+    Ok(json!({
+        "vertex_count": mesh.vertex_count,
+        "triangle_count": mesh.indices.len() / 3,
+        "interleaved_bytes": mesh.interleaved.len(),
+        "index_bytes": index_bytes(mesh),
+        "pos_psnr_db": if psnr.is_finite() { Some(psnr) } else { None },
+        "max_pos_error_m": err.max.iter().fold(0.0f32, |a, &b| a.max(b)),
+        "max_normal_angle_deg": normal_angle,
+        "compression_ratio": compression_stats(mesh.vertex_count, mesh).ratio,
+    }))
+}
@@ -0,0 +1,58 @@
+use crate::load::InputMesh;
+use meshguard_process::{find_non_manifold_edges, remove_degenerate_triangles, DEGENERATE_EPSILON};
+use meshguard_quantize::validate_positions;
+
+/// bake前のチェック。見つかった問題を人間向けの文で返す (空なら合格)
+pub fn check_mesh(mesh: &InputMesh) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Err(e) = validate_positions(&mesh.positions) {
+        problems.push(e.to_string());
+    }
+    let edges = find_non_manifold_edges(&mesh.indices);
+    if !edges.is_empty() {
+        let shown: Vec<String> = edges.iter().take(8).map(|(a, b)| format!("{}-{}", a, b)).collect();
+        let more = if edges.len() > shown.len() { ", ..." } else { "" };
+        problems.push(format!("{} non-manifold edges ({}{})", edges.len(), shown.join(", "), more));
+    }
+    // DEGENERATE_EPSILON は面積の2倍に対する閾値
+    match remove_degenerate_triangles(&mesh.positions, &mesh.indices, DEGENERATE_EPSILON * 0.5) {
+        Ok((_, _, 0)) => {}
+        Ok((_, _, degenerate)) => problems.push(format!("{} of {} triangles are degenerate", degenerate, mesh.indices.len() / 3)),
+        Err(e) => problems.push(e.to_string()),
+    }
+    if !mesh.normals.is_empty() {
+        let bad = mesh.normals.iter().filter(|n| !(0.99..=1.01).contains(&(n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt())).count();
+        if bad > 0 {
+            problems.push(format!("{:.2}% of normals ({} of {}) have length outside [0.99, 1.01]", 100.0 * bad as f64 / mesh.normals.len() as f64, bad, mesh.normals.len()));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_failed_check() {
+        let quad = InputMesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            uvs: Vec::new(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+        };
+        assert!(check_mesh(&quad).is_empty());
+
+        // 辺0-2を3枚目の三角形 (面積0) も共有し、法線の1本は長さ2
+        let mut bad = quad;
+        bad.indices.extend_from_slice(&[0, 2, 2]);
+        bad.normals[1] = [0.0, 0.0, 2.0];
+        bad.positions[3][1] = f32::NAN;
+        let problems = check_mesh(&bad);
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert_eq!(problems[0], "invalid input: NaN in position[3][1]");
+        assert!(problems[1].starts_with("1 non-manifold edges (0-2)"));
+        assert_eq!(problems[2], "1 of 3 triangles are degenerate");
+        assert!(problems[3].starts_with("25.00% of normals"));
+    }
+}